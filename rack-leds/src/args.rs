@@ -1,13 +1,14 @@
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
-use clap::Parser;
-use eyre::Result;
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::{OptionExt, Result};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
-use crate::config::Config;
+use crate::{collector::snmp::AuthProtocol, config::Config, ui::Colors};
 
 const DEFAULT_HTTP_SERVER_ADDR: SocketAddr =
     SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9753);
@@ -15,9 +16,12 @@ const DEFAULT_HTTP_SERVER_ADDR: SocketAddr =
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Display config
-    #[arg(short, long, value_name = "DISPLAY_CONFIG")]
-    config: PathBuf,
+    #[arg(short, long, value_name = "DISPLAY_CONFIG", global = true)]
+    config: Option<PathBuf>,
 
     /// Enable console-subscriber for use with tokyo-console
     #[arg(long)]
@@ -28,18 +32,59 @@ pub struct Args {
     pub headless: bool,
 
     /// Prometheus source
-    #[arg(short, long, value_name = "URL")]
-    pub source: String,
+    #[arg(short, long, value_name = "URL", global = true)]
+    source: Option<String>,
 
     /// Simulate updates (ignores --source)
     #[arg(long)]
     pub simulate: bool,
 
-    /// Prometheus refresh period
+    /// Scrape an OpenMetrics or Prometheus text endpoint directly, may be repeated (ignores
+    /// --source)
+    #[arg(long, value_name = "URL")]
+    pub scrape: Vec<String>,
+
+    /// Poll devices directly over SNMP (ignores --source)
+    #[arg(long)]
+    pub snmp: bool,
+
+    /// SNMPv2c community
+    #[arg(long, value_name = "COMMUNITY", default_value = "public")]
+    pub snmp_community: String,
+
+    /// SNMPv3 user, switches from SNMPv2c to SNMPv3
+    #[arg(long, value_name = "USER")]
+    pub snmp_user: Option<String>,
+
+    /// SNMPv3 authentication protocol
+    #[arg(long, value_name = "PROTOCOL", default_value = "sha")]
+    pub snmp_auth_protocol: AuthProtocol,
+
+    /// SNMPv3 authentication password
+    #[arg(long, value_name = "PASSWORD", requires = "snmp_user")]
+    pub snmp_auth_password: Option<String>,
+
+    /// SNMPv3 privacy (AES) password
+    #[arg(long, value_name = "PASSWORD", requires = "snmp_auth_password")]
+    pub snmp_privacy_password: Option<String>,
+
+    /// Record updates to FILE as newline-delimited JSON
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Replay updates recorded with --record (ignores --source)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["simulate", "snmp", "scrape", "record"])]
+    pub replay: Option<PathBuf>,
+
+    /// Replay speed, i.e. 2.0 replays twice as fast as recorded
+    #[arg(long, value_name = "FLOAT", default_value_t = 1.0)]
+    pub replay_speed: f64,
+
+    /// Prometheus, scrape or SNMP refresh period
     #[arg(long, value_name = "SECONDS", value_parser = secs)]
     period: Option<Duration>,
 
-    /// Prometheus query timeout in milliseconds
+    /// Prometheus query, scrape or SNMP request timeout in milliseconds
     #[arg(long, value_name = "MILLISECONDS", value_parser = millis)]
     timeout: Option<Duration>,
 
@@ -51,6 +96,14 @@ pub struct Args {
     )]
     pub server_address: SocketAddr,
 
+    /// Number of recent frames served as an animation from /recent.apng
+    #[arg(long, value_name = "FRAMES", value_parser = frames, default_value = "20")]
+    pub recent_frames: usize,
+
+    /// Seconds of per-device history shown in the TUI
+    #[arg(long, value_name = "SECONDS", value_parser = secs, default_value = "3600")]
+    pub history: Duration,
+
     /// Frame rate, i.e. number of frames per second
     #[arg(short, long, value_name = "FLOAT", default_value_t = 1.0)]
     pub frame_rate: f64,
@@ -58,15 +111,94 @@ pub struct Args {
     /// Tick rate, i.e. number of ticks per second
     #[arg(long, value_name = "FLOAT", default_value_t = 4.0)]
     pub tick_rate: f64,
+
+    /// Colours used to draw the LEDs in the UI
+    #[arg(long, value_name = "COLORS", default_value = "auto")]
+    pub colors: Colors,
+
+    /// Leave the mouse to the terminal so text can be selected, ports can't be clicked to inspect
+    #[arg(long)]
+    pub no_mouse: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Render the display once to a PNG and exit
+    Render(Render),
+
+    /// Render the display for each step of a time range from Prometheus range queries
+    RenderRange(RenderRange),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Render {
+    /// Output file, or - for stdout
+    #[arg(short, long, value_name = "PATH")]
+    pub out: PathBuf,
+
+    /// Display to render, defaults to the first display
+    #[arg(long, value_name = "NAME")]
+    pub display: Option<String>,
+}
+
+impl Render {
+    pub fn to_stdout(&self) -> bool {
+        self.out.as_os_str() == "-"
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct RenderRange {
+    /// Start time in RFC 3339 format, like 2024-10-31T01:00:00-07:00
+    #[arg(long, value_name = "TIME", value_parser = rfc3339)]
+    pub start: OffsetDateTime,
+
+    /// End time in RFC 3339 format
+    #[arg(long, value_name = "TIME", value_parser = rfc3339)]
+    pub end: OffsetDateTime,
+
+    /// Time between frames
+    #[arg(long, value_name = "SECONDS", value_parser = secs, default_value = "60")]
+    pub step: Duration,
+
+    /// Output file, or directory for a PNG sequence
+    #[arg(short, long, value_name = "PATH")]
+    pub out: PathBuf,
+
+    /// Display to render, defaults to the first display
+    #[arg(long, value_name = "NAME")]
+    pub display: Option<String>,
+
+    /// Output format
+    #[arg(long, value_name = "FORMAT", default_value = "apng")]
+    pub format: Format,
+
+    /// How long each frame of an animation is shown in milliseconds
+    #[arg(long, value_name = "MILLISECONDS", value_parser = millis, default_value = "200")]
+    pub frame_delay: Duration,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Format {
+    /// Animated PNG
+    Apng,
+    /// Numbered PNG files in a directory
+    Sequence,
 }
 
 impl Args {
     pub fn config(&self) -> Result<Config> {
-        let config = std::fs::read(self.config.clone())?;
+        Config::read(self.config_path()?)
+    }
 
-        let config = serde_json::from_slice(&config)?;
+    pub fn config_path(&self) -> Result<&Path> {
+        self.config.as_deref().ok_or_eyre("--config is required")
+    }
 
-        Ok(config)
+    pub fn source(&self) -> Result<&str> {
+        self.source
+            .as_deref()
+            .ok_or_eyre("--source is required to query Prometheus")
     }
 
     pub fn period(&self) -> Duration {
@@ -93,3 +225,15 @@ fn secs(secs: &str) -> Result<Duration, String> {
 
     Ok(Duration::from_secs(secs))
 }
+
+fn frames(frames: &str) -> Result<usize, String> {
+    match frames.parse() {
+        Ok(0) => Err("at least one frame is needed".to_string()),
+        Ok(frames) => Ok(frames),
+        Err(_) => Err(format!("{frames} isn't a valid number of frames")),
+    }
+}
+
+fn rfc3339(time: &str) -> Result<OffsetDateTime, String> {
+    OffsetDateTime::parse(time, &Rfc3339).map_err(|e| format!("{time} isn't an RFC 3339 time: {e}"))
+}
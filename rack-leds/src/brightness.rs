@@ -0,0 +1,245 @@
+use std::{fmt, mem::MaybeUninit, str::FromStr, time::SystemTime};
+
+use eyre::{bail, Context, Report, Result};
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, Time, UtcOffset};
+
+use crate::Framebuffer;
+
+/// The local UTC offset at `time`, from the system time zone
+///
+/// This is looked up for every time rather than once at startup so daylight saving changes apply
+/// without a restart.  UTC is used when the offset can't be found.
+#[cfg(unix)]
+pub fn local_offset_at(time: SystemTime) -> UtcOffset {
+    let seconds = OffsetDateTime::from(time).unix_timestamp() as libc::time_t;
+    let mut tm = MaybeUninit::<libc::tm>::uninit();
+
+    // SAFETY: `localtime_r` only writes to `tm`, which lives past the call, and the time zone
+    // environment is not changed after startup
+    let tm = unsafe { libc::localtime_r(&seconds, tm.as_mut_ptr()).as_ref() };
+
+    tm.and_then(|tm| UtcOffset::from_whole_seconds(tm.tm_gmtoff as i32).ok())
+        .unwrap_or(UtcOffset::UTC)
+}
+
+/// The local UTC offset at `time`, UTC when it can't be found
+#[cfg(not(unix))]
+pub fn local_offset_at(time: SystemTime) -> UtcOffset {
+    UtcOffset::local_offset_at(time.into()).unwrap_or(UtcOffset::UTC)
+}
+
+/// Post-processing for frames sent to LEDs
+///
+/// Each channel is gamma corrected then scaled by `level` and the level of the current
+/// [`Period`] of the `schedule`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Brightness {
+    /// From 0 (off) to 1 (full)
+    pub level: f32,
+    /// Gamma curve exponent, around 2.2 for LEDs, 1 leaves colors unchanged
+    pub gamma: f32,
+    /// Periods start at local times, following daylight saving changes as they happen
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<Period>,
+    /// Also adjust the display in the TUI
+    pub preview: bool,
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self {
+            level: 1.0,
+            gamma: 1.0,
+            schedule: vec![],
+            preview: false,
+        }
+    }
+}
+
+impl Brightness {
+    /// The level at `time` including the schedule
+    pub fn level_at(&self, time: SystemTime) -> f32 {
+        self.level_with(time, local_offset_at)
+    }
+
+    /// The level at `time` with the local UTC offset from `offset_at`
+    fn level_with(&self, time: SystemTime, offset_at: impl Fn(SystemTime) -> UtcOffset) -> f32 {
+        let now = OffsetDateTime::from(time).to_offset(offset_at(time)).time();
+
+        // The period that started most recently, wrapping around to yesterday's last period
+        let scheduled = self
+            .schedule
+            .iter()
+            .filter(|period| period.from.0 <= now)
+            .max_by_key(|period| period.from.0)
+            .or_else(|| self.schedule.iter().max_by_key(|period| period.from.0))
+            .map(|period| period.level)
+            .unwrap_or(1.0);
+
+        (self.level * scheduled).clamp(0.0, 1.0)
+    }
+
+    /// Adjust every pixel of `framebuffer` for `time`
+    pub fn apply(&self, framebuffer: &mut Framebuffer, time: SystemTime) {
+        let table = self.table(time);
+
+        framebuffer.map(|[r, g, b]| [table[r as usize], table[g as usize], table[b as usize]]);
+    }
+
+    /// Adjusted value of each channel value
+    fn table(&self, time: SystemTime) -> [u8; 256] {
+        let level = self.level_at(time);
+        let gamma = if self.gamma > 0.0 { self.gamma } else { 1.0 };
+
+        std::array::from_fn(|value| {
+            let value = (value as f32 / 255.0).powf(gamma) * level;
+
+            (value * 255.0).round() as u8
+        })
+    }
+}
+
+/// The brightness level from a time of day until the next period starts
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Period {
+    /// Local time in 24 hour `HH:MM` format
+    pub from: TimeOfDay,
+    /// Multiplies the overall level, 0 blanks the panel
+    pub level: f32,
+}
+
+/// A local time parsed from and written as `HH:MM`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(Time);
+
+impl FromStr for TimeOfDay {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((hour, minute)) = s.split_once(':') else {
+            bail!("Time {s} must be HH:MM");
+        };
+
+        let hour = hour
+            .parse()
+            .wrap_err_with(|| format!("Invalid hour in {s}"))?;
+        let minute = minute
+            .parse()
+            .wrap_err_with(|| format!("Invalid minute in {s}"))?;
+
+        let time = Time::from_hms(hour, minute, 0).wrap_err_with(|| format!("Invalid time {s}"))?;
+
+        Ok(Self(time))
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = Report;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0.hour(), self.0.minute())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    fn at(hour: u64, minute: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(hour * 3600 + minute * 60)
+    }
+
+    #[test]
+    fn apply() {
+        let brightness = Brightness {
+            level: 0.5,
+            gamma: 2.0,
+            ..Default::default()
+        };
+
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.set(0, 0, [255, 128, 0]);
+
+        brightness.apply(&mut framebuffer, at(12, 0));
+
+        assert_eq!(Some([128, 32, 0]), framebuffer.get(0, 0));
+    }
+
+    fn schedule() -> Brightness {
+        Brightness {
+            level: 0.8,
+            schedule: vec![
+                Period {
+                    from: "07:00".parse().unwrap(),
+                    level: 1.0,
+                },
+                Period {
+                    from: "19:30".parse().unwrap(),
+                    level: 0.5,
+                },
+                Period {
+                    from: "23:00".parse().unwrap(),
+                    level: 0.0,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn level_at() {
+        let brightness = schedule();
+        let utc = |_| UtcOffset::UTC;
+
+        assert_eq!(0.0, brightness.level_with(at(3, 0), utc));
+        assert_eq!(0.8, brightness.level_with(at(7, 0), utc));
+        assert_eq!(0.8, brightness.level_with(at(19, 29), utc));
+        assert_eq!(0.4, brightness.level_with(at(19, 30), utc));
+        assert_eq!(0.0, brightness.level_with(at(23, 59), utc));
+    }
+
+    #[test]
+    fn level_across_offset_change() {
+        let brightness = schedule();
+        // Clocks go forward an hour at 01:00 UTC
+        let offset_at = |time| {
+            if time < at(1, 0) {
+                UtcOffset::UTC
+            } else {
+                UtcOffset::from_hms(1, 0, 0).unwrap()
+            }
+        };
+
+        assert_eq!(0.0, brightness.level_with(at(0, 30), offset_at));
+        assert_eq!(0.0, brightness.level_with(at(5, 59), offset_at));
+        assert_eq!(0.8, brightness.level_with(at(6, 0), offset_at));
+        assert_eq!(0.4, brightness.level_with(at(18, 30), offset_at));
+    }
+
+    #[test]
+    fn time_of_day() {
+        let time: TimeOfDay = "7:05".parse().unwrap();
+
+        assert_eq!("07:05", time.to_string());
+
+        assert!("24:00".parse::<TimeOfDay>().is_err());
+        assert!("noon".parse::<TimeOfDay>().is_err());
+    }
+}
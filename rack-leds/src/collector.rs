@@ -1,12 +1,17 @@
 mod absolute;
 mod data;
 mod diff;
+mod history;
+mod metrics_source;
 pub mod prometheus;
+pub mod scrape;
+pub mod snmp;
+mod status;
 
 use std::{
     collections::HashMap,
     sync::Arc,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 pub use absolute::Absolute;
@@ -14,9 +19,15 @@ pub use data::Data;
 use deadpool::managed::Pool;
 pub use diff::Diff;
 use eyre::{eyre, Context, Result};
+pub use history::History;
+#[cfg(test)]
+pub use metrics_source::Fake;
+pub use metrics_source::MetricsSource;
 pub use prometheus::Prometheus;
+pub use scrape::Scraper;
+pub use status::{Round, Status};
 use tokio::{sync::watch, task::JoinSet, time};
-use tracing::{debug, error, info, instrument, trace};
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
     device::{Device, Id},
@@ -29,10 +40,18 @@ pub type UpdateSender = watch::Sender<(HashMap<Id, Update>, SystemTime)>;
 pub struct Collector {
     devices: Vec<Arc<Device>>,
     period: Duration,
-    pool: Pool<prometheus::Manager>,
+    source: Source,
+    status: Status,
     update_sender: UpdateSender,
 }
 
+/// Where the collector retrieves metrics from
+#[derive(Clone)]
+enum Source {
+    Prometheus(Pool<prometheus::Manager>),
+    Scrape(Arc<Scraper>),
+}
+
 impl Collector {
     pub fn new(args: &Args, devices: &Devices) -> Result<Self> {
         let (update_sender, _) = watch::channel((HashMap::default(), UNIX_EPOCH));
@@ -43,14 +62,30 @@ impl Collector {
 
         debug!("devices: {:#?}", devices);
 
-        let pool = Pool::builder(prometheus::Manager::new(args)?)
-            .build()
-            .wrap_err(format!("Unable to create pool for {}", args.source))?;
+        let source = if args.scrape.is_empty() {
+            let pool = Pool::builder(prometheus::Manager::new(args)?)
+                .build()
+                .wrap_err(format!("Unable to create pool for {}", args.source()?))?;
+
+            Source::Prometheus(pool)
+        } else {
+            let scraper = Scraper::new(&args.scrape, args.timeout())?;
+
+            scraper.compile(
+                devices
+                    .iter()
+                    .flat_map(|device| device.queries())
+                    .map(|(_, query)| query.get()),
+            )?;
+
+            Source::Scrape(Arc::new(scraper))
+        };
 
         Ok(Self {
             devices,
             period: args.period(),
-            pool,
+            source,
+            status: Status::new(args.period()),
             update_sender,
         })
     }
@@ -65,33 +100,63 @@ impl Collector {
         loop {
             interval.tick().await;
 
+            let started = Instant::now();
+
+            if let Source::Scrape(scraper) = &self.source {
+                scraper.scrape().await;
+            }
+
             debug!(count = self.devices.len(), "updating devices");
 
             let mut update_tasks = JoinSet::new();
 
             for device in self.devices.iter() {
                 let device = device.clone();
-                let pool = self.pool.clone();
+                let source = self.source.clone();
+                let status = self.status.clone();
 
                 update_tasks
                     .build_task()
                     .name(&format!("update {}", device))
-                    .spawn(async move { update(pool, device).await })?;
+                    .spawn(async move {
+                        let id = device.id();
+                        let started = Instant::now();
+
+                        let update = match source {
+                            Source::Prometheus(pool) => update(pool, device).await,
+                            Source::Scrape(scraper) => scrape_update(&scraper, device).await,
+                        };
+
+                        status.record(id, &update, started.elapsed());
+
+                        update
+                    })?;
             }
 
             let mut updates = HashMap::with_capacity(self.devices.len());
 
             while let Some(result) = update_tasks.join_next().await {
-                match result? {
-                    Ok(update) => {
+                match result {
+                    Ok(Ok(update)) => {
                         updates.insert(update.id(), update);
                     }
-                    Err(e) => error!(?e, "device update error"),
+                    Ok(Err(e)) => error!(?e, "device update error"),
+                    Err(e) => error!(?e, "device update task failed"),
                 }
             }
 
-            self.update_sender
-                .send_replace((updates, SystemTime::now()));
+            let pool = match &self.source {
+                Source::Prometheus(pool) => {
+                    let status = pool.status();
+
+                    Some((status.size, status.max_size))
+                }
+                Source::Scrape(_) => None,
+            };
+
+            self.status.record_round(started.elapsed(), pool);
+
+            send_updates(&self.update_sender, updates, self.devices.len());
         }
     }
 
@@ -104,20 +169,43 @@ impl Collector {
         Ok(())
     }
 
+    pub fn status(&self) -> Status {
+        self.status.clone()
+    }
+
     pub fn subscribe(&self) -> UpdateReceiver {
         self.update_sender.subscribe()
     }
 }
 
+/// Send `updates` from a round over `devices` devices, unless every device failed
+///
+/// Sending nothing lets the heartbeat go stale instead of counting an empty beat.
+fn send_updates(sender: &UpdateSender, updates: HashMap<Id, Update>, devices: usize) {
+    if updates.is_empty() && devices > 0 {
+        warn!("every device failed to update");
+        return;
+    }
+
+    sender.send_replace((updates, SystemTime::now()));
+}
+
 #[instrument(skip_all, err, fields(url = pool.manager().url(), device = %device.id()))]
 async fn update(pool: Pool<prometheus::Manager>, device: Arc<Device>) -> Result<Update> {
     trace!("updating");
 
     match pool.get().await {
-        Ok(conn) => device.update(&conn).await,
+        Ok(conn) => device.update(&*conn).await,
         Err(e) => Err(eyre!(e)).wrap_err(format!(
             "retrieving connection for {}",
             pool.manager().url()
         )),
     }
 }
+
+#[instrument(skip_all, err, fields(targets = scraper.targets(), device = %device.id()))]
+async fn scrape_update(scraper: &Scraper, device: Arc<Device>) -> Result<Update> {
+    trace!("updating");
+
+    device.update(scraper).await
+}
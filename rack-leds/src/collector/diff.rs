@@ -10,6 +10,14 @@ pub struct Diff<T> {
     inner: Arc<RwLock<Inner<T>>>,
 }
 
+impl<T: Default> Diff<T> {
+    /// Forget the previous and current values, the next difference is taken from the update
+    /// after
+    pub fn reset(&self) {
+        *self.inner.write().unwrap() = Inner::default();
+    }
+}
+
 impl Diff<u64> {
     pub fn difference(&self) -> u64 {
         let inner = self.inner.read().unwrap();
@@ -17,6 +25,11 @@ impl Diff<u64> {
         inner.difference()
     }
 
+    /// The latest value, before the difference is taken
+    pub fn current(&self) -> u64 {
+        self.inner.read().unwrap().current
+    }
+
     pub fn update(&self, update: u64) {
         let mut inner = self.inner.write().unwrap();
 
@@ -31,6 +44,11 @@ impl Diff<Vec<u64>> {
         inner.difference()
     }
 
+    /// The latest values, before the difference is taken
+    pub fn current(&self) -> Vec<u64> {
+        self.inner.read().unwrap().current.clone()
+    }
+
     pub fn len(&self) -> usize {
         let inner = self.inner.read().unwrap();
 
@@ -0,0 +1,323 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use eyre::Result;
+use tokio::task::JoinSet;
+use tracing::{info, instrument, trace};
+
+use crate::{collector::UpdateReceiver, device::Id, update::Scale, Update};
+
+/// Most snapshots kept, in case updates arrive much faster than expected
+const CAPACITY: usize = 3600;
+
+/// Updates of every device and when they were sent
+type Updates = (HashMap<Id, Update>, SystemTime);
+
+/// Recent updates of every device, oldest first, for sparklines and stepping back in the TUI
+///
+/// Snapshots older than `window` before the newest update are dropped.
+#[derive(Clone)]
+pub struct History {
+    snapshots: Arc<Mutex<Snapshots>>,
+    window: Duration,
+}
+
+#[derive(Default)]
+struct Snapshots {
+    kept: VecDeque<Updates>,
+    /// Snapshots dropped so far, the id of the oldest one kept
+    dropped: usize,
+}
+
+impl Snapshots {
+    fn get(&self, index: usize) -> Option<Snapshot> {
+        let (updates, time) = self.kept.get(index)?.clone();
+
+        Some(Snapshot {
+            updates,
+            time,
+            id: self.dropped + index,
+            index,
+            count: self.kept.len(),
+        })
+    }
+}
+
+/// Updates of every device as they were at one time
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub updates: HashMap<Id, Update>,
+    pub time: SystemTime,
+    /// Identifies the snapshot while it is kept
+    pub id: usize,
+    /// Position from the oldest snapshot
+    pub index: usize,
+    /// Snapshots in the history
+    pub count: usize,
+}
+
+/// Collected values of one port over time, oldest first
+#[derive(Debug, Default, PartialEq)]
+pub struct PortHistory {
+    pub times: Vec<SystemTime>,
+    /// Values for each scale the port is drawn with, one per time
+    pub rows: Vec<(Scale, Vec<u64>)>,
+}
+
+impl PortHistory {
+    /// The highest value of each row in each of `buckets` equal parts of the `window` ending at
+    /// `end`, oldest first
+    ///
+    /// Buckets without a value are 0.
+    pub fn downsample(
+        &self,
+        end: SystemTime,
+        window: Duration,
+        buckets: usize,
+    ) -> Vec<(Scale, Vec<u64>)> {
+        let start = end.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let bucket = |time: &SystemTime| {
+            let since = time.duration_since(start).ok()?;
+            let index = (since.as_secs_f64() / window.as_secs_f64() * buckets as f64) as usize;
+
+            Some(index.min(buckets.checked_sub(1)?))
+        };
+
+        self.rows
+            .iter()
+            .map(|(scale, values)| {
+                let mut downsampled = vec![0; buckets];
+
+                for (time, value) in self.times.iter().zip(values) {
+                    if let Some(max) = bucket(time).and_then(|index| downsampled.get_mut(index)) {
+                        *max = (*max).max(*value);
+                    }
+                }
+
+                (*scale, downsampled)
+            })
+            .collect()
+    }
+}
+
+impl History {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            snapshots: Default::default(),
+            window,
+        }
+    }
+
+    /// Add a snapshot of `updates`
+    pub fn push(&self, updates: &HashMap<Id, Update>, time: SystemTime) {
+        let oldest = time
+            .checked_sub(self.window)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut snapshots = self.snapshots.lock().unwrap();
+
+        if snapshots.kept.len() >= CAPACITY {
+            snapshots.kept.pop_front();
+            snapshots.dropped += 1;
+        }
+
+        snapshots.kept.push_back((updates.clone(), time));
+
+        while snapshots
+            .kept
+            .front()
+            .is_some_and(|(_, time)| *time < oldest)
+        {
+            snapshots.kept.pop_front();
+            snapshots.dropped += 1;
+        }
+    }
+
+    /// The newest snapshot
+    pub fn newest(&self) -> Option<Snapshot> {
+        let snapshots = self.snapshots.lock().unwrap();
+
+        snapshots.get(snapshots.kept.len().checked_sub(1)?)
+    }
+
+    /// The snapshot `id`, or `None` once it has been dropped
+    pub fn snapshot(&self, id: usize) -> Option<Snapshot> {
+        let snapshots = self.snapshots.lock().unwrap();
+
+        snapshots.get(id.checked_sub(snapshots.dropped)?)
+    }
+
+    /// The snapshot `steps` after snapshot `id`, stopping at the oldest and newest
+    pub fn step(&self, id: usize, steps: isize) -> Option<Snapshot> {
+        let snapshots = self.snapshots.lock().unwrap();
+
+        let index = id
+            .saturating_sub(snapshots.dropped)
+            .saturating_add_signed(steps)
+            .min(snapshots.kept.len().checked_sub(1)?);
+
+        snapshots.get(index)
+    }
+
+    /// Collected values of `port` of device `id` from every snapshot with an update for it
+    pub fn port(&self, id: Id, port: usize) -> PortHistory {
+        let snapshots = self.snapshots.lock().unwrap();
+        let mut history = PortHistory::default();
+
+        for (updates, time) in &snapshots.kept {
+            let Some(update) = updates.get(&id) else {
+                continue;
+            };
+
+            let values = update.values(port);
+
+            if history.rows.is_empty() {
+                history.rows = values.iter().map(|(scale, _)| (*scale, vec![])).collect();
+            }
+
+            history.times.push(*time);
+
+            for ((_, row), (_, value)) in history.rows.iter_mut().zip(values) {
+                row.push(value);
+            }
+        }
+
+        history
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    #[instrument(name = "history", skip_all, fields(window = ?self.window))]
+    pub async fn run(self, mut updates: UpdateReceiver) -> Result<()> {
+        info!("started");
+
+        loop {
+            updates.changed().await?;
+
+            let (updates, time) = &*updates.borrow_and_update();
+
+            self.push(updates, *time);
+
+            trace!(?time, "recorded history");
+        }
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>, updates: UpdateReceiver) -> Result<()> {
+        join_set
+            .build_task()
+            .name("history")
+            .spawn(async move { self.run(updates).await })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{update::Switch, Layout};
+
+    fn updates(id: Id, receive: u64) -> HashMap<Id, Update> {
+        HashMap::from([(
+            id,
+            Update::Switch {
+                id,
+                device: Switch::new(vec![0, receive], vec![0, 2], vec![0, 1])
+                    .with_rates(vec![0, receive * 10], vec![0, 20]),
+                layout: Layout::SwitchEight,
+            },
+        )])
+    }
+
+    #[test]
+    fn push() {
+        let history = History::new(Duration::from_secs(60));
+        let id: Id = serde_json::from_str("1").unwrap();
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        history.push(&updates(id, 10), start);
+        history.push(&updates(id, 20), start + Duration::from_secs(30));
+        history.push(&updates(id, 30), start + Duration::from_secs(61));
+
+        assert_eq!(
+            PortHistory {
+                times: vec![
+                    start + Duration::from_secs(30),
+                    start + Duration::from_secs(61)
+                ],
+                rows: vec![
+                    (Scale::Receive, vec![200, 300]),
+                    (Scale::Transmit, vec![20, 20]),
+                    (Scale::Poe, vec![1, 1]),
+                ],
+            },
+            history.port(id, 1)
+        );
+        assert_eq!(vec![0, 0], history.port(id, 0).rows[0].1);
+        assert_eq!(
+            PortHistory::default(),
+            history.port(serde_json::from_str("2").unwrap(), 1)
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let history = History::new(Duration::from_secs(60));
+        let id: Id = serde_json::from_str("1").unwrap();
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert!(history.newest().is_none());
+        assert!(history.step(0, 0).is_none());
+
+        for (seconds, receive) in [(0, 10), (30, 20), (61, 30), (70, 40), (70, 50)] {
+            history.push(&updates(id, receive), start + Duration::from_secs(seconds));
+        }
+
+        let receive = |snapshot: Snapshot| match &snapshot.updates[&id] {
+            Update::Switch { device, .. } => device.receive()[1],
+            Update::AccessPoint { .. } => unreachable!(),
+        };
+
+        let newest = history.newest().unwrap();
+        assert_eq!((4, 3, 4), (newest.id, newest.index, newest.count));
+        assert_eq!(start + Duration::from_secs(70), newest.time);
+
+        let previous = history.step(newest.id, -1).unwrap();
+        assert_eq!(40, receive(previous.clone()));
+        assert_eq!(30, receive(history.step(previous.id, -1).unwrap()));
+        assert_eq!(20, receive(history.step(previous.id, -5).unwrap()));
+        assert_eq!(50, receive(history.step(previous.id, 5).unwrap()));
+        assert_eq!(20, receive(history.snapshot(1).unwrap()));
+        assert!(history.snapshot(0).is_none());
+
+        history.push(&updates(id, 60), start + Duration::from_secs(140));
+
+        assert!(history.snapshot(previous.id).is_none());
+        assert_eq!(60, receive(history.step(previous.id, -1).unwrap()));
+    }
+
+    #[test]
+    fn downsample() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let at = |seconds| start + Duration::from_secs(seconds);
+
+        let port = PortHistory {
+            times: vec![at(0), at(10), at(20), at(50), at(59)],
+            rows: vec![(Scale::Receive, vec![1, 4, 2, 3, 5])],
+        };
+
+        assert_eq!(
+            vec![(Scale::Receive, vec![4, 2, 0, 5])],
+            port.downsample(at(60), Duration::from_secs(60), 4)
+        );
+        assert_eq!(
+            vec![(Scale::Receive, vec![])],
+            port.downsample(at(60), Duration::from_secs(60), 0)
+        );
+    }
+}
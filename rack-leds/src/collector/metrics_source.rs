@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod fake;
+
+use std::future::Future;
+
+use eyre::Result;
+#[cfg(test)]
+pub use fake::Fake;
+
+/// A source of metrics for device updates
+///
+/// Queries are in the language of the source, such as PromQL for [`Prometheus`].
+///
+/// [`Prometheus`]: crate::collector::Prometheus
+pub trait MetricsSource {
+    /// The value of `label` on the first series matching `query`
+    fn get_label(&self, query: &str, label: &str) -> impl Future<Output = Result<String>> + Send;
+
+    /// The values of every series matching `query`, ordered by the `ifIndex` label if present
+    fn get_values(&self, query: &str) -> impl Future<Output = Result<Vec<f64>>> + Send;
+
+    /// The values of every series matching `query` along with the value of `label`
+    fn get_values_with_label(
+        &self,
+        query: &str,
+        label: &str,
+    ) -> impl Future<Output = Result<Vec<(f64, Option<String>)>>> + Send;
+}
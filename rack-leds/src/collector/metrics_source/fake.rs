@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use eyre::{OptionExt, Result};
+use itertools::Itertools;
+
+use crate::collector::MetricsSource;
+
+type Series = (f64, Vec<(String, String)>);
+
+/// An in-memory [`MetricsSource`] returning the series inserted for each query
+#[derive(Default)]
+pub struct Fake {
+    series: HashMap<String, Vec<Series>>,
+}
+
+impl Fake {
+    /// Add a series with `value` and `labels` to the result of `query`
+    pub fn insert(&mut self, query: impl Into<String>, value: f64, labels: &[(&str, &str)]) {
+        let labels = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        self.series
+            .entry(query.into())
+            .or_default()
+            .push((value, labels));
+    }
+
+    fn series(&self, query: &str) -> &[Series] {
+        self.series
+            .get(query)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+fn label<'a>(labels: &'a [(String, String)], label: &str) -> Option<&'a String> {
+    labels.iter().find(|(k, _)| k == label).map(|(_, v)| v)
+}
+
+impl MetricsSource for Fake {
+    async fn get_label(&self, query: &str, name: &str) -> Result<String> {
+        let (_, labels) = self.series(query).first().ok_or_eyre("Nothing matched")?;
+
+        label(labels, name)
+            .cloned()
+            .ok_or_eyre(format!("Could not find label {name}"))
+    }
+
+    async fn get_values(&self, query: &str) -> Result<Vec<f64>> {
+        Ok(self
+            .series(query)
+            .iter()
+            .sorted_by_key(|(_, labels)| {
+                label(labels, "ifIndex")
+                    .and_then(|i| i.parse::<u64>().ok())
+                    .unwrap_or(0)
+            })
+            .map(|(value, _)| *value)
+            .collect())
+    }
+
+    async fn get_values_with_label(
+        &self,
+        query: &str,
+        name: &str,
+    ) -> Result<Vec<(f64, Option<String>)>> {
+        Ok(self
+            .series(query)
+            .iter()
+            .map(|(value, labels)| (*value, label(labels, name).cloned()))
+            .collect())
+    }
+}
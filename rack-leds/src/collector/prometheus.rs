@@ -1,14 +1,21 @@
 mod manager;
+mod range;
 
-use deadpool::managed::Object;
 use eyre::{Context, OptionExt, Result};
 use itertools::Itertools;
 pub use manager::Manager;
 use prometheus_http_query::{response::PromqlResult, Client};
-use std::fmt::Display;
+pub use range::Range;
+use std::{collections::HashMap, fmt::Display};
 use tracing::{debug, instrument, trace};
 
-pub type Connection = Object<Manager>;
+use crate::collector::MetricsSource;
+
+/// The labels of a series and its `(timestamp, value)` samples
+pub type Series = (HashMap<String, String>, Vec<(i64, f64)>);
+
+/// The labels of a series and its value
+pub type Sample = (HashMap<String, String>, f64);
 
 pub struct Prometheus {
     client: Client,
@@ -24,8 +31,87 @@ impl Prometheus {
         Ok(Self { client, timeout })
     }
 
+    /// The labels of every series matching `query`
+    #[instrument(skip_all, fields(%query))]
+    pub async fn get_labels(&self, query: impl Display) -> Result<Vec<HashMap<String, String>>> {
+        let labels: Vec<_> = self
+            .query(query)
+            .await?
+            .data()
+            .as_vector()
+            .ok_or_eyre("Non-vector query result")?
+            .iter()
+            .map(|v| v.metric().clone())
+            .collect();
+
+        trace!(?labels);
+
+        Ok(labels)
+    }
+
+    /// Every series matching `query` with samples every `step` seconds from `start` to `end`
+    #[instrument(skip_all, fields(%query, start, end, step))]
+    pub async fn get_range(
+        &self,
+        query: impl Display,
+        start: i64,
+        end: i64,
+        step: f64,
+    ) -> Result<Vec<Series>> {
+        let result = self
+            .client
+            .query_range(query, start, end, step)
+            .timeout(self.timeout)
+            .get()
+            .await?;
+
+        let series: Vec<_> = result
+            .data()
+            .as_matrix()
+            .ok_or_eyre("Non-matrix query result")?
+            .iter()
+            .map(|series| {
+                let samples = series
+                    .samples()
+                    .iter()
+                    .map(|sample| (sample.timestamp() as i64, sample.value()))
+                    .collect();
+
+                (series.metric().clone(), samples)
+            })
+            .collect();
+
+        trace!(?series);
+
+        Ok(series)
+    }
+
+    /// The labels and value of every series matching `query`
+    #[instrument(skip_all, fields(%query))]
+    pub async fn get_vector(&self, query: impl Display) -> Result<Vec<Sample>> {
+        let vector: Vec<_> = self
+            .query(query)
+            .await?
+            .data()
+            .as_vector()
+            .ok_or_eyre("Non-vector query result")?
+            .iter()
+            .map(|v| (v.metric().clone(), v.sample().value()))
+            .collect();
+
+        trace!(?vector);
+
+        Ok(vector)
+    }
+
+    async fn query(&self, query: impl Display) -> Result<PromqlResult> {
+        Ok(self.client.query(query).timeout(self.timeout).get().await?)
+    }
+}
+
+impl MetricsSource for Prometheus {
     #[instrument(skip_all, fields(%query, %label))]
-    pub async fn get_label(&self, query: impl Display, label: impl Display) -> Result<String> {
+    async fn get_label(&self, query: &str, label: &str) -> Result<String> {
         let result = self.query(query).await?;
 
         let value = result
@@ -36,7 +122,7 @@ impl Prometheus {
             .next()
             .ok_or_eyre("Nothing matched")?
             .metric()
-            .get(&label.to_string())
+            .get(label)
             .ok_or_eyre(format!("Could not find label {label}"))?;
 
         trace!(?value);
@@ -45,7 +131,7 @@ impl Prometheus {
     }
 
     #[instrument(skip_all, fields(%query))]
-    pub async fn get_values(&self, query: impl Display) -> Result<Vec<f64>> {
+    async fn get_values(&self, query: &str) -> Result<Vec<f64>> {
         let values: Vec<_> = self
             .query(query)
             .await?
@@ -69,13 +155,11 @@ impl Prometheus {
     }
 
     #[instrument(skip_all, fields(%query, %label))]
-    pub async fn get_values_with_label(
+    async fn get_values_with_label(
         &self,
-        query: impl Display,
-        label: impl Display,
-    ) -> Result<Vec<(u64, Option<String>)>> {
-        let label = label.to_string();
-
+        query: &str,
+        label: &str,
+    ) -> Result<Vec<(f64, Option<String>)>> {
         let values: Vec<_> = self
             .query(query)
             .await?
@@ -83,17 +167,13 @@ impl Prometheus {
             .as_vector()
             .ok_or_eyre("Non-vector query result")?
             .iter()
-            .map(|v| (v.sample().value() as u64, v.metric().get(&label).cloned()))
+            .map(|v| (v.sample().value(), v.metric().get(label).cloned()))
             .collect();
 
         trace!(?values);
 
         Ok(values)
     }
-
-    async fn query(&self, query: impl Display) -> Result<PromqlResult> {
-        Ok(self.client.query(query).timeout(self.timeout).get().await?)
-    }
 }
 
 impl std::fmt::Debug for Prometheus {
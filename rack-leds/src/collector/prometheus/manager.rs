@@ -21,10 +21,15 @@ impl Manager {
 
         Ok(Self {
             timeout,
-            url: args.source.clone(),
+            url: args.source()?.to_string(),
         })
     }
 
+    /// Create a client outside of the pool
+    pub fn client(&self) -> Result<Prometheus> {
+        Prometheus::new(&self.url, self.timeout)
+    }
+
     pub fn url(&self) -> &str {
         &self.url
     }
@@ -36,7 +41,7 @@ impl managed::Manager for Manager {
     type Error = Error;
 
     async fn create(&self) -> Result<Self::Type, Self::Error> {
-        Prometheus::new(&self.url, self.timeout)
+        self.client()
     }
 
     async fn recycle(
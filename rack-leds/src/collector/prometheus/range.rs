@@ -0,0 +1,174 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use eyre::{ensure, OptionExt, Result};
+use itertools::Itertools;
+use tokio::sync::Mutex;
+use tracing::{debug, instrument};
+
+use crate::collector::{prometheus::Series, MetricsSource, Prometheus};
+
+/// Range queries over a span of time, evaluated one step at a time
+///
+/// Each query is sent once as a range query covering every step.  [`Range::at`] returns a
+/// [`MetricsSource`] answering from the results at a single step.
+pub struct Range {
+    prometheus: Prometheus,
+    start: i64,
+    end: i64,
+    step: i64,
+    results: Mutex<HashMap<String, Arc<Vec<Series>>>>,
+}
+
+impl Range {
+    /// Query from `start` to `end`, in seconds since the Unix epoch, every `step`
+    pub fn new(prometheus: Prometheus, start: i64, end: i64, step: Duration) -> Result<Self> {
+        let step = step.as_secs() as i64;
+
+        ensure!(step > 0, "step must be at least one second");
+        ensure!(start <= end, "start must not be after end");
+
+        Ok(Self {
+            prometheus,
+            start,
+            end,
+            step,
+            results: Default::default(),
+        })
+    }
+
+    /// The time of each step
+    pub fn steps(&self) -> impl Iterator<Item = i64> {
+        (self.start..=self.end).step_by(self.step as usize)
+    }
+
+    /// A [`MetricsSource`] for the step at `time`
+    pub fn at(&self, time: i64) -> At<'_> {
+        At { range: self, time }
+    }
+
+    async fn series(&self, query: &str) -> Result<Arc<Vec<Series>>> {
+        let mut results = self.results.lock().await;
+
+        if let Some(series) = results.get(query) {
+            return Ok(series.clone());
+        }
+
+        let series = Arc::new(
+            self.prometheus
+                .get_range(query, self.start, self.end, self.step as f64)
+                .await?,
+        );
+
+        debug!(query, count = series.len(), "retrieved range");
+
+        results.insert(query.to_string(), series.clone());
+
+        Ok(series)
+    }
+}
+
+/// A [`MetricsSource`] for one step of a [`Range`]
+pub struct At<'a> {
+    range: &'a Range,
+    time: i64,
+}
+
+impl At<'_> {
+    /// Series with a sample at this step along with the sample value
+    async fn instant(&self, query: &str) -> Result<Vec<(HashMap<String, String>, f64)>> {
+        let series = self.range.series(query).await?;
+
+        Ok(series
+            .iter()
+            .filter_map(|(labels, samples)| {
+                let (_, value) = samples.iter().find(|(time, _)| *time == self.time)?;
+
+                Some((labels.clone(), *value))
+            })
+            .collect())
+    }
+}
+
+impl MetricsSource for At<'_> {
+    #[instrument(skip_all, fields(%query, %label, time = self.time))]
+    async fn get_label(&self, query: &str, label: &str) -> Result<String> {
+        let series = self.instant(query).await?;
+
+        let (labels, _) = series.first().ok_or_eyre("Nothing matched")?;
+
+        labels
+            .get(label)
+            .cloned()
+            .ok_or_eyre(format!("Could not find label {label}"))
+    }
+
+    #[instrument(skip_all, fields(%query, time = self.time))]
+    async fn get_values(&self, query: &str) -> Result<Vec<f64>> {
+        Ok(self
+            .instant(query)
+            .await?
+            .into_iter()
+            .sorted_by_key(|(labels, _)| {
+                labels
+                    .get("ifIndex")
+                    .and_then(|i| i.parse::<u64>().ok())
+                    .unwrap_or(0)
+            })
+            .map(|(_, value)| value)
+            .collect())
+    }
+
+    #[instrument(skip_all, fields(%query, %label, time = self.time))]
+    async fn get_values_with_label(
+        &self,
+        query: &str,
+        label: &str,
+    ) -> Result<Vec<(f64, Option<String>)>> {
+        Ok(self
+            .instant(query)
+            .await?
+            .into_iter()
+            .map(|(labels, value)| (value, labels.get(label).cloned()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn at() {
+        let prometheus = Prometheus::new("http://prometheus.invalid:9090", 100).unwrap();
+        let range = Range::new(prometheus, 1000, 1120, Duration::from_secs(60)).unwrap();
+
+        assert_eq!(vec![1000, 1060, 1120], range.steps().collect::<Vec<_>>());
+
+        let port = |index: &str| HashMap::from([("ifIndex".to_string(), index.to_string())]);
+
+        range.results.lock().await.insert(
+            "receive".into(),
+            Arc::new(vec![
+                (port("2"), vec![(1000, 20.0), (1060, 21.0), (1120, 22.0)]),
+                (port("1"), vec![(1000, 10.0), (1120, 12.0)]),
+            ]),
+        );
+
+        assert_eq!(
+            vec![10.0, 20.0],
+            range.at(1000).get_values("receive").await.unwrap()
+        );
+        assert_eq!(
+            vec![21.0],
+            range.at(1060).get_values("receive").await.unwrap()
+        );
+        assert_eq!(
+            "2",
+            range
+                .at(1120)
+                .get_label("receive", "ifIndex")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,309 @@
+mod exposition;
+mod query;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use eyre::{Context, OptionExt, Result};
+use futures::future::join_all;
+use itertools::Itertools;
+use reqwest::{header::ACCEPT, Client, Url};
+use tracing::{debug, instrument, trace, warn};
+
+use crate::collector::{
+    scrape::{
+        exposition::{Labels, Sample},
+        query::{Query, Snapshot},
+    },
+    MetricsSource,
+};
+
+const ACCEPT_EXPOSITION: &str =
+    "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5";
+
+/// Scrape OpenMetrics or Prometheus text endpoints and query them without a Prometheus server
+///
+/// Samples without an `instance` label are labeled with the `host:port` of their target, like
+/// Prometheus does.
+pub struct Scraper {
+    client: Client,
+    targets: Vec<Url>,
+    snapshots: RwLock<Snapshots>,
+    /// Parsed queries by their text so matchers are compiled once, not every period
+    queries: RwLock<HashMap<String, Arc<Query>>>,
+}
+
+#[derive(Default)]
+struct Snapshots {
+    current: Option<Snapshot>,
+    previous: Option<Snapshot>,
+}
+
+impl Scraper {
+    pub fn new(targets: &[String], timeout: Duration) -> Result<Self> {
+        let targets = targets
+            .iter()
+            .map(|target| Url::parse(target).wrap_err_with(|| format!("invalid target {target}")))
+            .collect::<Result<_>>()?;
+
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .wrap_err("Unable to create scrape client")?;
+
+        Ok(Self {
+            client,
+            targets,
+            snapshots: Default::default(),
+            queries: Default::default(),
+        })
+    }
+
+    /// Parse `queries` ahead of the first scrape, failing on the first invalid query
+    pub fn compile(&self, queries: impl IntoIterator<Item = String>) -> Result<()> {
+        for query in queries {
+            self.query(&query)?;
+        }
+
+        Ok(())
+    }
+
+    /// The parsed `query`, parsing it the first time it is used
+    fn query(&self, query: &str) -> Result<Arc<Query>> {
+        if let Some(parsed) = self.queries.read().unwrap().get(query) {
+            return Ok(parsed.clone());
+        }
+
+        let parsed =
+            Arc::new(Query::parse(query).wrap_err_with(|| format!("invalid query {query}"))?);
+
+        self.queries
+            .write()
+            .unwrap()
+            .insert(query.to_string(), parsed.clone());
+
+        Ok(parsed)
+    }
+
+    pub fn targets(&self) -> String {
+        self.targets.iter().join(", ")
+    }
+
+    /// Scrape every target, replacing the previous scrape
+    ///
+    /// Targets that fail are logged and left out of the scrape.
+    #[instrument(skip_all)]
+    pub async fn scrape(&self) {
+        let scrapes = join_all(self.targets.iter().map(|target| self.scrape_target(target))).await;
+
+        let samples = self
+            .targets
+            .iter()
+            .zip(scrapes)
+            .filter_map(|(target, scrape)| match scrape {
+                Ok(samples) => Some(samples),
+                Err(e) => {
+                    warn!(%target, ?e, "scrape failed");
+                    None
+                }
+            })
+            .flatten()
+            .collect();
+
+        self.record(Snapshot {
+            at: Instant::now(),
+            samples,
+        });
+    }
+
+    async fn scrape_target(&self, target: &Url) -> Result<Vec<Sample>> {
+        let text = self
+            .client
+            .get(target.clone())
+            .header(ACCEPT, ACCEPT_EXPOSITION)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let mut samples = exposition::parse(&text)?;
+
+        let instance = instance(target);
+
+        for sample in samples.iter_mut() {
+            sample
+                .labels
+                .entry("instance".into())
+                .or_insert_with(|| instance.clone());
+        }
+
+        debug!(%target, count = samples.len(), "scraped");
+
+        Ok(samples)
+    }
+
+    fn record(&self, snapshot: Snapshot) {
+        let mut snapshots = self.snapshots.write().unwrap();
+
+        snapshots.previous = snapshots.current.replace(snapshot);
+    }
+
+    fn evaluate(&self, query: &str) -> Result<Vec<(Labels, f64)>> {
+        let parsed = self.query(query)?;
+
+        let snapshots = self.snapshots.read().unwrap();
+
+        let current = snapshots.current.as_ref().ok_or_eyre("Nothing scraped")?;
+
+        let series = parsed.evaluate(current, snapshots.previous.as_ref());
+
+        trace!(?series);
+
+        Ok(series)
+    }
+}
+
+fn instance(target: &Url) -> String {
+    let host = target.host_str().unwrap_or_default();
+
+    match target.port_or_known_default() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+impl MetricsSource for Scraper {
+    #[instrument(skip_all, fields(%query, %label))]
+    async fn get_label(&self, query: &str, label: &str) -> Result<String> {
+        let series = self.evaluate(query)?;
+
+        let (labels, _) = series.first().ok_or_eyre("Nothing matched")?;
+
+        labels
+            .get(label)
+            .cloned()
+            .ok_or_eyre(format!("Could not find label {label}"))
+    }
+
+    #[instrument(skip_all, fields(%query))]
+    async fn get_values(&self, query: &str) -> Result<Vec<f64>> {
+        Ok(self
+            .evaluate(query)?
+            .into_iter()
+            .sorted_by_key(|(labels, _)| {
+                labels
+                    .get("ifIndex")
+                    .and_then(|i| i.parse::<u64>().ok())
+                    .unwrap_or(0)
+            })
+            .map(|(_, value)| value)
+            .collect())
+    }
+
+    #[instrument(skip_all, fields(%query, %label))]
+    async fn get_values_with_label(
+        &self,
+        query: &str,
+        label: &str,
+    ) -> Result<Vec<(f64, Option<String>)>> {
+        Ok(self
+            .evaluate(query)?
+            .into_iter()
+            .map(|(labels, value)| (value, labels.get(label).cloned()))
+            .collect())
+    }
+}
+
+impl std::fmt::Debug for Scraper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scraper")
+            .field("targets", &self.targets)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::device::Switch;
+
+    #[test]
+    fn instance_label() {
+        let target = Url::parse("http://nas.local:9100/metrics").unwrap();
+        assert_eq!("nas.local:9100", instance(&target));
+
+        let target = Url::parse("http://unpoller/metrics").unwrap();
+        assert_eq!("unpoller:80", instance(&target));
+    }
+
+    #[test]
+    fn compile() {
+        let scraper = Scraper::new(&[], Duration::from_secs(1)).unwrap();
+
+        scraper
+            .compile(["up".to_string(), "sum(up) by (instance)".to_string()])
+            .unwrap();
+        assert!(scraper.compile(["up{".to_string()]).is_err());
+
+        assert_eq!(2, scraper.queries.read().unwrap().len());
+        assert!(Arc::ptr_eq(
+            &scraper.query("up").unwrap(),
+            &scraper.query("up").unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn switch_update() {
+        let scraper = Scraper::new(&[], Duration::from_secs(1)).unwrap();
+
+        let interfaces = |octets: u64| {
+            let text = format!(
+                r#"
+ifHCInOctets{{instance="switch",ifAlias="Port 1",ifIndex="1"}} {octets}
+ifHCInOctets{{instance="switch",ifAlias="Port 2",ifIndex="2"}} {}
+ifHCOutOctets{{instance="switch",ifAlias="Port 1",ifIndex="1"}} 0
+ifHCOutOctets{{instance="switch",ifAlias="Port 2",ifIndex="2"}} 0
+"#,
+                octets * 2
+            );
+
+            exposition::parse(&text).unwrap()
+        };
+
+        let switch = Switch::new(
+            "switch",
+            "instance=\"switch\"",
+            "sum(rate(ifHCInOctets{instance=\"switch\", ifAlias=~\"(Port|SFP) .*\"}[1m])) by (ifIndex)",
+            "sum(rate(ifHCOutOctets{instance=\"switch\", ifAlias=~\"(Port|SFP) .*\"}[1m])) by (ifIndex)",
+            "unpoller_device_port_poe_amperes{instance=\"switch\"}",
+        );
+
+        let at = Instant::now();
+
+        scraper.record(Snapshot {
+            at,
+            samples: interfaces(0),
+        });
+        scraper.record(Snapshot {
+            at: at + Duration::from_secs(10),
+            samples: interfaces(100),
+        });
+
+        let update = switch.update(&scraper).await.unwrap();
+
+        assert_eq!(&vec![0, 0], update.receive());
+
+        scraper.record(Snapshot {
+            at: at + Duration::from_secs(20),
+            samples: interfaces(300),
+        });
+
+        let update = switch.update(&scraper).await.unwrap();
+
+        assert_eq!(&vec![10, 20], update.receive());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+use eyre::{bail, eyre, Context, OptionExt, Result};
+
+pub type Labels = BTreeMap<String, String>;
+
+/// A sample from the Prometheus text or OpenMetrics exposition format
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub labels: Labels,
+    pub value: f64,
+}
+
+/// Parse samples from a Prometheus text or OpenMetrics exposition
+///
+/// Metadata, timestamps and exemplars are ignored.
+pub fn parse(text: &str) -> Result<Vec<Sample>> {
+    text.lines()
+        .enumerate()
+        .map(|(number, line)| (number, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| sample(line).wrap_err_with(|| format!("line {}", number + 1)))
+        .collect()
+}
+
+fn sample(line: &str) -> Result<Sample> {
+    let mut chars = line.chars().peekable();
+
+    let name: String = take_while(&mut chars, |c| {
+        c.is_ascii_alphanumeric() || c == '_' || c == ':'
+    });
+
+    if name.is_empty() {
+        bail!("missing metric name");
+    }
+
+    let labels = if chars.peek() == Some(&'{') {
+        chars.next();
+        labels(&mut chars)?
+    } else {
+        Labels::new()
+    };
+
+    let rest: String = chars.collect();
+
+    let value = rest
+        .split_whitespace()
+        .next()
+        .ok_or_eyre(format!("missing value for {name}"))?;
+
+    let value = value
+        .parse()
+        .map_err(|_| eyre!("invalid value {value} for {name}"))?;
+
+    Ok(Sample {
+        name,
+        labels,
+        value,
+    })
+}
+
+fn labels(chars: &mut Peekable<Chars<'_>>) -> Result<Labels> {
+    let mut labels = Labels::new();
+
+    loop {
+        skip_whitespace(chars);
+
+        if chars.next_if_eq(&'}').is_some() {
+            return Ok(labels);
+        }
+
+        let name: String = take_while(chars, |c| c.is_ascii_alphanumeric() || c == '_');
+
+        skip_whitespace(chars);
+
+        if name.is_empty() || chars.next() != Some('=') {
+            bail!("invalid label {name}");
+        }
+
+        skip_whitespace(chars);
+
+        if chars.next() != Some('"') {
+            bail!("unquoted value for label {name}");
+        }
+
+        labels.insert(name, quoted(chars)?);
+
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => (),
+            Some('}') => return Ok(labels),
+            _ => bail!("unterminated labels"),
+        }
+    }
+}
+
+/// Read a double-quoted string after the opening quote
+pub fn quoted(chars: &mut impl Iterator<Item = char>) -> Result<String> {
+    let mut value = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(value),
+            Some('\\') => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some(c) => value.push(c),
+                None => break,
+            },
+            Some(c) => value.push(c),
+            None => break,
+        }
+    }
+
+    bail!("unterminated string")
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn take_while(chars: &mut Peekable<Chars<'_>>, f: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+
+    while let Some(c) = chars.next_if(|c| f(*c)) {
+        taken.push(c);
+    }
+
+    taken
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_text() {
+        let text = r#"
+# HELP ifHCInOctets The total number of octets received on the interface
+# TYPE ifHCInOctets counter
+ifHCInOctets{ifAlias="Port 1",ifIndex="1"} 1.2345e+06
+ifHCInOctets{ifAlias="Port \"2\"", ifIndex="2",} 17 1729000000000
+up 1
+node_load1 NaN
+"#;
+
+        let samples = parse(text).unwrap();
+
+        assert_eq!(4, samples.len());
+
+        assert_eq!("ifHCInOctets", samples[0].name);
+        assert_eq!(
+            Some(&"Port 1".to_string()),
+            samples[0].labels.get("ifAlias")
+        );
+        assert_eq!(1_234_500.0, samples[0].value);
+
+        assert_eq!(
+            Some(&"Port \"2\"".to_string()),
+            samples[1].labels.get("ifAlias")
+        );
+        assert_eq!(17.0, samples[1].value);
+
+        assert!(samples[2].labels.is_empty());
+        assert!(samples[3].value.is_nan());
+    }
+
+    #[test]
+    fn parse_open_metrics() {
+        let text = r#"# TYPE unpoller_device_radio_stations gauge
+unpoller_device_radio_stations{name="office",radio="na"} 4
+unpoller_device_bytes_total{name="office"} 100 # {trace_id="abc"} 1.0
+# EOF
+"#;
+
+        let samples = parse(text).unwrap();
+
+        assert_eq!(2, samples.len());
+        assert_eq!(100.0, samples[1].value);
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = parse("up 1\nbroken{name=\"x} 1\n").unwrap_err();
+
+        assert_eq!("line 2", error.to_string());
+    }
+}
@@ -0,0 +1,444 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use eyre::{bail, ensure, eyre, Result};
+use regex::Regex;
+
+use crate::collector::scrape::exposition::{self, Labels, Sample};
+
+/// Samples from one round of scrapes
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub at: Instant,
+    pub samples: Vec<Sample>,
+}
+
+/// A PromQL subset evaluated over scraped samples
+///
+/// Supports instant selectors with `=`, `!=`, `=~` and `!~` matchers, `rate()` of a range
+/// selector, and `sum()` and `count()` with an optional `by` clause.  `rate()` uses the last two
+/// scrapes, not the range.
+#[derive(Debug)]
+pub enum Query {
+    Selector(Selector),
+    Rate(Selector),
+    Sum { query: Box<Query>, by: Vec<String> },
+    Count { query: Box<Query>, by: Vec<String> },
+}
+
+#[derive(Debug)]
+pub struct Selector {
+    name: String,
+    matchers: Vec<Matcher>,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Equal(String, String),
+    NotEqual(String, String),
+    Match(String, Regex),
+    NotMatch(String, Regex),
+}
+
+impl Matcher {
+    fn matches(&self, labels: &Labels) -> bool {
+        let value = |label: &String| labels.get(label).map(String::as_str).unwrap_or_default();
+
+        match self {
+            Matcher::Equal(label, expected) => value(label) == expected,
+            Matcher::NotEqual(label, expected) => value(label) != expected,
+            Matcher::Match(label, regex) => regex.is_match(value(label)),
+            Matcher::NotMatch(label, regex) => !regex.is_match(value(label)),
+        }
+    }
+}
+
+impl Selector {
+    fn matches(&self, sample: &Sample) -> bool {
+        sample.name == self.name && self.matchers.iter().all(|m| m.matches(&sample.labels))
+    }
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            position: 0,
+        };
+
+        let parsed = parser.query()?;
+
+        ensure!(
+            parser.position == parser.tokens.len(),
+            "unexpected {:?} in {query}",
+            parser.tokens[parser.position]
+        );
+
+        Ok(parsed)
+    }
+
+    /// Evaluate the query against the `current` scrape and, for `rate()`, the `previous` scrape
+    pub fn evaluate(&self, current: &Snapshot, previous: Option<&Snapshot>) -> Vec<(Labels, f64)> {
+        match self {
+            Query::Selector(selector) => current
+                .samples
+                .iter()
+                .filter(|sample| selector.matches(sample))
+                .map(|sample| (sample.labels.clone(), sample.value))
+                .collect(),
+            Query::Rate(selector) => {
+                let Some(previous) = previous else {
+                    return vec![];
+                };
+
+                let elapsed = current
+                    .at
+                    .saturating_duration_since(previous.at)
+                    .as_secs_f64();
+
+                if elapsed == 0.0 {
+                    return vec![];
+                }
+
+                let before: BTreeMap<_, _> = previous
+                    .samples
+                    .iter()
+                    .filter(|sample| selector.matches(sample))
+                    .map(|sample| (&sample.labels, sample.value))
+                    .collect();
+
+                current
+                    .samples
+                    .iter()
+                    .filter(|sample| selector.matches(sample))
+                    .filter_map(|sample| {
+                        let before = before.get(&sample.labels)?;
+
+                        // A counter that went backwards was reset
+                        let increase = if sample.value < *before {
+                            sample.value
+                        } else {
+                            sample.value - before
+                        };
+
+                        Some((sample.labels.clone(), increase / elapsed))
+                    })
+                    .collect()
+            }
+            Query::Sum { query, by } => aggregate(query.evaluate(current, previous), by, |v| v),
+            Query::Count { query, by } => aggregate(query.evaluate(current, previous), by, |_| 1.0),
+        }
+    }
+}
+
+fn aggregate(
+    series: Vec<(Labels, f64)>,
+    by: &[String],
+    value: impl Fn(f64) -> f64,
+) -> Vec<(Labels, f64)> {
+    let mut groups: BTreeMap<Labels, f64> = BTreeMap::new();
+
+    for (labels, v) in series {
+        let group = labels
+            .into_iter()
+            .filter(|(label, _)| by.contains(label))
+            .collect();
+
+        *groups.entry(group).or_default() += value(v);
+    }
+
+    groups.into_iter().collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Duration(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 11] = ["!=", "=~", "!~", "=", "{", "}", "(", ")", "[", "]", ","];
+
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.peek().copied() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::String(exposition::quoted(&mut chars)?));
+        } else if c.is_ascii_alphabetic() || c == '_' || c == ':' {
+            let mut identifier = String::new();
+
+            while let Some(c) =
+                chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == ':')
+            {
+                identifier.push(c);
+            }
+
+            tokens.push(Token::Identifier(identifier));
+        } else if c.is_ascii_digit() {
+            let mut duration = String::new();
+
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric()) {
+                duration.push(c);
+            }
+
+            tokens.push(Token::Duration(duration));
+        } else {
+            let rest: String = chars.clone().take(2).collect();
+
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(*symbol))
+                .ok_or_else(|| eyre!("unexpected {c:?} in {query}"))?;
+
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        match self.next() {
+            Some(Token::Symbol(s)) if s == symbol => Ok(()),
+            token => bail!("expected {symbol:?}, found {token:?}"),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Identifier(identifier)) => Ok(identifier),
+            token => bail!("expected an identifier, found {token:?}"),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query> {
+        let name = self.identifier()?;
+
+        match name.as_str() {
+            "sum" | "count" if self.peek() != Some(&Token::Symbol("{")) => {
+                let mut by = self.by()?;
+
+                self.expect("(")?;
+                let query = Box::new(self.query()?);
+                self.expect(")")?;
+
+                if by.is_empty() {
+                    by = self.by()?;
+                }
+
+                Ok(if name == "sum" {
+                    Query::Sum { query, by }
+                } else {
+                    Query::Count { query, by }
+                })
+            }
+            "rate" if self.peek() == Some(&Token::Symbol("(")) => {
+                self.expect("(")?;
+                let name = self.identifier()?;
+                let selector = self.selector(name)?;
+
+                self.expect("[")?;
+                match self.next() {
+                    Some(Token::Duration(_)) => (),
+                    token => bail!("expected a range, found {token:?}"),
+                }
+                self.expect("]")?;
+                self.expect(")")?;
+
+                Ok(Query::Rate(selector))
+            }
+            _ => Ok(Query::Selector(self.selector(name)?)),
+        }
+    }
+
+    fn by(&mut self) -> Result<Vec<String>> {
+        if self.peek() != Some(&Token::Identifier("by".into())) {
+            return Ok(vec![]);
+        }
+
+        self.next();
+        self.expect("(")?;
+
+        let mut labels = vec![];
+
+        loop {
+            labels.push(self.identifier()?);
+
+            match self.next() {
+                Some(Token::Symbol(",")) => (),
+                Some(Token::Symbol(")")) => return Ok(labels),
+                token => bail!("expected \",\" or \")\", found {token:?}"),
+            }
+        }
+    }
+
+    fn selector(&mut self, name: String) -> Result<Selector> {
+        let mut matchers = vec![];
+
+        if self.peek() != Some(&Token::Symbol("{")) {
+            return Ok(Selector { name, matchers });
+        }
+
+        self.next();
+
+        loop {
+            if self.peek() == Some(&Token::Symbol("}")) {
+                self.next();
+                break;
+            }
+
+            let label = self.identifier()?;
+
+            let operator = match self.next() {
+                Some(Token::Symbol(operator @ ("=" | "!=" | "=~" | "!~"))) => operator,
+                token => bail!("expected a matcher for {label}, found {token:?}"),
+            };
+
+            let value = match self.next() {
+                Some(Token::String(value)) => value,
+                token => bail!("expected a string for {label}, found {token:?}"),
+            };
+
+            // Regular expressions are anchored like Prometheus
+            let regex = || Regex::new(&format!("^(?:{value})$"));
+
+            matchers.push(match operator {
+                "=" => Matcher::Equal(label, value),
+                "!=" => Matcher::NotEqual(label, value),
+                "=~" => Matcher::Match(label, regex()?),
+                _ => Matcher::NotMatch(label, regex()?),
+            });
+
+            match self.next() {
+                Some(Token::Symbol(",")) => (),
+                Some(Token::Symbol("}")) => break,
+                token => bail!("expected \",\" or \"}}\", found {token:?}"),
+            }
+        }
+
+        Ok(Selector { name, matchers })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn snapshot(at: Instant, text: &str) -> Snapshot {
+        Snapshot {
+            at,
+            samples: exposition::parse(text).unwrap(),
+        }
+    }
+
+    fn values(series: Vec<(Labels, f64)>) -> Vec<f64> {
+        series.into_iter().map(|(_, v)| v).collect()
+    }
+
+    const INTERFACES: &str = r#"
+ifHCInOctets{instance="switch",ifAlias="Port 1",ifIndex="1"} 1000
+ifHCInOctets{instance="switch",ifAlias="Port 2",ifIndex="2"} 2000
+ifHCInOctets{instance="switch",ifAlias="SFP 1",ifIndex="3"} 3000
+ifHCInOctets{instance="switch",ifAlias="switch0",ifIndex="4"} 4000
+ifHCInOctets{instance="other",ifAlias="Port 1",ifIndex="1"} 5000
+"#;
+
+    #[test]
+    fn selector() {
+        let current = snapshot(Instant::now(), INTERFACES);
+
+        let query =
+            Query::parse(r#"ifHCInOctets{instance="switch", ifAlias=~"(Port|SFP) .*"}"#).unwrap();
+
+        assert_eq!(
+            vec![1000.0, 2000.0, 3000.0],
+            values(query.evaluate(&current, None))
+        );
+
+        let query = Query::parse(r#"ifHCInOctets{instance!="switch"}"#).unwrap();
+
+        assert_eq!(vec![5000.0], values(query.evaluate(&current, None)));
+    }
+
+    #[test]
+    fn count() {
+        let current = snapshot(Instant::now(), INTERFACES);
+
+        let query =
+            Query::parse(r#"count(ifHCInOctets{instance="switch", ifAlias=~"(Port|SFP) .*"})"#)
+                .unwrap();
+
+        assert_eq!(vec![3.0], values(query.evaluate(&current, None)));
+    }
+
+    #[test]
+    fn sum_rate_by() {
+        let at = Instant::now();
+        let previous = snapshot(at, INTERFACES);
+        let current = snapshot(
+            at + Duration::from_secs(10),
+            &INTERFACES
+                .replace("} 1000", "} 1500")
+                .replace("} 2000", "} 100"),
+        );
+
+        let query = Query::parse(
+            r#"sum(rate(ifHCInOctets{instance="switch", ifAlias=~"(Port|SFP) .*"}[1m])) by (ifIndex)"#,
+        )
+        .unwrap();
+
+        assert!(query.evaluate(&current, None).is_empty());
+
+        let series = query.evaluate(&current, Some(&previous));
+
+        assert_eq!(
+            Some(&"1".to_string()),
+            series[0].0.get("ifIndex"),
+            "{series:?}"
+        );
+        assert_eq!(vec![50.0, 10.0, 0.0], values(series));
+
+        let query = Query::parse(r#"sum by (instance) (rate(ifHCInOctets[5m]))"#).unwrap();
+
+        assert_eq!(
+            vec![0.0, 60.0],
+            values(query.evaluate(&current, Some(&previous)))
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(Query::parse("rate(ifHCInOctets)").is_err());
+        assert!(Query::parse(r#"up{instance="a""#).is_err());
+        assert!(Query::parse("up + 1").is_err());
+        assert!(Query::parse("sum(up) by (instance").is_err());
+    }
+}
@@ -0,0 +1,701 @@
+mod ber;
+mod client;
+mod pdu;
+mod usm;
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use eyre::Result;
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinSet,
+    time,
+};
+use tracing::{debug, error, info, instrument, trace};
+pub use usm::AuthProtocol;
+
+use crate::{
+    collector::{
+        send_updates,
+        snmp::{
+            ber::Oid,
+            client::{Client, Security},
+            usm::Usm,
+        },
+        Status, UpdateReceiver, UpdateSender,
+    },
+    device::{AccessPoint, Device, Switch},
+    update, Args, Devices, Layout, Update,
+};
+
+/// SNMPv2-MIB sysDescr.0
+const SYS_DESCR: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 1, 0];
+/// IF-MIB ifName
+const IF_NAME: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 1];
+/// IF-MIB ifHCInOctets
+const IF_HC_IN_OCTETS: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 6];
+/// IF-MIB ifHCOutOctets
+const IF_HC_OUT_OCTETS: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 10];
+/// IF-MIB ifAlias
+const IF_ALIAS: &[u32] = &[1, 3, 6, 1, 2, 1, 31, 1, 1, 1, 18];
+/// ENTITY-MIB entPhysicalModelName
+const ENT_PHYSICAL_MODEL_NAME: &[u32] = &[1, 3, 6, 1, 2, 1, 47, 1, 1, 1, 1, 13];
+/// EdgeSwitch-POWER-ETHERNET-MIB agentPethOutputCurrent, milliamps drawn by each PoE port
+///
+/// POWER-ETHERNET-MIB only reports whether a port is delivering power, not how much.
+const AGENT_PETH_OUTPUT_CURRENT: &[u32] = &[1, 3, 6, 1, 4, 1, 4413, 1, 1, 15, 1, 1, 1, 3];
+/// UBNT-UniFi-MIB unifiRadioRadio
+const UNIFI_RADIO_RADIO: &[u32] = &[1, 3, 6, 1, 4, 1, 41112, 1, 6, 1, 1, 1, 2];
+/// UBNT-UniFi-MIB unifiRadioCuTotal, channel utilization percent
+const UNIFI_RADIO_CU_TOTAL: &[u32] = &[1, 3, 6, 1, 4, 1, 41112, 1, 6, 1, 1, 1, 6];
+/// UBNT-UniFi-MIB unifiVapNumStations
+const UNIFI_VAP_NUM_STATIONS: &[u32] = &[1, 3, 6, 1, 4, 1, 41112, 1, 6, 1, 2, 1, 8];
+/// UBNT-UniFi-MIB unifiVapRadio
+const UNIFI_VAP_RADIO: &[u32] = &[1, 3, 6, 1, 4, 1, 41112, 1, 6, 1, 2, 1, 9];
+
+/// UniFi names of the 2.4 GHz and 5 GHz radios
+const RADIOS: [&str; 2] = ["ng", "na"];
+
+/// Interfaces per request when retrieving counters
+const COUNTERS_PER_REQUEST: usize = 16;
+
+/// Interface names of the AP uplink, 2.4 GHz and 5 GHz radios, matching the Prometheus queries
+const ACCESS_POINT_INTERFACES: [&str; 3] = ["eth0", "wifi1", "wifi0"];
+
+/// Poll devices directly over SNMP instead of through Prometheus
+pub struct Snmp {
+    targets: Vec<Arc<Target>>,
+    period: Duration,
+    status: Status,
+    update_sender: UpdateSender,
+}
+
+impl Snmp {
+    pub fn new(args: &Args, devices: &Devices) -> Result<Self> {
+        let (update_sender, _) = watch::channel((HashMap::default(), UNIX_EPOCH));
+
+        let security = match &args.snmp_user {
+            Some(user) => Security::Usm(Usm::new(
+                user.clone(),
+                args.snmp_auth_password
+                    .clone()
+                    .map(|password| (args.snmp_auth_protocol, password)),
+                args.snmp_privacy_password.clone(),
+            )?),
+            None => Security::Community(args.snmp_community.clone()),
+        };
+
+        let mut targets: Vec<_> = devices
+            .devices()
+            .values()
+            .map(|device| Arc::new(Target::new(device.clone(), &security, args.timeout())))
+            .collect();
+
+        targets.sort_by_cached_key(|target| target.device.id());
+
+        debug!("targets: {:#?}", targets);
+
+        Ok(Self {
+            targets,
+            period: args.period(),
+            status: Status::new(args.period()),
+            update_sender,
+        })
+    }
+
+    #[instrument(name = "snmp", skip_all)]
+    pub async fn run(self) -> Result<()> {
+        let mut interval = time::interval(self.period);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+        info!(period = ?interval.period(), "started");
+
+        loop {
+            interval.tick().await;
+
+            let started = Instant::now();
+
+            debug!(count = self.targets.len(), "polling devices");
+
+            let mut poll_tasks = JoinSet::new();
+
+            for target in self.targets.iter() {
+                let target = target.clone();
+                let status = self.status.clone();
+
+                poll_tasks
+                    .build_task()
+                    .name(&format!("poll {}", target.device))
+                    .spawn(async move {
+                        let started = Instant::now();
+                        let update = target.poll().await;
+
+                        status.record(target.device.id(), &update, started.elapsed());
+
+                        update
+                    })?;
+            }
+
+            let mut updates = HashMap::with_capacity(self.targets.len());
+
+            while let Some(result) = poll_tasks.join_next().await {
+                match result {
+                    Ok(Ok(update)) => {
+                        updates.insert(update.id(), update);
+                    }
+                    Ok(Err(e)) => error!(?e, "device poll error"),
+                    Err(e) => error!(?e, "device poll task failed"),
+                }
+            }
+
+            self.status.record_round(started.elapsed(), None);
+
+            send_updates(&self.update_sender, updates, self.targets.len());
+        }
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>) -> Result<()> {
+        join_set
+            .build_task()
+            .name("snmp")
+            .spawn(async move { self.run().await })?;
+
+        Ok(())
+    }
+
+    pub fn status(&self) -> Status {
+        self.status.clone()
+    }
+
+    pub fn subscribe(&self) -> UpdateReceiver {
+        self.update_sender.subscribe()
+    }
+}
+
+/// A device polled over SNMP along with its connection and previous counters
+struct Target {
+    device: Arc<Device>,
+    security: Security,
+    timeout: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    client: Option<Client>,
+    counters: Option<Counters>,
+}
+
+/// Octet counters for a list of interfaces
+#[derive(Clone, Debug)]
+struct Counters {
+    at: Instant,
+    receive: Vec<u64>,
+    transmit: Vec<u64>,
+}
+
+impl Counters {
+    /// Receive and transmit rates in octets per second since `previous`
+    fn rates(&self, previous: Option<&Counters>) -> (Vec<u64>, Vec<u64>) {
+        let zero = || vec![0; self.receive.len()];
+
+        let Some(previous) = previous else {
+            return (zero(), zero());
+        };
+
+        let elapsed = self.at.saturating_duration_since(previous.at).as_secs_f64();
+
+        if elapsed == 0.0 {
+            return (zero(), zero());
+        }
+
+        let rate = |previous: &[u64], current: &[u64]| {
+            current
+                .iter()
+                .enumerate()
+                .map(|(i, current)| match previous.get(i) {
+                    // A counter that went backwards was reset
+                    Some(previous) => (current.saturating_sub(*previous) as f64 / elapsed) as u64,
+                    None => 0,
+                })
+                .collect()
+        };
+
+        (
+            rate(&previous.receive, &self.receive),
+            rate(&previous.transmit, &self.transmit),
+        )
+    }
+}
+
+impl Target {
+    fn new(device: Arc<Device>, security: &Security, timeout: Duration) -> Self {
+        Self {
+            device,
+            security: security.clone(),
+            timeout,
+            state: Default::default(),
+        }
+    }
+
+    fn address(&self) -> String {
+        match self.device.as_ref() {
+            Device::AccessPoint { device, .. } => device.address().to_string(),
+            Device::Switch { device, .. } => device.address(),
+        }
+    }
+
+    #[instrument(skip_all, err, fields(device = %self.device.id(), address = self.address()))]
+    async fn poll(&self) -> Result<Update> {
+        trace!("polling");
+
+        let mut state = self.state.lock().await;
+
+        let mut client = match state.client.take() {
+            Some(client) => client,
+            None => Client::connect(&self.address(), self.security.clone(), self.timeout).await?,
+        };
+
+        let update = match self.device.as_ref() {
+            Device::AccessPoint { id, device } => {
+                poll_access_point(&mut client, device, &mut state.counters)
+                    .await
+                    .map(|device| Update::AccessPoint {
+                        id: *id,
+                        device,
+                        layout: Layout::AccessPoint,
+                    })
+            }
+            Device::Switch { id, device } => poll_switch(&mut client, device, &mut state.counters)
+                .await
+                .map(|(layout, device)| Update::Switch {
+                    id: *id,
+                    device,
+                    layout,
+                }),
+        };
+
+        // Reconnect after errors so the agent address and SNMPv3 engine are rediscovered
+        if update.is_ok() {
+            state.client = Some(client);
+        }
+
+        update
+    }
+}
+
+impl std::fmt::Debug for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Target")
+            .field("device", &self.device)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+async fn poll_access_point(
+    client: &mut Client,
+    access_point: &AccessPoint,
+    previous: &mut Option<Counters>,
+) -> Result<update::AccessPoint> {
+    let names: HashMap<_, _> = client
+        .walk(&IF_NAME.into())
+        .await?
+        .into_iter()
+        .filter_map(|(oid, value)| Some((value.as_string()?, oid.index_after(&IF_NAME.into())?)))
+        .collect();
+
+    // Index 0 is never valid so missing interfaces read as zero
+    let interfaces: Vec<_> = ACCESS_POINT_INTERFACES
+        .iter()
+        .map(|name| names.get(*name).copied().unwrap_or(0))
+        .collect();
+
+    let (receive, transmit) = rates(client, &interfaces, previous).await?;
+
+    // Channel utilization and stations aren't in the standard MIBs
+    let [utilization_24_ghz, utilization_5_ghz] =
+        per_radio(client, UNIFI_RADIO_RADIO, UNIFI_RADIO_CU_TOTAL).await?;
+    let stations = per_radio(client, UNIFI_VAP_RADIO, UNIFI_VAP_NUM_STATIONS).await?;
+
+    Ok(access_point.record(
+        [
+            utilization_24_ghz as f64 / 100.0,
+            utilization_5_ghz as f64 / 100.0,
+        ],
+        [receive[0], receive[1], receive[2]],
+        stations,
+        [transmit[0], transmit[1], transmit[2]],
+    ))
+}
+
+async fn poll_switch(
+    client: &mut Client,
+    switch: &Switch,
+    previous: &mut Option<Counters>,
+) -> Result<(Layout, update::Switch)> {
+    let ports: Vec<_> = client
+        .walk(&IF_ALIAS.into())
+        .await?
+        .into_iter()
+        .filter_map(|(oid, value)| {
+            let alias = value.as_string()?;
+
+            (alias.starts_with("Port ") || alias.starts_with("SFP "))
+                .then(|| oid.index_after(&IF_ALIAS.into()))
+                .flatten()
+        })
+        .collect();
+
+    let (receive, transmit) = rates(client, &ports, previous).await?;
+
+    let poe = client
+        .walk(&AGENT_PETH_OUTPUT_CURRENT.into())
+        .await?
+        .into_iter()
+        .filter_map(|(oid, value)| {
+            // Indexed by pethPsePortGroupIndex.pethPsePortIndex
+            let &[_, port] = oid.suffix(&AGENT_PETH_OUTPUT_CURRENT.into())? else {
+                return None;
+            };
+
+            Some((port.checked_sub(1)? as usize, value.as_u64()?))
+        })
+        .collect();
+
+    let layout = layout(client, ports.len()).await?;
+
+    Ok((layout, switch.record(receive, transmit, poe)))
+}
+
+async fn layout(client: &mut Client, ports: usize) -> Result<Layout> {
+    let description = client
+        .get(&[SYS_DESCR.into()])
+        .await?
+        .first()
+        .and_then(|(_, value)| value.as_string())
+        .unwrap_or_default();
+
+    if let Some(layout) = Layout::described(&description) {
+        return Ok(layout);
+    }
+
+    let model = client
+        .walk(&ENT_PHYSICAL_MODEL_NAME.into())
+        .await?
+        .iter()
+        .find_map(|(_, value)| Layout::described(&value.as_string()?));
+
+    Ok(model.unwrap_or_else(|| Layout::ports(ports)))
+}
+
+/// Sum `values` for each of [`RADIOS`], matching rows to the radio named in the `radios` column
+///
+/// Agents without the UniFi MIB have no rows so every radio reads zero.
+async fn per_radio(client: &mut Client, radios: &[u32], values: &[u32]) -> Result<[u64; 2]> {
+    let radios: HashMap<_, _> = client
+        .walk(&radios.into())
+        .await?
+        .into_iter()
+        .filter_map(|(oid, value)| Some((oid.index_after(&radios.into())?, value.as_string()?)))
+        .collect();
+
+    let mut totals = [0; 2];
+
+    for (oid, value) in client.walk(&values.into()).await? {
+        let radio = oid
+            .index_after(&values.into())
+            .and_then(|index| radios.get(&index))
+            .and_then(|name| RADIOS.iter().position(|radio| radio == name));
+
+        if let Some(radio) = radio {
+            totals[radio] += value.as_u64().unwrap_or(0);
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Retrieve octet counters for `interfaces` and convert them to rates since the `previous` poll
+async fn rates(
+    client: &mut Client,
+    interfaces: &[u32],
+    previous: &mut Option<Counters>,
+) -> Result<(Vec<u64>, Vec<u64>)> {
+    let mut receive = Vec::with_capacity(interfaces.len());
+    let mut transmit = Vec::with_capacity(interfaces.len());
+
+    for chunk in interfaces.chunks(COUNTERS_PER_REQUEST) {
+        let oids: Vec<Oid> = chunk
+            .iter()
+            .flat_map(|index| {
+                [
+                    Oid::from(IF_HC_IN_OCTETS).with(*index),
+                    Oid::from(IF_HC_OUT_OCTETS).with(*index),
+                ]
+            })
+            .collect();
+
+        for pair in client.get(&oids).await?.chunks(2) {
+            let value = |i: usize| pair.get(i).and_then(|(_, v)| v.as_u64()).unwrap_or(0);
+
+            receive.push(value(0));
+            transmit.push(value(1));
+        }
+    }
+
+    let counters = Counters {
+        at: Instant::now(),
+        receive,
+        transmit,
+    };
+
+    let rates = counters.rates(previous.as_ref());
+
+    *previous = Some(counters);
+
+    Ok(rates)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        net::SocketAddr,
+        ops::Bound,
+        sync::{Arc, Mutex},
+    };
+
+    use tokio::net::UdpSocket;
+
+    use super::*;
+    use crate::collector::snmp::{
+        ber::Value,
+        pdu::{self, Pdu},
+    };
+
+    type Mib = Arc<Mutex<BTreeMap<Oid, Value>>>;
+
+    /// An SNMPv2c agent stand-in answering Get and GetBulk requests from `mib`
+    async fn agent(mib: Mib) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 65535];
+
+            loop {
+                let (length, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let (community, request) =
+                    pdu::decode_community_message(&buffer[..length]).unwrap();
+
+                let mib = mib.lock().unwrap().clone();
+
+                let varbinds = match request.tag {
+                    pdu::GET => request
+                        .varbinds
+                        .iter()
+                        .map(|(oid, _)| {
+                            let value = mib.get(oid).cloned().unwrap_or(Value::NoSuchInstance);
+
+                            (oid.clone(), value)
+                        })
+                        .collect(),
+                    pdu::GET_BULK => request
+                        .varbinds
+                        .iter()
+                        .flat_map(|(oid, _)| {
+                            let mut next: Vec<_> = mib
+                                .range((Bound::Excluded(oid.clone()), Bound::Unbounded))
+                                .take(request.error_index as usize)
+                                .map(|(oid, value)| (oid.clone(), value.clone()))
+                                .collect();
+
+                            if next.len() < request.error_index as usize {
+                                let last = next.last().map_or(oid.clone(), |(oid, _)| oid.clone());
+                                next.push((last, Value::EndOfMibView));
+                            }
+
+                            next
+                        })
+                        .collect(),
+                    tag => panic!("unexpected PDU {tag:#04x}"),
+                };
+
+                let response = Pdu::response(request.request_id, varbinds);
+                let message = pdu::community_message(&community, &response);
+
+                socket.send_to(&message, peer).await.unwrap();
+            }
+        });
+
+        address
+    }
+
+    fn switch_mib(in_octets: u64) -> BTreeMap<Oid, Value> {
+        let mut mib = BTreeMap::new();
+
+        let string = |s: &str| Value::OctetString(s.as_bytes().to_vec());
+
+        mib.insert(SYS_DESCR.into(), string("Linux switch 4.4.153"));
+        mib.insert(
+            Oid::from(ENT_PHYSICAL_MODEL_NAME).with(1),
+            string("USW-8-150W"),
+        );
+
+        for index in 1..=10 {
+            let alias = if index <= 8 {
+                format!("Port {index}")
+            } else {
+                format!("SFP {}", index - 8)
+            };
+
+            mib.insert(Oid::from(IF_ALIAS).with(index), string(&alias));
+            mib.insert(
+                Oid::from(IF_HC_IN_OCTETS).with(index),
+                Value::Counter64(in_octets * index as u64),
+            );
+            mib.insert(Oid::from(IF_HC_OUT_OCTETS).with(index), Value::Counter64(0));
+        }
+
+        // The CPU interface isn't a port
+        mib.insert(Oid::from(IF_ALIAS).with(11), string("switch0"));
+
+        for port in 1..=8 {
+            let milliamps = if port == 2 { 120 } else { 0 };
+
+            mib.insert(
+                Oid::from(AGENT_PETH_OUTPUT_CURRENT).with(1).with(port),
+                Value::Gauge32(milliamps),
+            );
+        }
+
+        mib
+    }
+
+    #[tokio::test]
+    async fn client_walk() {
+        let mib = Arc::new(Mutex::new(switch_mib(100)));
+        let address = agent(mib).await;
+
+        let mut client = Client::connect(
+            &address.to_string(),
+            Security::Community("public".into()),
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+
+        let aliases = client.walk(&IF_ALIAS.into()).await.unwrap();
+
+        assert_eq!(11, aliases.len());
+        assert_eq!(Some(1), aliases[0].0.index_after(&IF_ALIAS.into()));
+        assert_eq!(Some("switch0".into()), aliases[10].1.as_string());
+
+        let description = client.get(&[SYS_DESCR.into()]).await.unwrap();
+
+        assert_eq!(
+            Some("Linux switch 4.4.153".into()),
+            description[0].1.as_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn poll_switch() {
+        let mib = Arc::new(Mutex::new(switch_mib(100)));
+        let address = agent(mib.clone()).await;
+
+        let device: Device = crate::config::Device::switch(address.to_string()).into();
+        let target = Target::new(
+            Arc::new(device),
+            &Security::Community("public".into()),
+            Duration::from_secs(1),
+        );
+
+        let Update::Switch { layout, device, .. } = target.poll().await.unwrap() else {
+            panic!("expected a switch update");
+        };
+
+        assert!(matches!(layout, Layout::SwitchEightPlusTwo));
+        assert_eq!(&vec![0; 10], device.receive());
+        assert_eq!(&vec![0; 10], device.transmit());
+        assert_eq!(&vec![0, 120, 0, 0, 0, 0, 0, 0, 0, 0], device.poe());
+
+        *mib.lock().unwrap() = switch_mib(200);
+
+        let Update::Switch { device, .. } = target.poll().await.unwrap() else {
+            panic!("expected a switch update");
+        };
+
+        assert!(device.receive().iter().all(|rate| *rate > 0));
+        assert!(device.receive().windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn poll_access_point() {
+        let string = |s: &str| Value::OctetString(s.as_bytes().to_vec());
+        let mut mib = BTreeMap::new();
+
+        for (index, name) in [(1, "eth0"), (2, "wifi0"), (3, "wifi1")] {
+            mib.insert(Oid::from(IF_NAME).with(index), string(name));
+            mib.insert(Oid::from(IF_HC_IN_OCTETS).with(index), Value::Counter64(0));
+            mib.insert(Oid::from(IF_HC_OUT_OCTETS).with(index), Value::Counter64(0));
+        }
+
+        for (index, radio, utilization) in [(1, "ng", 40), (2, "na", 15)] {
+            mib.insert(Oid::from(UNIFI_RADIO_RADIO).with(index), string(radio));
+            mib.insert(
+                Oid::from(UNIFI_RADIO_CU_TOTAL).with(index),
+                Value::Integer(utilization),
+            );
+        }
+
+        for (index, radio, stations) in [(1, "ng", 2), (2, "na", 5), (3, "na", 1)] {
+            mib.insert(Oid::from(UNIFI_VAP_RADIO).with(index), string(radio));
+            mib.insert(
+                Oid::from(UNIFI_VAP_NUM_STATIONS).with(index),
+                Value::Gauge32(stations),
+            );
+        }
+
+        let address = agent(Arc::new(Mutex::new(mib))).await;
+
+        let device: Device = crate::config::Device::access_point(address.to_string(), "ap").into();
+        let target = Target::new(
+            Arc::new(device),
+            &Security::Community("public".into()),
+            Duration::from_secs(1),
+        );
+
+        let Update::AccessPoint { device, .. } = target.poll().await.unwrap() else {
+            panic!("expected an access point update");
+        };
+
+        assert_eq!(vec![40, 15], device.channel_utilization());
+        assert_eq!(vec![2, 6], device.stations());
+    }
+
+    #[test]
+    fn rates() {
+        let at = Instant::now();
+
+        let previous = Counters {
+            at,
+            receive: vec![100, 500],
+            transmit: vec![0, 0],
+        };
+
+        let current = Counters {
+            at: at + Duration::from_secs(10),
+            receive: vec![1100, 400],
+            transmit: vec![50, 100],
+        };
+
+        assert_eq!((vec![0, 0], vec![0, 0]), current.rates(None));
+        assert_eq!((vec![100, 0], vec![5, 10]), current.rates(Some(&previous)));
+    }
+}
@@ -0,0 +1,451 @@
+use std::{fmt::Display, str::FromStr};
+
+use eyre::{bail, ensure, eyre, OptionExt, Result};
+
+pub const INTEGER: u8 = 0x02;
+pub const OCTET_STRING: u8 = 0x04;
+pub const NULL: u8 = 0x05;
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+pub const IP_ADDRESS: u8 = 0x40;
+pub const COUNTER32: u8 = 0x41;
+pub const GAUGE32: u8 = 0x42;
+pub const TIMETICKS: u8 = 0x43;
+pub const OPAQUE: u8 = 0x44;
+pub const COUNTER64: u8 = 0x46;
+pub const NO_SUCH_OBJECT: u8 = 0x80;
+pub const NO_SUCH_INSTANCE: u8 = 0x81;
+pub const END_OF_MIB_VIEW: u8 = 0x82;
+
+/// An SNMP object identifier
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Oid(Vec<u32>);
+
+impl Oid {
+    /// The sub-identifier following `prefix`, used as the table index for single-index tables
+    pub fn index_after(&self, prefix: &Oid) -> Option<u32> {
+        self.suffix(prefix)?.first().copied()
+    }
+
+    pub fn is_child_of(&self, parent: &Oid) -> bool {
+        self.0.len() > parent.0.len() && self.0.starts_with(&parent.0)
+    }
+
+    /// Sub-identifiers following `prefix`
+    pub fn suffix(&self, prefix: &Oid) -> Option<&[u32]> {
+        self.0.strip_prefix(prefix.0.as_slice())
+    }
+
+    pub fn with(&self, sub_identifier: u32) -> Self {
+        let mut oid = self.0.clone();
+        oid.push(sub_identifier);
+
+        Self(oid)
+    }
+}
+
+impl From<&[u32]> for Oid {
+    fn from(oid: &[u32]) -> Self {
+        Self(oid.to_vec())
+    }
+}
+
+impl Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let oid: Vec<_> = self.0.iter().map(|s| s.to_string()).collect();
+
+        f.write_str(&oid.join("."))
+    }
+}
+
+impl FromStr for Oid {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let oid = s
+            .trim_start_matches('.')
+            .split('.')
+            .map(|s| s.parse().map_err(|_| eyre!("invalid OID {s}")))
+            .collect::<Result<Vec<_>>>()?;
+
+        ensure!(oid.len() >= 2, "OID {s} is too short");
+
+        Ok(Self(oid))
+    }
+}
+
+/// A variable binding value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Null,
+    ObjectIdentifier(Oid),
+    IpAddress([u8; 4]),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Opaque(Vec<u8>),
+    Counter64(u64),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+impl Value {
+    pub fn as_string(&self) -> Option<String> {
+        match self {
+            Value::OctetString(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Integer(i) => (*i).try_into().ok(),
+            Value::Counter32(c) | Value::Gauge32(c) | Value::TimeTicks(c) => Some((*c).into()),
+            Value::Counter64(c) => Some(*c),
+            _ => None,
+        }
+    }
+}
+
+/// Encode a tag-length-value triple
+pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(content.len() + 6);
+    encoded.push(tag);
+    length(content.len(), &mut encoded);
+    encoded.extend_from_slice(content);
+
+    encoded
+}
+
+fn length(length: usize, encoded: &mut Vec<u8>) {
+    if length < 0x80 {
+        encoded.push(length as u8);
+    } else {
+        let bytes = length.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        let bytes = &bytes[skip..];
+
+        encoded.push(0x80 | bytes.len() as u8);
+        encoded.extend_from_slice(bytes);
+    }
+}
+
+pub fn integer(value: i64) -> Vec<u8> {
+    tlv(INTEGER, &signed(value))
+}
+
+fn signed(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut skip = 0;
+
+    // Drop leading bytes that only repeat the sign bit
+    while skip < 7 {
+        let redundant = (bytes[skip] == 0x00 && bytes[skip + 1] & 0x80 == 0)
+            || (bytes[skip] == 0xff && bytes[skip + 1] & 0x80 != 0);
+
+        if !redundant {
+            break;
+        }
+
+        skip += 1;
+    }
+
+    bytes[skip..].to_vec()
+}
+
+fn unsigned(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|b| **b == 0).count();
+    let mut content = bytes[skip..].to_vec();
+
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+
+    content
+}
+
+pub fn octet_string(value: &[u8]) -> Vec<u8> {
+    tlv(OCTET_STRING, value)
+}
+
+pub fn null() -> Vec<u8> {
+    tlv(NULL, &[])
+}
+
+pub fn oid(oid: &Oid) -> Vec<u8> {
+    let mut content = vec![];
+
+    let (first, rest) = match oid.0.as_slice() {
+        [a, b, rest @ ..] => (a * 40 + b, rest),
+        [a] => (a * 40, &[][..]),
+        [] => (0, &[][..]),
+    };
+
+    for sub_identifier in std::iter::once(&first).chain(rest) {
+        let mut chunk = vec![(*sub_identifier & 0x7f) as u8];
+        let mut remaining = sub_identifier >> 7;
+
+        while remaining > 0 {
+            chunk.push((remaining & 0x7f) as u8 | 0x80);
+            remaining >>= 7;
+        }
+
+        content.extend(chunk.iter().rev());
+    }
+
+    tlv(OBJECT_IDENTIFIER, &content)
+}
+
+pub fn sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+    constructed(SEQUENCE, parts)
+}
+
+pub fn constructed(tag: u8, parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(tag, &parts.concat())
+}
+
+pub fn value(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Integer(i) => integer(*i),
+        Value::OctetString(s) => octet_string(s),
+        Value::Null => null(),
+        Value::ObjectIdentifier(o) => oid(o),
+        Value::IpAddress(a) => tlv(IP_ADDRESS, a),
+        Value::Counter32(c) => tlv(COUNTER32, &unsigned((*c).into())),
+        Value::Gauge32(g) => tlv(GAUGE32, &unsigned((*g).into())),
+        Value::TimeTicks(t) => tlv(TIMETICKS, &unsigned((*t).into())),
+        Value::Opaque(o) => tlv(OPAQUE, o),
+        Value::Counter64(c) => tlv(COUNTER64, &unsigned(*c)),
+        Value::NoSuchObject => tlv(NO_SUCH_OBJECT, &[]),
+        Value::NoSuchInstance => tlv(NO_SUCH_INSTANCE, &[]),
+        Value::EndOfMibView => tlv(END_OF_MIB_VIEW, &[]),
+    }
+}
+
+/// Sequential reader over BER encoded data
+#[derive(Clone, Copy)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Read the next tag and content
+    pub fn tlv(&mut self) -> Result<(u8, &'a [u8])> {
+        let (&tag, rest) = self.data.split_first().ok_or_eyre("truncated BER tag")?;
+        let (&first, mut rest) = rest.split_first().ok_or_eyre("truncated BER length")?;
+
+        let length = if first & 0x80 == 0 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            ensure!(
+                count > 0 && count <= size_of::<usize>(),
+                "unsupported BER length"
+            );
+            ensure!(rest.len() >= count, "truncated BER length");
+
+            let (bytes, remaining) = rest.split_at(count);
+            rest = remaining;
+
+            bytes
+                .iter()
+                .fold(0, |length, b| (length << 8) | *b as usize)
+        };
+
+        ensure!(rest.len() >= length, "truncated BER value");
+
+        let (content, rest) = rest.split_at(length);
+        self.data = rest;
+
+        Ok((tag, content))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<&'a [u8]> {
+        let (tag, content) = self.tlv()?;
+
+        ensure!(
+            tag == expected,
+            "expected BER tag {expected:#04x}, got {tag:#04x}"
+        );
+
+        Ok(content)
+    }
+
+    pub fn integer(&mut self) -> Result<i64> {
+        decode_signed(self.expect(INTEGER)?)
+    }
+
+    pub fn octet_string(&mut self) -> Result<&'a [u8]> {
+        self.expect(OCTET_STRING)
+    }
+
+    pub fn oid(&mut self) -> Result<Oid> {
+        decode_oid(self.expect(OBJECT_IDENTIFIER)?)
+    }
+
+    pub fn sequence(&mut self) -> Result<Reader<'a>> {
+        self.constructed(SEQUENCE)
+    }
+
+    pub fn constructed(&mut self, tag: u8) -> Result<Reader<'a>> {
+        Ok(Reader::new(self.expect(tag)?))
+    }
+
+    pub fn value(&mut self) -> Result<Value> {
+        let (tag, content) = self.tlv()?;
+
+        let value = match tag {
+            INTEGER => Value::Integer(decode_signed(content)?),
+            OCTET_STRING => Value::OctetString(content.to_vec()),
+            NULL => Value::Null,
+            OBJECT_IDENTIFIER => Value::ObjectIdentifier(decode_oid(content)?),
+            IP_ADDRESS => Value::IpAddress(
+                content
+                    .try_into()
+                    .map_err(|_| eyre!("invalid IpAddress length {}", content.len()))?,
+            ),
+            COUNTER32 => Value::Counter32(decode_unsigned(content)?.try_into()?),
+            GAUGE32 => Value::Gauge32(decode_unsigned(content)?.try_into()?),
+            TIMETICKS => Value::TimeTicks(decode_unsigned(content)?.try_into()?),
+            OPAQUE => Value::Opaque(content.to_vec()),
+            COUNTER64 => Value::Counter64(decode_unsigned(content)?),
+            NO_SUCH_OBJECT => Value::NoSuchObject,
+            NO_SUCH_INSTANCE => Value::NoSuchInstance,
+            END_OF_MIB_VIEW => Value::EndOfMibView,
+            tag => bail!("unsupported value tag {tag:#04x}"),
+        };
+
+        Ok(value)
+    }
+}
+
+fn decode_signed(content: &[u8]) -> Result<i64> {
+    ensure!(
+        !content.is_empty() && content.len() <= 8,
+        "invalid INTEGER length {}",
+        content.len()
+    );
+
+    let initial = if content[0] & 0x80 != 0 { -1 } else { 0 };
+
+    Ok(content
+        .iter()
+        .fold(initial, |value, b| (value << 8) | *b as i64))
+}
+
+fn decode_unsigned(content: &[u8]) -> Result<u64> {
+    let content = match content {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        content => content,
+    };
+
+    ensure!(
+        !content.is_empty() && content.len() <= 8,
+        "invalid unsigned length {}",
+        content.len()
+    );
+
+    Ok(content.iter().fold(0, |value, b| (value << 8) | *b as u64))
+}
+
+fn decode_oid(content: &[u8]) -> Result<Oid> {
+    let mut sub_identifiers = vec![];
+    let mut current: u32 = 0;
+
+    for byte in content {
+        current = current
+            .checked_mul(128)
+            .ok_or_eyre("OID sub-identifier overflow")?
+            | (byte & 0x7f) as u32;
+
+        if byte & 0x80 == 0 {
+            sub_identifiers.push(current);
+            current = 0;
+        }
+    }
+
+    let Some((first, rest)) = sub_identifiers.split_first() else {
+        bail!("empty OID");
+    };
+
+    let (a, b) = match first {
+        0..40 => (0, *first),
+        40..80 => (1, first - 40),
+        _ => (2, first - 80),
+    };
+
+    let mut oid = vec![a, b];
+    oid.extend_from_slice(rest);
+
+    Ok(Oid(oid))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integer_round_trip() {
+        for i in [0, 1, 127, 128, 255, 256, -1, -128, -129, i64::MAX, i64::MIN] {
+            let encoded = integer(i);
+
+            assert_eq!(i, Reader::new(&encoded).integer().unwrap(), "{encoded:x?}");
+        }
+
+        assert_eq!(vec![0x02, 0x02, 0x00, 0x80], integer(128));
+        assert_eq!(vec![0x02, 0x01, 0xff], integer(-1));
+    }
+
+    #[test]
+    fn length_long_form() {
+        let content = vec![0; 300];
+        let encoded = octet_string(&content);
+
+        assert_eq!(&[0x04, 0x82, 0x01, 0x2c], &encoded[..4]);
+        assert_eq!(content, Reader::new(&encoded).octet_string().unwrap());
+    }
+
+    #[test]
+    fn oid_round_trip() {
+        let sys_descr: Oid = "1.3.6.1.2.1.1.1.0".parse().unwrap();
+        let encoded = oid(&sys_descr);
+
+        assert_eq!(
+            vec![0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00],
+            encoded
+        );
+        assert_eq!(sys_descr, Reader::new(&encoded).oid().unwrap());
+
+        let large: Oid = "1.3.6.1.4.1.41112.1.6".parse().unwrap();
+        assert_eq!(large, Reader::new(&oid(&large)).oid().unwrap());
+    }
+
+    #[test]
+    fn value_round_trip() {
+        let values = [
+            Value::Counter32(u32::MAX),
+            Value::Counter64(u64::MAX),
+            Value::Gauge32(0),
+            Value::OctetString(b"Port 1".to_vec()),
+            Value::NoSuchInstance,
+        ];
+
+        for value in values {
+            let encoded = super::value(&value);
+
+            assert_eq!(value, Reader::new(&encoded).value().unwrap());
+        }
+    }
+}
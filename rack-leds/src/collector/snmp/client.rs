@@ -0,0 +1,273 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use eyre::{bail, ensure, eyre, Context, OptionExt, Result};
+use tokio::{net::UdpSocket, time};
+use tracing::{debug, instrument, trace};
+
+use crate::collector::snmp::{
+    ber::{Oid, Value},
+    pdu::{self, Pdu},
+    usm::{self, Engine, Keys, Usm},
+};
+
+const DEFAULT_PORT: u16 = 161;
+const MAX_REPETITIONS: i64 = 25;
+const RETRIES: usize = 2;
+
+const USM_STATS_NOT_IN_TIME_WINDOWS: &[u32] = &[1, 3, 6, 1, 6, 3, 15, 1, 1, 2, 0];
+
+/// How requests are authenticated
+#[derive(Clone, Debug)]
+pub enum Security {
+    /// SNMPv2c community string
+    Community(String),
+    /// SNMPv3 user-based security
+    Usm(Usm),
+}
+
+/// An SNMP client for a single agent
+pub struct Client {
+    address: String,
+    socket: UdpSocket,
+    security: Security,
+    timeout: Duration,
+    request_id: i32,
+    engine: Option<(Engine, Keys)>,
+}
+
+impl Client {
+    /// Connect to the agent at `address`, using port 161 if none is given
+    pub async fn connect(address: &str, security: Security, timeout: Duration) -> Result<Self> {
+        let target = match address.parse::<IpAddr>() {
+            Ok(ip) => SocketAddr::new(ip, DEFAULT_PORT).to_string(),
+            Err(_) if address.contains(':') => address.to_string(),
+            Err(_) => format!("{address}:{DEFAULT_PORT}"),
+        };
+
+        let remote = tokio::net::lookup_host(&target)
+            .await
+            .wrap_err_with(|| format!("resolving {target}"))?
+            .next()
+            .ok_or_eyre(format!("no address for {target}"))?;
+
+        let local = if remote.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(remote).await?;
+
+        debug!(%address, %remote, "connected");
+
+        Ok(Self {
+            address: address.to_string(),
+            socket,
+            security,
+            timeout,
+            request_id: rand::random::<i32>() & 0x7fff_ffff,
+            engine: None,
+        })
+    }
+
+    /// Retrieve the values of `oids`
+    pub async fn get(&mut self, oids: &[Oid]) -> Result<Vec<(Oid, Value)>> {
+        let request_id = self.next_request_id();
+
+        let response = self.request(Pdu::get(request_id, oids)).await?;
+
+        Ok(response.varbinds)
+    }
+
+    /// Retrieve every value in the subtree below `root` with GetBulk requests
+    #[instrument(skip(self), fields(address = self.address))]
+    pub async fn walk(&mut self, root: &Oid) -> Result<Vec<(Oid, Value)>> {
+        let mut values = vec![];
+        let mut next = root.clone();
+
+        loop {
+            let request_id = self.next_request_id();
+
+            let response = self
+                .request(Pdu::get_bulk(
+                    request_id,
+                    MAX_REPETITIONS,
+                    std::slice::from_ref(&next),
+                ))
+                .await?;
+
+            if response.varbinds.is_empty() {
+                break;
+            }
+
+            for (oid, value) in response.varbinds {
+                if !oid.is_child_of(root) || value == Value::EndOfMibView {
+                    trace!(count = values.len(), "walked");
+                    return Ok(values);
+                }
+
+                ensure!(oid > next, "agent returned {oid} out of order after {next}");
+
+                next = oid.clone();
+                values.push((oid, value));
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn next_request_id(&mut self) -> i32 {
+        self.request_id = self.request_id.wrapping_add(1) & 0x7fff_ffff;
+
+        self.request_id
+    }
+
+    async fn request(&mut self, pdu: Pdu) -> Result<Pdu> {
+        let response = match self.security.clone() {
+            Security::Community(community) => {
+                let message = pdu::community_message(&community, &pdu);
+
+                self.exchange(&message, pdu.request_id, |data| {
+                    let (_, response) = pdu::decode_community_message(data)?;
+
+                    Ok((response.request_id, response))
+                })
+                .await?
+            }
+            Security::Usm(usm) => self.request_usm(&usm, &pdu).await?,
+        };
+
+        ensure!(
+            response.tag == pdu::RESPONSE,
+            "unexpected PDU {:#04x} from {}",
+            response.tag,
+            self.address
+        );
+
+        response.check()?;
+
+        Ok(response)
+    }
+
+    async fn request_usm(&mut self, usm: &Usm, pdu: &Pdu) -> Result<Pdu> {
+        if self.engine.is_none() {
+            self.discover_engine(usm).await?;
+        }
+
+        // One retry after the agent corrects our idea of its clock
+        for _ in 0..2 {
+            let (engine, keys) = self
+                .engine
+                .clone()
+                .ok_or_eyre("SNMPv3 engine discovery failed")?;
+
+            let engine = engine.now();
+
+            let message_id = self.next_request_id();
+            let message = usm.message(message_id, pdu, &engine, &keys, rand::random())?;
+
+            let response = self
+                .exchange(&message, message_id, |data| {
+                    let message = usm::decode(data, &keys)?;
+
+                    Ok((message.message_id, message))
+                })
+                .await?;
+
+            if response.pdu.tag != pdu::REPORT {
+                return Ok(response.pdu);
+            }
+
+            let not_in_time_window: Oid = USM_STATS_NOT_IN_TIME_WINDOWS.into();
+
+            let Some((oid, _)) = response.pdu.varbinds.first() else {
+                bail!("empty report from {}", self.address);
+            };
+
+            if *oid != not_in_time_window {
+                bail!("SNMPv3 report {oid} from {}", self.address);
+            }
+
+            debug!(
+                boots = response.engine.boots,
+                time = response.engine.time,
+                "resynchronized"
+            );
+
+            self.engine = Some((response.engine, keys));
+        }
+
+        Err(eyre!(
+            "SNMPv3 time synchronization with {} failed",
+            self.address
+        ))
+    }
+
+    async fn discover_engine(&mut self, usm: &Usm) -> Result<()> {
+        let message_id = self.next_request_id();
+        let message = usm.discovery_message(message_id, &Pdu::get(message_id, &[]));
+
+        let response = self
+            .exchange(&message, message_id, |data| {
+                let message = usm::decode(data, &Keys::default())?;
+
+                Ok((message.message_id, message))
+            })
+            .await?;
+
+        ensure!(
+            !response.engine.id.is_empty(),
+            "no engine ID in discovery response from {}",
+            self.address
+        );
+
+        let keys = usm.keys(&response.engine.id);
+
+        debug!(engine_id = ?response.engine.id, "discovered engine");
+
+        self.engine = Some((response.engine, keys));
+
+        Ok(())
+    }
+
+    /// Send `message` and wait for a response with a matching `id`, retrying on timeout
+    async fn exchange<T>(
+        &self,
+        message: &[u8],
+        id: i32,
+        decode: impl Fn(&[u8]) -> Result<(i32, T)>,
+    ) -> Result<T> {
+        let mut buffer = vec![0; 65535];
+
+        for attempt in 0..=RETRIES {
+            self.socket.send(message).await?;
+
+            let receive = async {
+                loop {
+                    let length = self.socket.recv(&mut buffer).await?;
+
+                    match decode(&buffer[..length]) {
+                        Ok((received, response)) if received == id => return Ok(response),
+                        Ok((received, _)) => trace!(received, id, "ignoring stale response"),
+                        Err(e) => return Err(e),
+                    }
+                }
+            };
+
+            match time::timeout(self.timeout, receive).await {
+                Ok(result) => return result,
+                Err(_) => trace!(attempt, "timed out"),
+            }
+        }
+
+        Err(eyre!(
+            "no response from {} after {} attempts",
+            self.address,
+            RETRIES + 1
+        ))
+    }
+}
@@ -0,0 +1,150 @@
+use eyre::{bail, ensure, Result};
+
+use crate::collector::snmp::ber::{self, Oid, Reader, Value};
+
+pub const GET: u8 = 0xa0;
+pub const GET_NEXT: u8 = 0xa1;
+pub const RESPONSE: u8 = 0xa2;
+pub const GET_BULK: u8 = 0xa5;
+pub const REPORT: u8 = 0xa8;
+
+const VERSION_2C: i64 = 1;
+
+/// An SNMP protocol data unit
+///
+/// For GetBulk requests `error_status` and `error_index` hold non-repeaters and max-repetitions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pdu {
+    pub tag: u8,
+    pub request_id: i32,
+    pub error_status: i64,
+    pub error_index: i64,
+    pub varbinds: Vec<(Oid, Value)>,
+}
+
+impl Pdu {
+    pub fn get(request_id: i32, oids: &[Oid]) -> Self {
+        Self::request(GET, request_id, 0, 0, oids)
+    }
+
+    pub fn get_bulk(request_id: i32, max_repetitions: i64, oids: &[Oid]) -> Self {
+        Self::request(GET_BULK, request_id, 0, max_repetitions, oids)
+    }
+
+    fn request(tag: u8, request_id: i32, status: i64, index: i64, oids: &[Oid]) -> Self {
+        Self {
+            tag,
+            request_id,
+            error_status: status,
+            error_index: index,
+            varbinds: oids.iter().map(|oid| (oid.clone(), Value::Null)).collect(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn response(request_id: i32, varbinds: Vec<(Oid, Value)>) -> Self {
+        Self {
+            tag: RESPONSE,
+            request_id,
+            error_status: 0,
+            error_index: 0,
+            varbinds,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let varbinds: Vec<_> = self
+            .varbinds
+            .iter()
+            .map(|(oid, value)| ber::sequence(&[ber::oid(oid), ber::value(value)]))
+            .collect();
+
+        ber::constructed(
+            self.tag,
+            &[
+                ber::integer(self.request_id.into()),
+                ber::integer(self.error_status),
+                ber::integer(self.error_index),
+                ber::sequence(&varbinds),
+            ],
+        )
+    }
+
+    pub fn decode(reader: &mut Reader<'_>) -> Result<Self> {
+        let (tag, content) = reader.tlv()?;
+
+        ensure!(
+            matches!(tag, GET | GET_NEXT | RESPONSE | GET_BULK | REPORT),
+            "unsupported PDU {tag:#04x}"
+        );
+
+        let mut pdu = Reader::new(content);
+
+        let request_id = pdu.integer()?.try_into()?;
+        let error_status = pdu.integer()?;
+        let error_index = pdu.integer()?;
+
+        let mut list = pdu.sequence()?;
+        let mut varbinds = vec![];
+
+        while !list.is_empty() {
+            let mut varbind = list.sequence()?;
+
+            varbinds.push((varbind.oid()?, varbind.value()?));
+        }
+
+        Ok(Self {
+            tag,
+            request_id,
+            error_status,
+            error_index,
+            varbinds,
+        })
+    }
+
+    /// Return an error for a response with a non-zero error status
+    pub fn check(&self) -> Result<()> {
+        if self.error_status == 0 {
+            return Ok(());
+        }
+
+        let status = match self.error_status {
+            1 => "tooBig",
+            2 => "noSuchName",
+            3 => "badValue",
+            4 => "readOnly",
+            5 => "genErr",
+            6 => "noAccess",
+            16 => "authorizationError",
+            _ => "error",
+        };
+
+        bail!(
+            "SNMP {status} ({}) at index {}",
+            self.error_status,
+            self.error_index
+        )
+    }
+}
+
+/// Encode an SNMPv2c message
+pub fn community_message(community: &str, pdu: &Pdu) -> Vec<u8> {
+    ber::sequence(&[
+        ber::integer(VERSION_2C),
+        ber::octet_string(community.as_bytes()),
+        pdu.encode(),
+    ])
+}
+
+/// Decode an SNMPv2c message into its community and PDU
+pub fn decode_community_message(data: &[u8]) -> Result<(String, Pdu)> {
+    let mut message = Reader::new(data).sequence()?;
+
+    let version = message.integer()?;
+    ensure!(version == VERSION_2C, "unsupported SNMP version {version}");
+
+    let community = String::from_utf8_lossy(message.octet_string()?).into_owned();
+    let pdu = Pdu::decode(&mut message)?;
+
+    Ok((community, pdu))
+}
@@ -0,0 +1,460 @@
+use std::time::Instant;
+
+use aes::{
+    cipher::{AsyncStreamCipher, KeyIvInit},
+    Aes128,
+};
+use eyre::{bail, ensure, eyre, Result};
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use sha1::Sha1;
+
+use crate::collector::snmp::{
+    ber::{self, Reader},
+    pdu::Pdu,
+};
+
+const VERSION_3: i64 = 3;
+const USM_SECURITY_MODEL: i64 = 3;
+const MAX_MESSAGE_SIZE: i64 = 65507;
+const AUTH_PARAMETERS_LENGTH: usize = 12;
+
+const FLAG_AUTH: u8 = 0x01;
+const FLAG_PRIVACY: u8 = 0x02;
+const FLAG_REPORTABLE: u8 = 0x04;
+
+type Aes128CfbEnc = cfb_mode::Encryptor<Aes128>;
+type Aes128CfbDec = cfb_mode::Decryptor<Aes128>;
+
+/// SNMPv3 authentication protocols from RFC 3414
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum AuthProtocol {
+    Md5,
+    #[default]
+    Sha,
+}
+
+impl AuthProtocol {
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            AuthProtocol::Md5 => Md5::digest(data).to_vec(),
+            AuthProtocol::Sha => Sha1::digest(data).to_vec(),
+        }
+    }
+
+    fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            AuthProtocol::Md5 => {
+                let mut mac = Hmac::<Md5>::new_from_slice(key).expect("HMAC accepts any key");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            AuthProtocol::Sha => {
+                let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+
+    /// Convert a password to a key (RFC 3414 A.2)
+    pub fn password_to_key(&self, password: &str) -> Vec<u8> {
+        let password = password.as_bytes();
+
+        let expanded: Vec<_> = password.iter().copied().cycle().take(1_048_576).collect();
+
+        self.hash(&expanded)
+    }
+
+    /// Localize a key to an authoritative engine (RFC 3414 2.6)
+    pub fn localize(&self, key: &[u8], engine_id: &[u8]) -> Vec<u8> {
+        self.hash(&[key, engine_id, key].concat())
+    }
+}
+
+/// SNMPv3 user-based security credentials
+#[derive(Clone, Debug)]
+pub struct Usm {
+    user: String,
+    auth: Option<(AuthProtocol, String)>,
+    privacy: Option<String>,
+}
+
+impl Usm {
+    pub fn new(
+        user: String,
+        auth: Option<(AuthProtocol, String)>,
+        privacy: Option<String>,
+    ) -> Result<Self> {
+        ensure!(
+            privacy.is_none() || auth.is_some(),
+            "SNMPv3 privacy requires an authentication password"
+        );
+
+        Ok(Self {
+            user,
+            auth,
+            privacy,
+        })
+    }
+
+    fn flags(&self) -> u8 {
+        let mut flags = FLAG_REPORTABLE;
+
+        if self.auth.is_some() {
+            flags |= FLAG_AUTH;
+        }
+
+        if self.privacy.is_some() {
+            flags |= FLAG_PRIVACY;
+        }
+
+        flags
+    }
+
+    /// Keys localized to the authoritative engine
+    pub fn keys(&self, engine_id: &[u8]) -> Keys {
+        let Some((protocol, auth_password)) = &self.auth else {
+            return Keys::default();
+        };
+
+        let auth = protocol.localize(&protocol.password_to_key(auth_password), engine_id);
+
+        let privacy = self.privacy.as_ref().map(|privacy_password| {
+            let key = protocol.localize(&protocol.password_to_key(privacy_password), engine_id);
+
+            let mut aes_key = [0; 16];
+            aes_key.copy_from_slice(&key[..16]);
+
+            aes_key
+        });
+
+        Keys {
+            auth: Some((*protocol, auth)),
+            privacy,
+        }
+    }
+
+    /// Encode a message that discovers the authoritative engine ID, boots and time
+    pub fn discovery_message(&self, message_id: i32, pdu: &Pdu) -> Vec<u8> {
+        let engine = Engine::default();
+
+        encode(
+            message_id,
+            FLAG_REPORTABLE,
+            &engine,
+            "",
+            &[],
+            &[],
+            &scoped_pdu(&engine, pdu),
+        )
+    }
+
+    /// Encode a request for the authoritative `engine`
+    pub fn message(
+        &self,
+        message_id: i32,
+        pdu: &Pdu,
+        engine: &Engine,
+        keys: &Keys,
+        salt: u64,
+    ) -> Result<Vec<u8>> {
+        let scoped = scoped_pdu(engine, pdu);
+        let flags = self.flags();
+
+        let (data, privacy_parameters) = match keys.privacy {
+            Some(key) => {
+                let salt = salt.to_be_bytes();
+                let mut encrypted = scoped;
+
+                Aes128CfbEnc::new(&key.into(), &iv(engine, &salt).into()).encrypt(&mut encrypted);
+
+                (ber::octet_string(&encrypted), salt.to_vec())
+            }
+            None => (scoped, vec![]),
+        };
+
+        let Some((protocol, auth_key)) = &keys.auth else {
+            return Ok(encode(
+                message_id,
+                flags,
+                engine,
+                &self.user,
+                &[],
+                &privacy_parameters,
+                &data,
+            ));
+        };
+
+        let placeholder = [0; AUTH_PARAMETERS_LENGTH];
+
+        let unauthenticated = encode(
+            message_id,
+            flags,
+            engine,
+            &self.user,
+            &placeholder,
+            &privacy_parameters,
+            &data,
+        );
+
+        let digest = protocol.hmac(auth_key, &unauthenticated);
+
+        Ok(encode(
+            message_id,
+            flags,
+            engine,
+            &self.user,
+            &digest[..AUTH_PARAMETERS_LENGTH],
+            &privacy_parameters,
+            &data,
+        ))
+    }
+}
+
+/// Keys localized to an authoritative engine
+#[derive(Clone, Default)]
+pub struct Keys {
+    auth: Option<(AuthProtocol, Vec<u8>)>,
+    privacy: Option<[u8; 16]>,
+}
+
+/// Authoritative engine state
+#[derive(Clone, Debug)]
+pub struct Engine {
+    pub id: Vec<u8>,
+    pub boots: i64,
+    pub time: i64,
+    pub received: Instant,
+}
+
+impl Engine {
+    /// Estimate of the current engine time
+    pub fn now(&self) -> Self {
+        let elapsed: i64 = self.received.elapsed().as_secs().try_into().unwrap_or(0);
+
+        Self {
+            time: self.time.saturating_add(elapsed),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            id: vec![],
+            boots: 0,
+            time: 0,
+            received: Instant::now(),
+        }
+    }
+}
+
+fn iv(engine: &Engine, salt: &[u8; 8]) -> [u8; 16] {
+    let mut iv = [0; 16];
+    iv[..4].copy_from_slice(&(engine.boots as u32).to_be_bytes());
+    iv[4..8].copy_from_slice(&(engine.time as u32).to_be_bytes());
+    iv[8..].copy_from_slice(salt);
+
+    iv
+}
+
+fn scoped_pdu(engine: &Engine, pdu: &Pdu) -> Vec<u8> {
+    ber::sequence(&[
+        ber::octet_string(&engine.id),
+        ber::octet_string(&[]),
+        pdu.encode(),
+    ])
+}
+
+fn encode(
+    message_id: i32,
+    flags: u8,
+    engine: &Engine,
+    user: &str,
+    auth_parameters: &[u8],
+    privacy_parameters: &[u8],
+    data: &[u8],
+) -> Vec<u8> {
+    let global = ber::sequence(&[
+        ber::integer(message_id.into()),
+        ber::integer(MAX_MESSAGE_SIZE),
+        ber::octet_string(&[flags]),
+        ber::integer(USM_SECURITY_MODEL),
+    ]);
+
+    let security = ber::sequence(&[
+        ber::octet_string(&engine.id),
+        ber::integer(engine.boots),
+        ber::integer(engine.time),
+        ber::octet_string(user.as_bytes()),
+        ber::octet_string(auth_parameters),
+        ber::octet_string(privacy_parameters),
+    ]);
+
+    ber::sequence(&[
+        ber::integer(VERSION_3),
+        global,
+        ber::octet_string(&security),
+        data.to_vec(),
+    ])
+}
+
+/// A decoded SNMPv3 message
+pub struct Message {
+    pub message_id: i32,
+    pub engine: Engine,
+    pub pdu: Pdu,
+}
+
+/// Decode an SNMPv3 message, verifying and decrypting it with `keys` when the message requires
+pub fn decode(data: &[u8], keys: &Keys) -> Result<Message> {
+    let mut message = Reader::new(data).sequence()?;
+
+    let version = message.integer()?;
+    ensure!(version == VERSION_3, "unsupported SNMP version {version}");
+
+    let mut global = message.sequence()?;
+    let message_id = global.integer()?.try_into()?;
+    let _max_size = global.integer()?;
+    let flags = *global
+        .octet_string()?
+        .first()
+        .ok_or_else(|| eyre!("missing message flags"))?;
+
+    let mut security = Reader::new(message.octet_string()?).sequence()?;
+
+    let engine = Engine {
+        id: security.octet_string()?.to_vec(),
+        boots: security.integer()?,
+        time: security.integer()?,
+        received: Instant::now(),
+    };
+
+    let _user = security.octet_string()?;
+    let auth_parameters = security.octet_string()?;
+    let privacy_parameters = security.octet_string()?;
+
+    if flags & FLAG_AUTH != 0 {
+        let Some((protocol, key)) = &keys.auth else {
+            bail!("authenticated response without an authentication key");
+        };
+
+        ensure!(
+            auth_parameters.len() == AUTH_PARAMETERS_LENGTH,
+            "invalid authentication parameters"
+        );
+
+        let offset = auth_parameters.as_ptr() as usize - data.as_ptr() as usize;
+
+        let mut zeroed = data.to_vec();
+        zeroed[offset..offset + AUTH_PARAMETERS_LENGTH].fill(0);
+
+        let digest = protocol.hmac(key, &zeroed);
+
+        ensure!(
+            digest[..AUTH_PARAMETERS_LENGTH] == *auth_parameters,
+            "response authentication failed"
+        );
+    }
+
+    let decrypted;
+
+    let mut scoped = if flags & FLAG_PRIVACY != 0 {
+        let Some(key) = keys.privacy else {
+            bail!("encrypted response without a privacy key");
+        };
+
+        let salt: [u8; 8] = privacy_parameters
+            .try_into()
+            .map_err(|_| eyre!("invalid privacy parameters"))?;
+
+        let mut data = message.octet_string()?.to_vec();
+
+        Aes128CfbDec::new(&key.into(), &iv(&engine, &salt).into()).decrypt(&mut data);
+
+        decrypted = data;
+
+        Reader::new(&decrypted).sequence()?
+    } else {
+        message.sequence()?
+    };
+
+    let _context_engine_id = scoped.octet_string()?;
+    let _context_name = scoped.octet_string()?;
+
+    let pdu = Pdu::decode(&mut scoped)?;
+
+    Ok(Message {
+        message_id,
+        engine,
+        pdu,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    // RFC 3414 A.3.1 and A.3.2
+    #[test]
+    fn localize() {
+        let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+
+        let md5 = AuthProtocol::Md5;
+        let key = md5.password_to_key("maplesyrup");
+        assert_eq!("9faf3283884e92834ebc9847d8edd963", hex(&key));
+        assert_eq!(
+            "526f5eed9fcce26f8964c2930787d82b",
+            hex(&md5.localize(&key, &engine_id))
+        );
+
+        let sha = AuthProtocol::Sha;
+        let key = sha.password_to_key("maplesyrup");
+        assert_eq!("9fb5cc0381497b3793528939ff788d5d79145211", hex(&key));
+        assert_eq!(
+            "6695febc9288e36282235fc7151f128497b38f3f",
+            hex(&sha.localize(&key, &engine_id))
+        );
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let usm = Usm::new(
+            "monitor".into(),
+            Some((AuthProtocol::Sha, "authentication".into())),
+            Some("privacy password".into()),
+        )
+        .unwrap();
+
+        let engine = Engine {
+            id: b"\x80\x00\x1f\x88\x04rack".to_vec(),
+            boots: 3,
+            time: 1234,
+            received: Instant::now(),
+        };
+
+        let keys = usm.keys(&engine.id);
+
+        let pdu = Pdu::get(7, &["1.3.6.1.2.1.1.1.0".parse().unwrap()]);
+
+        let encoded = usm.message(42, &pdu, &engine, &keys, 99).unwrap();
+
+        let decoded = decode(&encoded, &keys).unwrap();
+
+        assert_eq!(42, decoded.message_id);
+        assert_eq!(engine.id, decoded.engine.id);
+        assert_eq!(pdu, decoded.pdu);
+
+        let mut tampered = encoded.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+
+        assert!(decode(&tampered, &keys).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use eyre::Result;
+
+use crate::{device::Id, Layout, Update};
+
+/// How collection is going, shared between a collector and the TUI
+#[derive(Clone, Default)]
+pub struct Status {
+    devices: Arc<Mutex<HashMap<Id, DeviceStatus>>>,
+    round: Arc<Mutex<Round>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DeviceStatus {
+    pub last_success: Option<SystemTime>,
+    /// The error from the last update and when it happened, cleared once an update succeeds
+    pub last_error: Option<(String, SystemTime)>,
+    /// How long the last update took, successful or not
+    pub latency: Option<Duration>,
+    /// Layout and port count from the last successful update
+    pub layout: Option<Layout>,
+    pub ports: Option<usize>,
+}
+
+/// Collection of every device once per period
+#[derive(Clone, Debug, Default)]
+pub struct Round {
+    pub period: Option<Duration>,
+    /// How long the last round took and when it finished
+    pub last: Option<(Duration, SystemTime)>,
+    /// Connections in the pool and the most allowed
+    pub pool: Option<(usize, usize)>,
+}
+
+impl Status {
+    /// Status of a collector updating every `period`
+    pub fn new(period: Duration) -> Self {
+        Self {
+            devices: Default::default(),
+            round: Arc::new(Mutex::new(Round {
+                period: Some(period),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Record the outcome of updating device `id`, which took `latency`
+    pub fn record(&self, id: Id, result: &Result<Update>, latency: Duration) {
+        let mut devices = self.devices.lock().unwrap();
+        let status = devices.entry(id).or_default();
+        let now = SystemTime::now();
+
+        status.latency = Some(latency);
+
+        match result {
+            Ok(update) => {
+                status.last_success = Some(now);
+                status.last_error = None;
+                status.layout = Some(update.layout());
+                status.ports = Some(update.ports());
+            }
+            Err(e) => status.last_error = Some((format!("{e:#}"), now)),
+        }
+    }
+
+    /// Record a round of updates that took `duration`, with the `pool` size if there is one
+    pub fn record_round(&self, duration: Duration, pool: Option<(usize, usize)>) {
+        let mut round = self.round.lock().unwrap();
+
+        round.last = Some((duration, SystemTime::now()));
+        round.pool = pool;
+    }
+
+    pub fn device(&self, id: Id) -> DeviceStatus {
+        self.devices
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn round(&self) -> Round {
+        self.round.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eyre::{eyre, WrapErr};
+
+    use super::*;
+    use crate::update::Switch;
+
+    #[test]
+    fn record() {
+        let status = Status::new(Duration::from_secs(15));
+        let id: Id = serde_json::from_str("3").unwrap();
+
+        assert!(status.device(id).last_success.is_none());
+
+        let update = Update::Switch {
+            id,
+            device: Switch::zero(10),
+            layout: Layout::SwitchEightPlusTwo,
+        };
+        status.record(id, &Ok(update), Duration::from_millis(20));
+
+        let error: Result<Update> = Err(eyre!("timed out")).wrap_err("polling switch");
+        status.record(id, &error, Duration::from_millis(100));
+
+        let device = status.device(id);
+
+        assert!(device.last_success.is_some());
+        assert_eq!(
+            "polling switch: timed out",
+            device.last_error.map(|(error, _)| error).unwrap()
+        );
+        assert_eq!(Some(Duration::from_millis(100)), device.latency);
+        assert_eq!(Some(10), device.ports);
+
+        let update = Update::Switch {
+            id,
+            device: Switch::zero(10),
+            layout: Layout::SwitchEightPlusTwo,
+        };
+        status.record(id, &Ok(update), Duration::from_millis(20));
+
+        assert!(status.device(id).last_error.is_none());
+
+        status.record_round(Duration::from_secs(1), Some((2, 16)));
+
+        let round = status.round();
+
+        assert_eq!(Some(Duration::from_secs(15)), round.period);
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            round.last.map(|(took, _)| took)
+        );
+        assert_eq!(Some((2, 16)), round.pool);
+    }
+}
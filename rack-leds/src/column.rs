@@ -2,35 +2,35 @@ use crate::device::Id;
 
 #[derive(Clone)]
 pub struct Column {
-    ids: Vec<Id>,
+    slots: Vec<Slot>,
+}
+
+/// What is drawn in part of a column
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slot {
+    Device(Id),
+    /// Empty rows
+    Spacer(u16),
 }
 
 impl Column {
     pub fn new(ids: Vec<Id>) -> Self {
-        Self { ids }
+        Self::with_slots(ids.into_iter().map(Slot::Device).collect())
     }
 
-    pub fn ids(&self) -> Iter<'_> {
-        Iter {
-            ids: &self.ids,
-            index: 0,
-        }
+    pub fn with_slots(slots: Vec<Slot>) -> Self {
+        Self { slots }
     }
-}
-
-pub struct Iter<'a> {
-    ids: &'a Vec<Id>,
-    index: usize,
-}
-
-impl Iterator for Iter<'_> {
-    type Item = Id;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let current = self.ids.get(self.index);
-
-        self.index += 1;
+    pub fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.slots.iter().filter_map(|slot| match slot {
+            Slot::Device(id) => Some(*id),
+            Slot::Spacer(_) => None,
+        })
+    }
 
-        current.cloned()
+    /// Devices and spacers from top to bottom
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
     }
 }
@@ -1,43 +1,562 @@
 mod column;
 mod device;
+mod discovery;
+mod display;
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
 
-use column::Column;
+use eyre::{bail, Context, OptionExt, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::error;
 
+pub use column::{Column, Entry};
 pub use device::Device;
+pub use discovery::Discovery;
+pub use display::Display;
 
-use crate::{Columns, Devices};
+use crate::{
+    collector::Prometheus, device::Id, Brightness, Columns, Devices, Geometry, Heartbeat, Output,
+    Overlay, Palette,
+};
+
+/// Name of the display made from the top-level `columns`, `geometry` and `palette`
+pub const DEFAULT_DISPLAY: &str = "default";
+
+/// Settings of a configured display, from the top level or `displays`
+struct DisplayParts<'a> {
+    name: &'a str,
+    columns: &'a [Column],
+    geometry: Geometry,
+    palette: Palette,
+    brightness: &'a Brightness,
+    heartbeat: Option<Heartbeat>,
+    overlays: &'a [Overlay],
+    outputs: &'a [Output],
+}
 
 #[derive(Deserialize, Serialize)]
 pub struct Config {
+    #[serde(default)]
     columns: Vec<Column>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    discovery: Vec<Discovery>,
+    #[serde(default)]
+    geometry: Geometry,
+    #[serde(default)]
+    palette: Palette,
+    #[serde(default)]
+    brightness: Brightness,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    heartbeat: Option<Heartbeat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    overlays: Vec<Overlay>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    outputs: Vec<Output>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    displays: Vec<Display>,
 }
 
-impl From<Config> for Devices {
-    fn from(config: Config) -> Self {
-        let (columns, devices) = config.columns.iter().fold(
-            (Vec::with_capacity(config.columns.len()), HashMap::default()),
-            |(mut columns, mut devices), column| {
-                let (column, _) = column.devices().iter().fold(
-                    (Vec::with_capacity(column.len()), &mut devices),
-                    |(mut column, devices), device| {
-                        let device: crate::device::Device = device.into();
-
-                        column.push(device.id());
-                        devices.insert(device.id(), device.into());
-
-                        (column, devices)
-                    },
+impl Config {
+    pub fn read(path: &Path) -> Result<Self> {
+        let config =
+            fs::read(path).wrap_err_with(|| format!("Unable to read config {}", path.display()))?;
+
+        serde_json::from_slice(&config)
+            .wrap_err_with(|| format!("Unable to parse config {}", path.display()))
+    }
+
+    /// Whether a device at `address` is in the config, rather than discovered
+    pub fn is_configured(&self, address: &str) -> bool {
+        self.addresses().any(|configured| configured == address)
+    }
+
+    /// Addresses of the devices on every display
+    fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.columns
+            .iter()
+            .chain(self.displays.iter().flat_map(|display| display.columns()))
+            .flat_map(|column| column.devices())
+            .map(|device| device.address())
+    }
+
+    /// Set the `name` query of the device at `address` on every display in the config at `path`
+    ///
+    /// Only those keys change, the rest of the file is kept as it was.
+    pub fn save_query(path: &Path, address: &str, name: &str, query: &str) -> Result<()> {
+        // Checks the query name, and that the device isn't discovered
+        Self::read(path)?.set_query(address, name, query)?;
+
+        edit(path, |config| {
+            for device in device_fields(config) {
+                if device.get("address").and_then(Value::as_str) == Some(address) {
+                    device.insert(name.to_string(), query.into());
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Replace the columns of the display called `name` in the config at `path`
+    ///
+    /// Only the columns change, the rest of the file is kept as it was.
+    pub fn save_columns(path: &Path, name: &str, columns: &[Column]) -> Result<()> {
+        let columns = serde_json::to_value(columns)?;
+
+        edit(path, |config| {
+            let display = if name == DEFAULT_DISPLAY {
+                config.as_object_mut()
+            } else {
+                config
+                    .get_mut("displays")
+                    .and_then(Value::as_array_mut)
+                    .into_iter()
+                    .flatten()
+                    .find(|display| display.get("name").and_then(Value::as_str) == Some(name))
+                    .and_then(Value::as_object_mut)
+            };
+
+            display
+                .ok_or_eyre(format!("No display {name} in the config"))?
+                .insert("columns".to_string(), columns);
+
+            Ok(())
+        })
+    }
+
+    /// Columns of the display called `name`, [`DEFAULT_DISPLAY`] is the top-level one
+    pub fn columns(&self, name: &str) -> Option<&[Column]> {
+        if name == DEFAULT_DISPLAY {
+            return Some(&self.columns);
+        }
+
+        self.displays
+            .iter()
+            .find(|display| display.name() == name)
+            .map(|display| display.columns())
+    }
+
+    pub fn columns_mut(&mut self, name: &str) -> Option<&mut Vec<Column>> {
+        if name == DEFAULT_DISPLAY {
+            return Some(&mut self.columns);
+        }
+
+        self.displays
+            .iter_mut()
+            .find(|display| display.name() == name)
+            .map(|display| display.columns_mut())
+    }
+
+    /// Override the `name` query of the device at `address` on every display
+    pub fn set_query(&mut self, address: &str, name: &str, query: &str) -> Result<()> {
+        let mut found = false;
+
+        let devices = self
+            .columns
+            .iter_mut()
+            .chain(
+                self.displays
+                    .iter_mut()
+                    .flat_map(|display| display.columns_mut()),
+            )
+            .flat_map(|column| column.devices_mut())
+            .filter(|device| device.address() == address);
+
+        for device in devices {
+            device.set_query(name, query.to_string())?;
+            found = true;
+        }
+
+        if !found {
+            bail!("No device with address {address} in the config, it may have been discovered");
+        }
+
+        Ok(())
+    }
+
+    /// Add devices found by each [`Discovery`] to the configured columns
+    ///
+    /// Devices with an address that is already configured on any display are not added again.  A
+    /// failed discovery is logged so the configured devices are still shown.
+    pub async fn discover(mut self, prometheus: &Prometheus) -> Self {
+        let mut known: HashSet<String> = self.addresses().map(str::to_string).collect();
+
+        for discovery in self.discovery.iter() {
+            if let Err(e) = discovery
+                .run(prometheus, &mut self.columns, &mut known)
+                .await
+            {
+                error!(
+                    ?e,
+                    "device discovery failed, continuing without discovered devices"
                 );
+            }
+        }
+
+        self
+    }
+
+    /// Check display names can be used in URLs and devices shared between displays match
+    ///
+    /// A device shown on more than one display is only updated once, so every entry for its
+    /// address must have the same queries.
+    fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+
+        for name in self.displays.iter().map(|display| display.name()) {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!("Display name {name:?} may only contain letters, digits, '-' and '_'");
+            }
+
+            if name == DEFAULT_DISPLAY {
+                bail!("Display name {name:?} is reserved for the top-level display");
+            }
+
+            if !names.insert(name) {
+                bail!("Display name {name:?} is used more than once");
+            }
+        }
+
+        let mut devices: HashMap<&str, &Device> = HashMap::default();
 
-                columns.push(crate::Column::new(column));
+        for display in self.displays() {
+            for device in display.columns.iter().flat_map(|column| column.devices()) {
+                let first = *devices.entry(device.address()).or_insert(device);
 
-                (columns, devices)
-            },
+                if first != device {
+                    bail!(
+                        "Device {} is configured more than once with different queries",
+                        device.address()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The top-level display followed by each named display
+    ///
+    /// The top-level display is left out when it has no columns and there are named displays.
+    fn displays(&self) -> Vec<DisplayParts<'_>> {
+        let default =
+            (!self.columns.is_empty() || self.displays.is_empty()).then_some(DisplayParts {
+                name: DEFAULT_DISPLAY,
+                columns: &self.columns,
+                geometry: self.geometry,
+                palette: self.palette,
+                brightness: &self.brightness,
+                heartbeat: self.heartbeat,
+                overlays: &self.overlays,
+                outputs: &self.outputs,
+            });
+
+        default
+            .into_iter()
+            .chain(self.displays.iter().map(|display| DisplayParts {
+                name: display.name(),
+                columns: display.columns(),
+                geometry: *display.geometry(),
+                palette: *display.palette(),
+                brightness: display.brightness(),
+                heartbeat: display.heartbeat(),
+                overlays: display.overlays(),
+                outputs: display.outputs(),
+            }))
+            .collect()
+    }
+}
+
+/// Change the config file at `path` in place with `f`
+///
+/// The edited config must still parse.  It is written to a temporary file that replaces the
+/// config, so a failed write leaves the config as it was.
+fn edit(path: &Path, f: impl FnOnce(&mut Value) -> Result<()>) -> Result<()> {
+    let read =
+        fs::read(path).wrap_err_with(|| format!("Unable to read config {}", path.display()))?;
+    let mut config: Value = serde_json::from_slice(&read)
+        .wrap_err_with(|| format!("Unable to parse config {}", path.display()))?;
+
+    f(&mut config)?;
+
+    Config::deserialize(&config).wrap_err("The edited config is invalid")?;
+
+    let mut contents = serde_json::to_string_pretty(&config)?;
+    contents.push('\n');
+
+    let file_name = path
+        .file_name()
+        .ok_or_eyre("The config path has no file name")?
+        .to_string_lossy();
+    let temporary = path.with_file_name(format!(".{file_name}.tmp"));
+
+    fs::write(&temporary, contents)
+        .wrap_err_with(|| format!("Unable to write {}", temporary.display()))?;
+    fs::rename(&temporary, path)
+        .wrap_err_with(|| format!("Unable to replace config {}", path.display()))
+}
+
+/// The fields of every device in the top-level and named display columns of a config file
+fn device_fields(config: &mut Value) -> Vec<&mut Map<String, Value>> {
+    let Some(config) = config.as_object_mut() else {
+        return vec![];
+    };
+
+    config
+        .iter_mut()
+        .flat_map(|(key, value)| match key.as_str() {
+            "columns" => vec![value],
+            "displays" => value
+                .as_array_mut()
+                .into_iter()
+                .flatten()
+                .filter_map(|display| display.get_mut("columns"))
+                .collect(),
+            _ => vec![],
+        })
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(|column| column.get_mut("devices"))
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(Value::as_object_mut)
+        // Devices are `{"Switch": {...}}`, spacers `{"spacer": 2}`
+        .flat_map(|entry| entry.values_mut())
+        .filter_map(Value::as_object_mut)
+        .collect()
+}
+
+impl TryFrom<Config> for Devices {
+    type Error = eyre::Report;
+
+    fn try_from(config: Config) -> Result<Self> {
+        config.validate()?;
+
+        let mut devices = HashMap::default();
+        // Devices shown on more than one display are only updated once
+        let mut ids: HashMap<String, Id> = HashMap::default();
+
+        let displays = config
+            .displays()
+            .into_iter()
+            .map(|display| {
+                let columns = display
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        column.resolve(|device| {
+                            let id =
+                                *ids.entry(device.address().to_string()).or_insert_with(|| {
+                                    let device: crate::device::Device = device.into();
+                                    let id = device.id();
+
+                                    devices.insert(id, device.into());
+
+                                    id
+                                });
+
+                            Some(id)
+                        })
+                    })
+                    .collect();
+
+                // Overlays may show devices on this display or earlier ones
+                let overlays = display
+                    .overlays
+                    .iter()
+                    .cloned()
+                    .map(|mut overlay| {
+                        overlay.resolve(&ids);
+                        overlay
+                    })
+                    .collect();
+
+                crate::Display::new(
+                    display.name.to_string(),
+                    Columns::new(columns),
+                    display.geometry,
+                    display.palette,
+                )
+                .with_brightness(display.brightness.clone())
+                .with_heartbeat(display.heartbeat)
+                .with_overlays(overlays)
+                .with_outputs(display.outputs.to_vec())
+            })
+            .collect();
+
+        Ok(Self::new(displays, devices))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::Rotation;
+
+    #[test]
+    fn displays() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "columns": [{"devices": [{"Switch": {"address": "core"}}]}],
+                "displays": [
+                    {
+                        "name": "rack2",
+                        "columns": [
+                            {"devices": [{"Switch": {"address": "core"}}]},
+                            {"devices": [{"Switch": {"address": "edge"}}]}
+                        ],
+                        "geometry": {"panel": "CosmicUnicorn", "rotation": "UpsideDown"},
+                        "palette": {"receive": {"hue": 30.0, "saturation": 1.0}}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let devices: Devices = config.try_into().unwrap();
+
+        let names: Vec<_> = devices.displays().iter().map(|d| d.name()).collect();
+        assert_eq!(vec![DEFAULT_DISPLAY, "rack2"], names);
+
+        assert_eq!(2, devices.devices().len());
+
+        let default = devices.display(None).unwrap();
+        let rack2 = devices.display(Some("rack2")).unwrap();
+
+        let core = default.columns().columns().next().unwrap().ids().next();
+        let rack2_core = rack2.columns().columns().next().unwrap().ids().next();
+        assert_eq!(core, rack2_core);
+
+        assert_eq!(Geometry::default(), *default.geometry());
+        assert_eq!(Rotation::UpsideDown, rack2.geometry().rotation);
+        assert_eq!(30.0, rack2.palette().receive.hue);
+        assert_eq!(Palette::default().transmit, rack2.palette().transmit);
+
+        assert!(devices.display(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn set_query() {
+        let mut config: Config = serde_json::from_str(
+            r#"{
+                "columns": [{"devices": [{"Switch": {"address": "core"}}]}],
+                "displays": [
+                    {"name": "rack2", "columns": [{"devices": [{"Switch": {"address": "core"}}]}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        config.set_query("core", "poe", "up").unwrap();
+
+        let queries: Vec<_> = config
+            .columns
+            .iter()
+            .chain(config.displays[0].columns())
+            .flat_map(|column| column.devices())
+            .map(|device| match device {
+                Device::Switch { poe, .. } => poe.clone(),
+                Device::AccessPoint { .. } => None,
+            })
+            .collect();
+
+        assert_eq!(vec![Some("up".to_string()); 2], queries);
+
+        assert!(config.set_query("core", "stations_5_ghz", "up").is_err());
+        assert!(config.set_query("edge", "poe", "up").is_err());
+    }
+
+    #[test]
+    fn displays_named_only() {
+        let config: Config =
+            serde_json::from_str(r#"{"displays": [{"name": "rack1", "columns": []}]}"#).unwrap();
+
+        let devices: Devices = config.try_into().unwrap();
+
+        assert_eq!("rack1", devices.display(None).unwrap().name());
+        assert_eq!(1, devices.displays().len());
+    }
+
+    #[test]
+    fn validate() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "columns": [{"devices": [{"Switch": {"address": "core"}}]}],
+                "displays": [
+                    {"name": "rack2", "columns": [{"devices": [{"Switch": {"address": "core", "poe": "up"}}]}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(Devices::try_from(config).is_err());
+
+        let config: Config =
+            serde_json::from_str(r#"{"displays": [{"name": "rack<1>", "columns": []}]}"#).unwrap();
+
+        assert!(Devices::try_from(config).is_err());
+    }
+
+    #[test]
+    fn validate_names() {
+        let config: Config = serde_json::from_str(
+            r#"{"displays": [{"name": "rack2", "columns": []}, {"name": "rack2", "columns": []}]}"#,
+        )
+        .unwrap();
+
+        let error = Devices::try_from(config).err().unwrap();
+        assert_eq!(
+            r#"Display name "rack2" is used more than once"#,
+            error.to_string()
+        );
+
+        let config: Config = serde_json::from_str(
+            r#"{
+                "columns": [{"devices": [{"Switch": {"address": "core"}}]}],
+                "displays": [{"name": "default", "columns": []}]
+            }"#,
+        )
+        .unwrap();
+
+        let error = Devices::try_from(config).err().unwrap();
+        assert_eq!(
+            r#"Display name "default" is reserved for the top-level display"#,
+            error.to_string()
         );
+    }
+
+    #[test]
+    fn save_query() {
+        let path = std::env::temp_dir().join(format!("rack-leds-{}.json", std::process::id()));
+        let written = r#"{
+  "geometry": {"panel": "CosmicUnicorn"},
+  "columns": [{"devices": [{"Switch": {"address": "core", "receive": "r"}}, {"spacer": 1}]}]
+}"#;
+        fs::write(&path, written).unwrap();
+
+        Config::save_query(&path, "core", "poe", "up").unwrap();
+        let saved = fs::read_to_string(&path);
 
-        Self::new(Columns::new(columns), devices)
+        assert!(Config::save_query(&path, "edge", "poe", "up").is_err());
+        fs::remove_file(&path).unwrap();
+
+        let saved: Value = serde_json::from_str(&saved.unwrap()).unwrap();
+        let keys: Vec<_> = saved.as_object().unwrap().keys().collect();
+
+        assert_eq!(vec!["geometry", "columns"], keys);
+        assert_eq!(
+            serde_json::json!({"address": "core", "receive": "r", "poe": "up"}),
+            saved["columns"][0]["devices"][0]["Switch"]
+        );
+        assert_eq!(None, saved.get("palette"));
     }
 }
@@ -1,18 +1,103 @@
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 
-use crate::config::Device;
+use crate::{column::Slot, config::Device, device::Id};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Column {
-    devices: Vec<Device>,
+    devices: Vec<Entry>,
+}
+
+/// A device in a column, or empty rows between devices
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Entry {
+    Spacer { spacer: u16 },
+    Device(Box<Device>),
+}
+
+/// Entries with a `spacer` are spacers and anything else is a device, so a mistake in a device
+/// reports what is wrong with it
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entry = Value::deserialize(deserializer)?;
+
+        if let Some(spacer) = entry.get("spacer") {
+            let spacer = u16::deserialize(spacer).map_err(de::Error::custom)?;
+
+            return Ok(Entry::Spacer { spacer });
+        }
+
+        let device = Device::deserialize(entry).map_err(de::Error::custom)?;
+
+        Ok(Entry::Device(Box::new(device)))
+    }
 }
 
 impl Column {
-    pub fn devices(&self) -> &[Device] {
+    pub fn devices(&self) -> impl Iterator<Item = &Device> {
+        self.devices.iter().filter_map(|entry| match entry {
+            Entry::Device(device) => Some(device.as_ref()),
+            Entry::Spacer { .. } => None,
+        })
+    }
+
+    pub fn devices_mut(&mut self) -> impl Iterator<Item = &mut Device> {
+        self.devices.iter_mut().filter_map(|entry| match entry {
+            Entry::Device(device) => Some(device.as_mut()),
+            Entry::Spacer { .. } => None,
+        })
+    }
+
+    /// Devices and spacers from top to bottom
+    pub fn entries(&self) -> &[Entry] {
         &self.devices
     }
 
-    pub fn len(&self) -> usize {
-        self.devices.len()
+    pub fn entries_mut(&mut self) -> &mut Vec<Entry> {
+        &mut self.devices
+    }
+
+    pub fn push(&mut self, device: Device) {
+        self.devices.push(Entry::Device(Box::new(device)));
+    }
+
+    /// The column shown on a display, `id` finds the running device for a configured device
+    pub fn resolve(&self, mut id: impl FnMut(&Device) -> Option<Id>) -> crate::Column {
+        let slots = self
+            .devices
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Device(device) => id(device).map(Slot::Device),
+                Entry::Spacer { spacer } => Some(Slot::Spacer(*spacer)),
+            })
+            .collect();
+
+        crate::Column::with_slots(slots)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        let column: Column = serde_json::from_str(
+            r#"{"devices": [{"spacer": 2}, {"Switch": {"address": "core"}}]}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(column.entries()[0], Entry::Spacer { spacer: 2 }));
+        assert_eq!("core", column.devices().next().unwrap().address());
+
+        let error =
+            serde_json::from_str::<Column>(r#"{"devices": [{"Switch": {"adress": "core"}}]}"#)
+                .unwrap_err();
+
+        assert!(
+            error.to_string().contains("missing field `address`"),
+            "{error}"
+        );
     }
 }
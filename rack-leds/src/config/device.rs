@@ -1,8 +1,9 @@
+use eyre::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::device::{AccessPoint, Switch};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Device {
     AccessPoint {
         address: String,
@@ -26,6 +27,89 @@ pub enum Device {
     },
 }
 
+impl Device {
+    /// An access point using the default queries
+    pub fn access_point(address: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::AccessPoint {
+            address: address.into(),
+            name: name.into(),
+            channel_utilization_24_ghz: None,
+            channel_utilization_5_ghz: None,
+            receive_ap: None,
+            receive_wan_24_ghz: None,
+            receive_wan_5_ghz: None,
+            stations_24_ghz: None,
+            stations_5_ghz: None,
+            transmit_ap: None,
+            transmit_wan_24_ghz: None,
+            transmit_wan_5_ghz: None,
+        }
+    }
+
+    /// A switch using the default queries
+    pub fn switch(address: impl Into<String>) -> Self {
+        Self::Switch {
+            address: address.into(),
+            receive: None,
+            transmit: None,
+            poe: None,
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        match self {
+            Device::AccessPoint { address, .. } => address,
+            Device::Switch { address, .. } => address,
+        }
+    }
+
+    /// Override the query in the field called `name`
+    pub fn set_query(&mut self, name: &str, query: String) -> Result<()> {
+        let field = match self {
+            Device::AccessPoint {
+                channel_utilization_24_ghz,
+                channel_utilization_5_ghz,
+                receive_ap,
+                receive_wan_24_ghz,
+                receive_wan_5_ghz,
+                stations_24_ghz,
+                stations_5_ghz,
+                transmit_ap,
+                transmit_wan_24_ghz,
+                transmit_wan_5_ghz,
+                ..
+            } => match name {
+                "channel_utilization_24_ghz" => channel_utilization_24_ghz,
+                "channel_utilization_5_ghz" => channel_utilization_5_ghz,
+                "receive_ap" => receive_ap,
+                "receive_wan_24_ghz" => receive_wan_24_ghz,
+                "receive_wan_5_ghz" => receive_wan_5_ghz,
+                "stations_24_ghz" => stations_24_ghz,
+                "stations_5_ghz" => stations_5_ghz,
+                "transmit_ap" => transmit_ap,
+                "transmit_wan_24_ghz" => transmit_wan_24_ghz,
+                "transmit_wan_5_ghz" => transmit_wan_5_ghz,
+                _ => bail!("Access points have no query {name}"),
+            },
+            Device::Switch {
+                receive,
+                transmit,
+                poe,
+                ..
+            } => match name {
+                "receive" => receive,
+                "transmit" => transmit,
+                "poe" => poe,
+                _ => bail!("Switches have no query {name}"),
+            },
+        };
+
+        *field = Some(query);
+
+        Ok(())
+    }
+}
+
 impl From<Device> for crate::device::Device {
     fn from(device: Device) -> Self {
         (&device).into()
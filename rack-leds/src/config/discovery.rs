@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+
+use eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    collector::Prometheus,
+    config::{Column, Device},
+};
+
+/// Create devices from the series returned by a Prometheus query
+///
+/// Each series matching `query` becomes one device.  The device address is taken from
+/// `address_label` and the name from `name_label`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Discovery {
+    query: String,
+    device: Kind,
+    #[serde(default = "default_address_label")]
+    address_label: String,
+    #[serde(default = "default_name_label")]
+    name_label: String,
+    #[serde(default)]
+    placement: Placement,
+}
+
+fn default_address_label() -> String {
+    "instance".into()
+}
+
+fn default_name_label() -> String {
+    "name".into()
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Kind {
+    AccessPoint,
+    Switch,
+}
+
+/// Where discovered devices are placed in the display columns
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Placement {
+    /// Sort devices by name and add them starting at `column`, moving to the next column after
+    /// `per_column` devices.  Without a `column` new columns are added after the existing ones.
+    Sorted {
+        column: Option<usize>,
+        per_column: Option<usize>,
+    },
+    /// Place devices in the column mapped from the value of `label`.  Devices with an unmapped
+    /// value are placed in the `default` column, or skipped if there is none.
+    Label {
+        label: String,
+        columns: HashMap<String, usize>,
+        default: Option<usize>,
+    },
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self::Sorted {
+            column: None,
+            per_column: None,
+        }
+    }
+}
+
+/// A device found by [`Discovery`] along with the labels of its series
+#[derive(Debug)]
+pub struct Discovered {
+    address: String,
+    name: String,
+    labels: HashMap<String, String>,
+}
+
+impl Discovery {
+    /// Run the discovery query and return devices not in `known` addresses
+    #[instrument(skip_all, fields(query = %self.query))]
+    pub async fn discover(
+        &self,
+        prometheus: &Prometheus,
+        known: &HashSet<String>,
+    ) -> Result<Vec<Discovered>> {
+        let series = prometheus.get_labels(&self.query).await?;
+
+        Ok(self.discovered(series, known))
+    }
+
+    fn discovered(
+        &self,
+        series: Vec<HashMap<String, String>>,
+        known: &HashSet<String>,
+    ) -> Vec<Discovered> {
+        series
+            .into_iter()
+            .filter_map(|labels| {
+                let Some(address) = labels.get(&self.address_label).cloned() else {
+                    warn!(?labels, label = self.address_label, "missing address label");
+                    return None;
+                };
+
+                let name = labels
+                    .get(&self.name_label)
+                    .cloned()
+                    .unwrap_or_else(|| address.clone());
+
+                Some(Discovered {
+                    address,
+                    name,
+                    labels,
+                })
+            })
+            .filter(|discovered| !known.contains(&discovered.address))
+            .unique_by(|discovered| discovered.address.clone())
+            .sorted_by(|a, b| a.name.cmp(&b.name).then_with(|| a.address.cmp(&b.address)))
+            .collect()
+    }
+
+    fn device(&self, discovered: &Discovered) -> Device {
+        match self.device {
+            Kind::AccessPoint => Device::access_point(&discovered.address, &discovered.name),
+            Kind::Switch => Device::switch(&discovered.address),
+        }
+    }
+
+    /// Add `discovered` devices to `columns` according to the [`Placement`]
+    pub fn place(&self, columns: &mut Vec<Column>, discovered: Vec<Discovered>) {
+        match &self.placement {
+            Placement::Sorted { column, per_column } => {
+                let first = column.unwrap_or(columns.len());
+                let per_column = per_column.unwrap_or(usize::MAX).max(1);
+
+                for (offset, chunk) in discovered.chunks(per_column).enumerate() {
+                    for discovered in chunk {
+                        column_at(columns, first + offset).push(self.device(discovered));
+                    }
+                }
+            }
+            Placement::Label {
+                label,
+                columns: mapping,
+                default,
+            } => {
+                for discovered in discovered.iter() {
+                    let index = discovered
+                        .labels
+                        .get(label)
+                        .and_then(|value| mapping.get(value))
+                        .or(default.as_ref());
+
+                    let Some(index) = index else {
+                        debug!(address = discovered.address, "no column for device");
+                        continue;
+                    };
+
+                    column_at(columns, *index).push(self.device(discovered));
+                }
+            }
+        }
+    }
+
+    /// Discover devices and add them to `columns`
+    pub async fn run(
+        &self,
+        prometheus: &Prometheus,
+        columns: &mut Vec<Column>,
+        known: &mut HashSet<String>,
+    ) -> Result<()> {
+        let discovered = self.discover(prometheus, known).await?;
+
+        info!(
+            query = self.query,
+            count = discovered.len(),
+            "discovered devices"
+        );
+
+        known.extend(discovered.iter().map(|d| d.address.clone()));
+
+        self.place(columns, discovered);
+
+        Ok(())
+    }
+}
+
+fn column_at(columns: &mut Vec<Column>, index: usize) -> &mut Column {
+    if columns.len() <= index {
+        columns.resize_with(index + 1, Column::default);
+    }
+
+    &mut columns[index]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn series(labels: &[&[(&str, &str)]]) -> Vec<HashMap<String, String>> {
+        labels
+            .iter()
+            .map(|labels| {
+                labels
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn addresses(columns: &[Column]) -> Vec<Vec<String>> {
+        columns
+            .iter()
+            .map(|column| {
+                column
+                    .devices()
+                    .map(|device| device.address().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn discovered() {
+        let discovery = Discovery {
+            query: "sysDescr".into(),
+            device: Kind::Switch,
+            address_label: default_address_label(),
+            name_label: default_name_label(),
+            placement: Placement::default(),
+        };
+
+        let series = series(&[
+            &[("instance", "c"), ("name", "b")],
+            &[("instance", "a"), ("name", "z")],
+            &[("instance", "b")],
+            &[("instance", "c"), ("name", "b")],
+            &[("instance", "d")],
+            &[("name", "missing")],
+        ]);
+
+        let known = HashSet::from(["d".to_string()]);
+
+        let discovered: Vec<_> = discovery
+            .discovered(series, &known)
+            .into_iter()
+            .map(|d| (d.address, d.name))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("b".to_string(), "b".to_string()),
+                ("c".to_string(), "b".to_string()),
+                ("a".to_string(), "z".to_string()),
+            ],
+            discovered
+        );
+    }
+
+    #[test]
+    fn place_sorted() {
+        let discovery = Discovery {
+            query: "sysDescr".into(),
+            device: Kind::Switch,
+            address_label: default_address_label(),
+            name_label: default_name_label(),
+            placement: Placement::Sorted {
+                column: None,
+                per_column: Some(2),
+            },
+        };
+
+        let mut columns = vec![Column::default()];
+        columns[0].push(Device::switch("configured"));
+
+        let series = series(&[
+            &[("instance", "c")],
+            &[("instance", "a")],
+            &[("instance", "b")],
+        ]);
+        let discovered = discovery.discovered(series, &HashSet::default());
+
+        discovery.place(&mut columns, discovered);
+
+        assert_eq!(
+            vec![
+                vec!["configured".to_string()],
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string()],
+            ],
+            addresses(&columns)
+        );
+    }
+
+    #[test]
+    fn place_label() {
+        let discovery = Discovery {
+            query: "unpoller_device_info".into(),
+            device: Kind::AccessPoint,
+            address_label: default_address_label(),
+            name_label: default_name_label(),
+            placement: Placement::Label {
+                label: "site".into(),
+                columns: HashMap::from([("upstairs".to_string(), 2)]),
+                default: None,
+            },
+        };
+
+        let mut columns = vec![];
+
+        let series = series(&[
+            &[("instance", "a"), ("site", "upstairs")],
+            &[("instance", "b"), ("site", "downstairs")],
+        ]);
+        let discovered = discovery.discovered(series, &HashSet::default());
+
+        discovery.place(&mut columns, discovered);
+
+        assert_eq!(
+            vec![vec![], vec![], vec!["a".to_string()]],
+            addresses(&columns)
+        );
+    }
+}
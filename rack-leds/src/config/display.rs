@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Column, Brightness, Geometry, Heartbeat, Output, Overlay, Palette};
+
+/// A named display with its own columns, geometry and palette
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Display {
+    name: String,
+    columns: Vec<Column>,
+    #[serde(default)]
+    geometry: Geometry,
+    #[serde(default)]
+    palette: Palette,
+    #[serde(default)]
+    brightness: Brightness,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    heartbeat: Option<Heartbeat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    overlays: Vec<Overlay>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    outputs: Vec<Output>,
+}
+
+impl Display {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn columns_mut(&mut self) -> &mut Vec<Column> {
+        &mut self.columns
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn brightness(&self) -> &Brightness {
+        &self.brightness
+    }
+
+    pub fn heartbeat(&self) -> Option<Heartbeat> {
+        self.heartbeat
+    }
+
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    pub fn outputs(&self) -> &[Output] {
+        &self.outputs
+    }
+}
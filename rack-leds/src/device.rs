@@ -1,5 +1,6 @@
 mod access_point;
 mod id;
+mod query;
 mod switch;
 
 use std::fmt::Display;
@@ -8,13 +9,14 @@ pub use access_point::AccessPoint;
 use eyre::Result;
 use id::next_id;
 pub use id::Id;
+pub use query::Query;
 pub use switch::Switch;
 
-use crate::{collector::prometheus, Update};
+use crate::{collector::MetricsSource, Update};
 
 #[derive(Clone, Debug)]
 pub enum Device {
-    AccessPoint { id: Id, device: AccessPoint },
+    AccessPoint { id: Id, device: Box<AccessPoint> },
     Switch { id: Id, device: Switch },
 }
 
@@ -22,7 +24,7 @@ impl Device {
     pub fn access_point(access_point: AccessPoint) -> Self {
         Device::AccessPoint {
             id: next_id(),
-            device: access_point,
+            device: Box::new(access_point),
         }
     }
 
@@ -40,14 +42,37 @@ impl Device {
         }
     }
 
-    pub async fn update(&self, connection: &prometheus::Connection) -> Result<Update> {
+    pub fn address(&self) -> String {
+        match self {
+            Device::AccessPoint { device, .. } => device.address().to_string(),
+            Device::Switch { device, .. } => device.address(),
+        }
+    }
+
+    /// Queries for each value, by the name of its config field
+    pub fn queries(&self) -> Vec<(&'static str, Query)> {
+        match self {
+            Device::AccessPoint { device, .. } => device.queries(),
+            Device::Switch { device, .. } => device.queries(),
+        }
+    }
+
+    /// Forget the rates received so far, after a query changes
+    pub fn reset(&self) {
+        match self {
+            Device::AccessPoint { device, .. } => device.reset(),
+            Device::Switch { device, .. } => device.reset(),
+        }
+    }
+
+    pub async fn update(&self, source: &impl MetricsSource) -> Result<Update> {
         let update = match self {
             Device::AccessPoint {
                 id,
                 device: access_point,
             } => {
-                let device = access_point.update(connection).await?;
-                let layout = access_point.layout(connection).await?;
+                let device = access_point.update(source).await?;
+                let layout = access_point.layout(source).await?;
 
                 Update::AccessPoint {
                     id: *id,
@@ -56,8 +81,8 @@ impl Device {
                 }
             }
             Device::Switch { id, device: switch } => {
-                let device = switch.update(connection).await?;
-                let layout = switch.layout(connection).await?;
+                let device = switch.update(source).await?;
+                let layout = switch.layout(source).await?;
 
                 Update::Switch {
                     id: *id,
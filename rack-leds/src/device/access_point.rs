@@ -3,8 +3,8 @@ use rand::{distributions::Uniform, prelude::SmallRng};
 use tracing::instrument;
 
 use crate::{
-    collector::{prometheus, Absolute, Diff},
-    device::Id,
+    collector::{Absolute, Diff, MetricsSource},
+    device::{Id, Query},
     simulator::Simulated,
     update, Layout,
 };
@@ -14,25 +14,25 @@ pub struct AccessPoint {
     address: String,
     name: String,
     channel_utilization_24_ghz: Absolute<f64>,
-    channel_utilization_24_ghz_query: String,
+    channel_utilization_24_ghz_query: Query,
     channel_utilization_5_ghz: Absolute<f64>,
-    channel_utilization_5_ghz_query: String,
+    channel_utilization_5_ghz_query: Query,
     receive_ap: Diff<u64>,
-    receive_ap_query: String,
+    receive_ap_query: Query,
     receive_wan_24_ghz: Diff<u64>,
-    receive_wan_24_ghz_query: String,
+    receive_wan_24_ghz_query: Query,
     receive_wan_5_ghz: Diff<u64>,
-    receive_wan_5_ghz_query: String,
+    receive_wan_5_ghz_query: Query,
     stations_24_ghz: Absolute<u64>,
-    stations_24_ghz_query: String,
+    stations_24_ghz_query: Query,
     stations_5_ghz: Absolute<u64>,
-    stations_5_ghz_query: String,
+    stations_5_ghz_query: Query,
     transmit_ap: Diff<u64>,
-    transmit_ap_query: String,
+    transmit_ap_query: Query,
     transmit_wan_24_ghz: Diff<u64>,
-    transmit_wan_24_ghz_query: String,
+    transmit_wan_24_ghz_query: Query,
     transmit_wan_5_ghz: Diff<u64>,
-    transmit_wan_5_ghz_query: String,
+    transmit_wan_5_ghz_query: Query,
 }
 
 impl AccessPoint {
@@ -55,33 +55,72 @@ impl AccessPoint {
             address,
             name,
             channel_utilization_24_ghz: Default::default(),
-            channel_utilization_24_ghz_query,
+            channel_utilization_24_ghz_query: channel_utilization_24_ghz_query.into(),
             channel_utilization_5_ghz: Default::default(),
-            channel_utilization_5_ghz_query,
+            channel_utilization_5_ghz_query: channel_utilization_5_ghz_query.into(),
             receive_ap: Default::default(),
-            receive_ap_query,
+            receive_ap_query: receive_ap_query.into(),
             receive_wan_24_ghz: Default::default(),
-            receive_wan_24_ghz_query,
+            receive_wan_24_ghz_query: receive_wan_24_ghz_query.into(),
             receive_wan_5_ghz: Default::default(),
-            receive_wan_5_ghz_query,
+            receive_wan_5_ghz_query: receive_wan_5_ghz_query.into(),
             stations_24_ghz: Default::default(),
-            stations_24_ghz_query,
+            stations_24_ghz_query: stations_24_ghz_query.into(),
             stations_5_ghz: Default::default(),
-            stations_5_ghz_query,
+            stations_5_ghz_query: stations_5_ghz_query.into(),
             transmit_ap: Default::default(),
-            transmit_ap_query,
+            transmit_ap_query: transmit_ap_query.into(),
             transmit_wan_24_ghz: Default::default(),
-            transmit_wan_24_ghz_query,
+            transmit_wan_24_ghz_query: transmit_wan_24_ghz_query.into(),
             transmit_wan_5_ghz: Default::default(),
-            transmit_wan_5_ghz_query,
+            transmit_wan_5_ghz_query: transmit_wan_5_ghz_query.into(),
         }
     }
 
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub async fn layout(&self, _connection: &prometheus::Connection) -> Result<Layout> {
+    /// Forget the rates received so far, so values from a changed query aren't compared with them
+    pub fn reset(&self) {
+        self.receive_ap.reset();
+        self.receive_wan_24_ghz.reset();
+        self.receive_wan_5_ghz.reset();
+        self.transmit_ap.reset();
+        self.transmit_wan_24_ghz.reset();
+        self.transmit_wan_5_ghz.reset();
+    }
+
+    /// Queries for each value, by the name of its config field
+    pub fn queries(&self) -> Vec<(&'static str, Query)> {
+        vec![
+            (
+                "channel_utilization_24_ghz",
+                self.channel_utilization_24_ghz_query.clone(),
+            ),
+            (
+                "channel_utilization_5_ghz",
+                self.channel_utilization_5_ghz_query.clone(),
+            ),
+            ("receive_ap", self.receive_ap_query.clone()),
+            ("receive_wan_24_ghz", self.receive_wan_24_ghz_query.clone()),
+            ("receive_wan_5_ghz", self.receive_wan_5_ghz_query.clone()),
+            ("stations_24_ghz", self.stations_24_ghz_query.clone()),
+            ("stations_5_ghz", self.stations_5_ghz_query.clone()),
+            ("transmit_ap", self.transmit_ap_query.clone()),
+            (
+                "transmit_wan_24_ghz",
+                self.transmit_wan_24_ghz_query.clone(),
+            ),
+            ("transmit_wan_5_ghz", self.transmit_wan_5_ghz_query.clone()),
+        ]
+    }
+
+    pub async fn layout(&self, _source: &impl MetricsSource) -> Result<Layout> {
         Ok(Layout::AccessPoint)
     }
 
@@ -90,105 +129,104 @@ impl AccessPoint {
     }
 
     #[instrument(level="debug", skip_all, ret, fields(address = ?self.address))]
-    pub async fn update(&self, connection: &prometheus::Connection) -> Result<update::AccessPoint> {
-        self.channel_utilization_24_ghz.update(
-            *connection
-                .get_values(&self.channel_utilization_24_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0),
-        );
-
-        self.channel_utilization_5_ghz.update(
-            *connection
-                .get_values(&self.channel_utilization_5_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0),
-        );
-
-        self.receive_ap.update(
-            *connection
-                .get_values(&self.receive_ap_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let receive_ap_difference = self.receive_ap.difference();
-
-        self.receive_wan_24_ghz.update(
-            *connection
-                .get_values(&self.receive_wan_24_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let receive_wan_24_ghz_difference = self.receive_wan_24_ghz.difference();
-
-        self.receive_wan_5_ghz.update(
-            *connection
-                .get_values(&self.receive_wan_5_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let receive_wan_5_ghz_difference = self.receive_wan_5_ghz.difference();
-
-        self.stations_24_ghz.update(
-            *connection
-                .get_values(&self.stations_24_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-
-        self.stations_5_ghz.update(
-            *connection
-                .get_values(&self.stations_5_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-
-        self.transmit_ap.update(
-            *connection
-                .get_values(&self.transmit_ap_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let transmit_ap_difference = self.transmit_ap.difference();
-
-        self.transmit_wan_24_ghz.update(
-            *connection
-                .get_values(&self.transmit_wan_24_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let transmit_wan_24_ghz_difference = self.transmit_wan_24_ghz.difference();
-
-        self.transmit_wan_5_ghz.update(
-            *connection
-                .get_values(&self.transmit_wan_5_ghz_query)
-                .await?
-                .first()
-                .unwrap_or(&0.0) as u64,
-        );
-        let transmit_wan_5_ghz_difference = self.transmit_wan_5_ghz.difference();
-
-        Ok(update::AccessPoint::new(
+    pub async fn update(&self, source: &impl MetricsSource) -> Result<update::AccessPoint> {
+        let mut values = Vec::with_capacity(10);
+
+        for query in [
+            &self.channel_utilization_24_ghz_query,
+            &self.channel_utilization_5_ghz_query,
+            &self.receive_ap_query,
+            &self.receive_wan_24_ghz_query,
+            &self.receive_wan_5_ghz_query,
+            &self.stations_24_ghz_query,
+            &self.stations_5_ghz_query,
+            &self.transmit_ap_query,
+            &self.transmit_wan_24_ghz_query,
+            &self.transmit_wan_5_ghz_query,
+        ] {
+            let query = query.get();
+
+            values.push(*source.get_values(&query).await?.first().unwrap_or(&0.0));
+        }
+
+        let [channel_utilization_24_ghz, channel_utilization_5_ghz, receive_ap, receive_wan_24_ghz, receive_wan_5_ghz, stations_24_ghz, stations_5_ghz, transmit_ap, transmit_wan_24_ghz, transmit_wan_5_ghz] =
+            values[..]
+        else {
+            unreachable!("one value per query");
+        };
+
+        Ok(self.record(
+            [channel_utilization_24_ghz, channel_utilization_5_ghz],
+            [
+                receive_ap as u64,
+                receive_wan_24_ghz as u64,
+                receive_wan_5_ghz as u64,
+            ],
+            [stations_24_ghz as u64, stations_5_ghz as u64],
+            [
+                transmit_ap as u64,
+                transmit_wan_24_ghz as u64,
+                transmit_wan_5_ghz as u64,
+            ],
+        ))
+    }
+
+    /// Record values from any source
+    ///
+    /// Channel utilization is a ratio for the 2.4 GHz and 5 GHz radios.  Receive and transmit
+    /// rates are ordered AP, 2.4 GHz WAN, 5 GHz WAN.  Stations are ordered 2.4 GHz, 5 GHz.
+    pub fn record(
+        &self,
+        channel_utilization: [f64; 2],
+        receive: [u64; 3],
+        stations: [u64; 2],
+        transmit: [u64; 3],
+    ) -> update::AccessPoint {
+        let [channel_utilization_24_ghz, channel_utilization_5_ghz] = channel_utilization;
+        let [receive_ap, receive_wan_24_ghz, receive_wan_5_ghz] = receive;
+        let [stations_24_ghz, stations_5_ghz] = stations;
+        let [transmit_ap, transmit_wan_24_ghz, transmit_wan_5_ghz] = transmit;
+
+        self.channel_utilization_24_ghz
+            .update(channel_utilization_24_ghz);
+        self.channel_utilization_5_ghz
+            .update(channel_utilization_5_ghz);
+
+        self.receive_ap.update(receive_ap);
+        self.receive_wan_24_ghz.update(receive_wan_24_ghz);
+        self.receive_wan_5_ghz.update(receive_wan_5_ghz);
+
+        self.stations_24_ghz.update(stations_24_ghz);
+        self.stations_5_ghz.update(stations_5_ghz);
+
+        self.transmit_ap.update(transmit_ap);
+        self.transmit_wan_24_ghz.update(transmit_wan_24_ghz);
+        self.transmit_wan_5_ghz.update(transmit_wan_5_ghz);
+
+        update::AccessPoint::new(
             (self.channel_utilization_24_ghz.value() * 100.0) as u64,
             (self.channel_utilization_5_ghz.value() * 100.0) as u64,
-            receive_ap_difference,
-            receive_wan_24_ghz_difference,
-            receive_wan_5_ghz_difference,
+            self.receive_ap.difference(),
+            self.receive_wan_24_ghz.difference(),
+            self.receive_wan_5_ghz.difference(),
             self.stations_24_ghz.value(),
             self.stations_5_ghz.value(),
-            transmit_ap_difference,
-            transmit_wan_24_ghz_difference,
-            transmit_wan_5_ghz_difference,
-        ))
+            self.transmit_ap.difference(),
+            self.transmit_wan_24_ghz.difference(),
+            self.transmit_wan_5_ghz.difference(),
+        )
+        .with_rates(
+            [
+                self.receive_ap.current(),
+                self.receive_wan_24_ghz.current(),
+                self.receive_wan_5_ghz.current(),
+            ],
+            [
+                self.transmit_ap.current(),
+                self.transmit_wan_24_ghz.current(),
+                self.transmit_wan_5_ghz.current(),
+            ],
+        )
     }
 }
 
@@ -3,9 +3,11 @@ use std::{
     sync::{Mutex, OnceLock},
 };
 
+use serde::{Deserialize, Serialize};
+
 static ID: OnceLock<Mutex<u64>> = OnceLock::new();
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Id(u64);
 
 impl Display for Id {
@@ -0,0 +1,31 @@
+use std::sync::{Arc, RwLock};
+
+/// A PromQL query that can be replaced while the device is running
+#[derive(Clone, Default)]
+pub struct Query {
+    inner: Arc<RwLock<String>>,
+}
+
+impl Query {
+    pub fn get(&self) -> String {
+        self.inner.read().unwrap().clone()
+    }
+
+    pub fn set(&self, query: impl Into<String>) {
+        *self.inner.write().unwrap() = query.into();
+    }
+}
+
+impl From<String> for Query {
+    fn from(query: String) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(query)),
+        }
+    }
+}
+
+impl From<&str> for Query {
+    fn from(query: &str) -> Self {
+        query.to_string().into()
+    }
+}
@@ -3,8 +3,8 @@ use rand::{distributions::Uniform, prelude::Distribution, rngs::SmallRng, seq::S
 use tracing::instrument;
 
 use crate::{
-    collector::{prometheus, Absolute, Diff},
-    device::Id,
+    collector::{Absolute, Diff, MetricsSource},
+    device::{Id, Query},
     simulator::Simulated,
     update, Layout,
 };
@@ -17,11 +17,11 @@ pub struct Switch {
     address: String,
     labels: String,
     receive: Diff<Vec<u64>>,
-    receive_query: String,
+    receive_query: Query,
     transmit: Diff<Vec<u64>>,
-    transmit_query: String,
+    transmit_query: Query,
     poe: Absolute<Vec<u64>>,
-    poe_query: String,
+    poe_query: Query,
 }
 
 impl Switch {
@@ -48,9 +48,24 @@ impl Switch {
         self.address.clone()
     }
 
+    /// Queries for each value, by the name of its config field
+    pub fn queries(&self) -> Vec<(&'static str, Query)> {
+        vec![
+            ("receive", self.receive_query.clone()),
+            ("transmit", self.transmit_query.clone()),
+            ("poe", self.poe_query.clone()),
+        ]
+    }
+
+    /// Forget the rates received so far, so values from a changed query aren't compared with them
+    pub fn reset(&self) {
+        self.receive.reset();
+        self.transmit.reset();
+    }
+
     #[instrument(skip_all, fields(labels = ?self.labels))]
-    pub async fn layout(&self, connection: &prometheus::Connection) -> Result<Layout> {
-        Layout::new(connection, &self.labels).await
+    pub async fn layout(&self, source: &impl MetricsSource) -> Result<Layout> {
+        Layout::new(source, &self.labels).await
     }
 
     // TODO: Return simulation data, let Device::simulate set id
@@ -76,54 +91,61 @@ impl Switch {
     }
 
     #[instrument(level="debug", skip_all, ret, fields(labels = ?self.labels))]
-    pub async fn update(&self, connection: &prometheus::Connection) -> Result<update::Switch> {
-        self.receive.update(
-            connection
-                .get_values(&self.receive_query)
-                .await?
-                .iter()
-                .map(|v| *v as u64)
-                .collect(),
-        );
-        let receive_difference = self.receive.difference();
-
-        self.transmit.update(
-            connection
-                .get_values(&self.transmit_query)
-                .await?
-                .iter()
-                .map(|v| *v as u64)
-                .collect(),
-        );
-        let transmit_difference = self.transmit.difference();
-
-        self.update_poe(connection).await?;
-
-        Ok(update::Switch::new(
-            receive_difference,
-            transmit_difference,
-            (&self.poe).into(),
-        ))
-    }
+    pub async fn update(&self, source: &impl MetricsSource) -> Result<update::Switch> {
+        let receive = source
+            .get_values(&self.receive_query.get())
+            .await?
+            .iter()
+            .map(|v| *v as u64)
+            .collect();
 
-    async fn update_poe(&self, connection: &prometheus::Connection) -> Result<()> {
-        let mut poe = vec![0; self.receive.len()];
+        let transmit = source
+            .get_values(&self.transmit_query.get())
+            .await?
+            .iter()
+            .map(|v| *v as u64)
+            .collect();
 
-        connection
-            .get_values_with_label(&self.poe_query, "port_num")
+        let poe = source
+            .get_values_with_label(&self.poe_query.get(), "port_num")
             .await?
             .iter()
-            .map(|(v, l)| {
+            .map(|(amperes, l)| {
                 (
-                    v,
                     l.clone().and_then(|l| l.parse::<usize>().ok()).unwrap_or(0),
+                    (amperes * 1000.0).round() as u64,
                 )
             })
-            .for_each(|(v, p)| poe[p] = *v);
+            .collect();
+
+        Ok(self.record(receive, transmit, poe))
+    }
+
+    /// Record per-port receive and transmit rates and PoE `(port, milliamps)` pairs from any source
+    pub fn record(
+        &self,
+        receive: Vec<u64>,
+        transmit: Vec<u64>,
+        poe_ports: Vec<(usize, u64)>,
+    ) -> update::Switch {
+        self.receive.update(receive);
+        let receive_difference = self.receive.difference();
+
+        self.transmit.update(transmit);
+        let transmit_difference = self.transmit.difference();
+
+        let mut poe = vec![0; self.receive.len()];
+
+        for (port, value) in poe_ports {
+            if let Some(poe) = poe.get_mut(port) {
+                *poe = value;
+            }
+        }
 
         self.poe.update(poe);
 
-        Ok(())
+        update::Switch::new(receive_difference, transmit_difference, (&self.poe).into())
+            .with_rates(self.receive.current(), self.transmit.current())
     }
 }
 
@@ -135,3 +157,50 @@ impl std::fmt::Debug for Switch {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::collector::Fake;
+
+    #[tokio::test]
+    async fn update() {
+        let switch = Switch::new(
+            "switch",
+            "instance=\"switch\"",
+            "receive",
+            "transmit",
+            "poe",
+        );
+
+        let mut source = Fake::default();
+        source.insert("receive", 30.0, &[("ifIndex", "3")]);
+        source.insert("receive", 10.0, &[("ifIndex", "1")]);
+        source.insert("receive", 20.0, &[("ifIndex", "2")]);
+        source.insert("transmit", 1.0, &[("ifIndex", "1")]);
+        source.insert("transmit", 2.0, &[("ifIndex", "2")]);
+        source.insert("transmit", 3.0, &[("ifIndex", "3")]);
+        source.insert("poe", 0.25, &[("port_num", "1")]);
+        source.insert("poe", 5.0, &[("port_num", "17")]);
+
+        let update = switch.update(&source).await.unwrap();
+
+        assert_eq!(&vec![0; 3], update.receive());
+        assert_eq!(&vec![0, 250, 0], update.poe());
+
+        let mut source = Fake::default();
+        source.insert("receive", 15.0, &[("ifIndex", "1")]);
+        source.insert("receive", 25.0, &[("ifIndex", "2")]);
+        source.insert("receive", 45.0, &[("ifIndex", "3")]);
+        source.insert("transmit", 1.0, &[("ifIndex", "1")]);
+        source.insert("transmit", 2.0, &[("ifIndex", "2")]);
+        source.insert("transmit", 3.0, &[("ifIndex", "3")]);
+
+        let update = switch.update(&source).await.unwrap();
+
+        assert_eq!(&vec![5, 5, 15], update.receive());
+        assert_eq!(&vec![15, 25, 45], update.receive_rate());
+        assert_eq!(&vec![0; 3], update.transmit());
+        assert_eq!(&vec![0; 3], update.poe());
+    }
+}
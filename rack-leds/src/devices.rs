@@ -1,24 +1,54 @@
 use std::{collections::HashMap, sync::Arc};
 
+use eyre::{OptionExt, Result};
+
 use crate::{
     device::{Device, Id},
-    Columns,
+    Display,
 };
 
 pub struct Devices {
-    columns: Columns,
+    displays: Vec<Display>,
     devices: HashMap<Id, Arc<Device>>,
 }
 impl Devices {
-    pub fn new(columns: Columns, devices: HashMap<Id, Arc<Device>>) -> Self {
-        Self { columns, devices }
+    pub fn new(displays: Vec<Display>, devices: HashMap<Id, Arc<Device>>) -> Self {
+        Self { displays, devices }
+    }
+
+    /// The display named `name`, or the first display
+    pub fn display(&self, name: Option<&str>) -> Result<&Display> {
+        match name {
+            Some(name) => self
+                .displays
+                .iter()
+                .find(|display| display.name() == name)
+                .ok_or_eyre(format!("No display named {name}")),
+            None => self.displays.first().ok_or_eyre("No displays configured"),
+        }
     }
 
-    pub fn columns(&self) -> &Columns {
-        &self.columns
+    pub fn displays(&self) -> &[Display] {
+        &self.displays
     }
 
     pub fn devices(&self) -> HashMap<Id, Arc<Device>> {
         self.devices.clone()
     }
+
+    /// The address of each device
+    pub fn addresses(&self) -> HashMap<Id, String> {
+        self.devices
+            .iter()
+            .map(|(id, device)| (*id, device.address()))
+            .collect()
+    }
+
+    /// Each device by address
+    pub fn ids(&self) -> HashMap<String, Id> {
+        self.devices
+            .iter()
+            .map(|(id, device)| (device.address(), *id))
+            .collect()
+    }
 }
@@ -0,0 +1,86 @@
+use crate::{Brightness, Columns, Geometry, Heartbeat, Output, Overlay, Palette};
+
+/// A named LED panel showing columns of devices
+#[derive(Clone)]
+pub struct Display {
+    name: String,
+    columns: Columns,
+    geometry: Geometry,
+    palette: Palette,
+    brightness: Brightness,
+    heartbeat: Option<Heartbeat>,
+    overlays: Vec<Overlay>,
+    outputs: Vec<Output>,
+}
+
+impl Display {
+    /// A display at full brightness without a heartbeat, overlays or outputs
+    pub fn new(name: String, columns: Columns, geometry: Geometry, palette: Palette) -> Self {
+        Self {
+            name,
+            columns,
+            geometry,
+            palette,
+            brightness: Brightness::default(),
+            heartbeat: None,
+            overlays: vec![],
+            outputs: vec![],
+        }
+    }
+
+    pub fn with_brightness(mut self, brightness: Brightness) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    pub fn with_heartbeat(mut self, heartbeat: Option<Heartbeat>) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    pub fn with_overlays(mut self, overlays: Vec<Overlay>) -> Self {
+        self.overlays = overlays;
+        self
+    }
+
+    pub fn with_outputs(mut self, outputs: Vec<Output>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn columns(&self) -> &Columns {
+        &self.columns
+    }
+
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn brightness(&self) -> &Brightness {
+        &self.brightness
+    }
+
+    pub fn heartbeat(&self) -> Option<&Heartbeat> {
+        self.heartbeat.as_ref()
+    }
+
+    pub fn overlays(&self) -> &[Overlay] {
+        &self.overlays
+    }
+
+    pub fn outputs(&self) -> &[Output] {
+        &self.outputs
+    }
+}
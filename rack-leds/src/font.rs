@@ -0,0 +1,114 @@
+//! A 3x5 bitmap font for text on LED panels
+//!
+//! Letters are upper case only, lower case letters are drawn as upper case and characters without
+//! a glyph are drawn as `?`.
+
+pub const WIDTH: u16 = 3;
+pub const HEIGHT: u16 = 5;
+
+/// Space between characters
+const SPACING: u16 = 1;
+
+/// Rows of a glyph from the top, the high bit of each row is the leftmost pixel
+type Glyph = [u8; HEIGHT as usize];
+
+fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+/// Width of `text` in pixels
+pub fn width(text: &str) -> u16 {
+    let count = text.chars().count() as u16;
+
+    (count * (WIDTH + SPACING)).saturating_sub(SPACING)
+}
+
+/// Positions of the lit pixels of `text` relative to its top left corner
+pub fn pixels(text: &str) -> impl Iterator<Item = (u16, u16)> + '_ {
+    text.chars().enumerate().flat_map(|(index, c)| {
+        let left = index as u16 * (WIDTH + SPACING);
+
+        glyph(c).into_iter().enumerate().flat_map(move |(y, row)| {
+            (0..WIDTH)
+                .filter(move |x| row & (1 << (WIDTH - 1 - x)) != 0)
+                .map(move |x| (left + x, y as u16))
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn pixels() {
+        let lit: Vec<_> = super::pixels("1.").collect();
+
+        assert_eq!(
+            vec![
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (0, 4),
+                (1, 4),
+                (2, 4),
+                (5, 4)
+            ],
+            lit
+        );
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!(0, super::width(""));
+        assert_eq!(3, super::width("a"));
+        assert_eq!(11, super::width("1:2"));
+    }
+}
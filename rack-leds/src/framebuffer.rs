@@ -0,0 +1,92 @@
+use ratatui::layout::{Position, Rect};
+
+/// RGB pixels of an LED panel, black until drawn on
+///
+/// Devices, overlays and the heartbeat draw here.  [`crate::PngBuilder`] encodes a framebuffer
+/// for LEDs and the web, and it renders as a ratatui widget for the TUI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Framebuffer {
+    width: u16,
+    height: u16,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0, 0, 0]; width as usize * height as usize],
+        }
+    }
+
+    /// The area of the whole framebuffer
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<[u8; 3]> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Set one pixel, pixels outside the framebuffer are ignored
+    pub fn set(&mut self, x: u16, y: u16, color: [u8; 3]) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Set a pixel relative to the top left of `area`, pixels outside `area` are ignored
+    pub fn set_in(&mut self, area: Rect, x: u16, y: u16, color: [u8; 3]) {
+        if x < area.width && y < area.height {
+            self.set(area.x + x, area.y + y, color);
+        }
+    }
+
+    /// Set every pixel of `area`
+    pub fn fill(&mut self, area: Rect, color: [u8; 3]) {
+        for Position { x, y } in area.intersection(self.area()).positions() {
+            self.set(x, y, color);
+        }
+    }
+
+    /// Replace every pixel with the result of `f`
+    pub fn map(&mut self, f: impl Fn([u8; 3]) -> [u8; 3]) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = f(*pixel));
+    }
+
+    /// Pixels row by row
+    pub fn pixels(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+
+        framebuffer.fill(Rect::new(1, 1, 5, 5), [1, 2, 3]);
+
+        assert_eq!(Some([0, 0, 0]), framebuffer.get(1, 0));
+        assert_eq!(Some([0, 0, 0]), framebuffer.get(0, 1));
+        assert_eq!(Some([1, 2, 3]), framebuffer.get(1, 1));
+        assert_eq!(Some([1, 2, 3]), framebuffer.get(2, 1));
+        assert_eq!(None, framebuffer.get(3, 1));
+    }
+}
@@ -0,0 +1,258 @@
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::PngBuilder;
+
+/// Where the rack is drawn on an LED panel and how the panel is mounted
+///
+/// The rack is drawn on a canvas offset by `x` and `y`, then the canvas is flipped, rotated to
+/// fit the panel, and scaled up.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Geometry {
+    pub panel: Panel,
+    pub x: u16,
+    pub y: u16,
+    pub rotation: Rotation,
+    pub flip: Flip,
+    /// Integer upscaling for browser views, LED panels need 1
+    pub scale: u16,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            panel: Panel::default(),
+            x: 0,
+            y: 0,
+            rotation: Rotation::default(),
+            flip: Flip::default(),
+            scale: 1,
+        }
+    }
+}
+
+impl Geometry {
+    /// The canvas the rack is drawn on, the panel before rotation
+    pub fn canvas(&self) -> Rect {
+        let (width, height) = self.panel.size();
+
+        if self.rotation.is_quarter_turn() {
+            Rect::new(0, 0, height, width)
+        } else {
+            Rect::new(0, 0, width, height)
+        }
+    }
+
+    /// The area of the canvas the rack is drawn in
+    pub fn rack(&self) -> Rect {
+        let canvas = self.canvas();
+
+        let x = self.x.min(canvas.width);
+        let y = self.y.min(canvas.height);
+
+        Rect::new(x, y, canvas.width - x, canvas.height - y)
+    }
+
+    /// Flip, rotate and scale a rendered canvas for the panel
+    pub fn transform(&self, canvas: &PngBuilder) -> PngBuilder {
+        let (width, height) = self.panel.size();
+        let (width, height) = (width as u32, height as u32);
+        let (canvas_width, canvas_height) = (canvas.width(), canvas.height());
+        let scale = self.scale.max(1) as u32;
+
+        PngBuilder::from_fn(width * scale, height * scale, |x, y| {
+            let (x, y) = (x / scale, y / scale);
+
+            let (x, y) = match self.rotation {
+                Rotation::None => (x, y),
+                Rotation::Clockwise => (y, canvas_height.wrapping_sub(1 + x)),
+                Rotation::UpsideDown => (
+                    canvas_width.wrapping_sub(1 + x),
+                    canvas_height.wrapping_sub(1 + y),
+                ),
+                Rotation::Counterclockwise => (canvas_width.wrapping_sub(1 + y), x),
+            };
+
+            let (x, y) = match self.flip {
+                Flip::None => (x, y),
+                Flip::Horizontal => (canvas_width.wrapping_sub(1 + x), y),
+                Flip::Vertical => (x, canvas_height.wrapping_sub(1 + y)),
+            };
+
+            canvas.pixel(x, y).unwrap_or_default()
+        })
+    }
+}
+
+/// An LED panel size
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Panel {
+    /// Pimoroni Galactic Unicorn, 53x11
+    #[default]
+    GalacticUnicorn,
+    /// Pimoroni Cosmic Unicorn, 32x32
+    CosmicUnicorn,
+    /// Pimoroni Stellar Unicorn, 16x16
+    StellarUnicorn,
+    Custom {
+        width: u16,
+        height: u16,
+    },
+}
+
+impl Panel {
+    /// Width and height in LEDs
+    pub fn size(&self) -> (u16, u16) {
+        match self {
+            Panel::GalacticUnicorn => (53, 11),
+            Panel::CosmicUnicorn => (32, 32),
+            Panel::StellarUnicorn => (16, 16),
+            Panel::Custom { width, height } => (*width, *height),
+        }
+    }
+}
+
+/// Clockwise rotation of the canvas to match how the panel is mounted
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise,
+    UpsideDown,
+    Counterclockwise,
+}
+
+impl Rotation {
+    fn is_quarter_turn(&self) -> bool {
+        matches!(self, Rotation::Clockwise | Rotation::Counterclockwise)
+    }
+}
+
+/// Mirroring of the canvas before rotation
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Flip {
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A 3x2 canvas with distinct pixels
+    ///
+    /// ```text
+    /// 0 1 2
+    /// 3 4 5
+    /// ```
+    fn canvas() -> PngBuilder {
+        PngBuilder::from_fn(3, 2, |x, y| [(y * 3 + x) as u8, 0, 0])
+    }
+
+    fn pixels(png: &PngBuilder) -> Vec<Vec<u8>> {
+        (0..png.height())
+            .map(|y| {
+                (0..png.width())
+                    .map(|x| png.pixel(x, y).unwrap()[0])
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn geometry(rotation: Rotation, flip: Flip) -> Geometry {
+        let panel = if rotation.is_quarter_turn() {
+            Panel::Custom {
+                width: 2,
+                height: 3,
+            }
+        } else {
+            Panel::Custom {
+                width: 3,
+                height: 2,
+            }
+        };
+
+        Geometry {
+            panel,
+            rotation,
+            flip,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn canvas_rack() {
+        let geometry = Geometry {
+            panel: Panel::CosmicUnicorn,
+            x: 2,
+            y: 40,
+            ..Default::default()
+        };
+
+        assert_eq!(Rect::new(0, 0, 32, 32), geometry.canvas());
+        assert_eq!(Rect::new(2, 32, 30, 0), geometry.rack());
+
+        let geometry = Geometry {
+            panel: Panel::Custom {
+                width: 8,
+                height: 4,
+            },
+            rotation: Rotation::Clockwise,
+            ..Default::default()
+        };
+
+        assert_eq!(Rect::new(0, 0, 4, 8), geometry.canvas());
+    }
+
+    #[test]
+    fn transform() {
+        let canvas = canvas();
+
+        let transformed = geometry(Rotation::None, Flip::None).transform(&canvas);
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4, 5]], pixels(&transformed));
+
+        let transformed = geometry(Rotation::Clockwise, Flip::None).transform(&canvas);
+        assert_eq!(
+            vec![vec![3, 0], vec![4, 1], vec![5, 2]],
+            pixels(&transformed)
+        );
+
+        let transformed = geometry(Rotation::UpsideDown, Flip::None).transform(&canvas);
+        assert_eq!(vec![vec![5, 4, 3], vec![2, 1, 0]], pixels(&transformed));
+
+        let transformed = geometry(Rotation::Counterclockwise, Flip::None).transform(&canvas);
+        assert_eq!(
+            vec![vec![2, 5], vec![1, 4], vec![0, 3]],
+            pixels(&transformed)
+        );
+
+        let transformed = geometry(Rotation::None, Flip::Horizontal).transform(&canvas);
+        assert_eq!(vec![vec![2, 1, 0], vec![5, 4, 3]], pixels(&transformed));
+
+        let transformed = geometry(Rotation::None, Flip::Vertical).transform(&canvas);
+        assert_eq!(vec![vec![3, 4, 5], vec![0, 1, 2]], pixels(&transformed));
+    }
+
+    #[test]
+    fn transform_scale() {
+        let geometry = Geometry {
+            scale: 2,
+            ..geometry(Rotation::None, Flip::None)
+        };
+
+        let transformed = geometry.transform(&canvas());
+
+        assert_eq!(
+            vec![
+                vec![0, 0, 1, 1, 2, 2],
+                vec![0, 0, 1, 1, 2, 2],
+                vec![3, 3, 4, 4, 5, 5],
+                vec![3, 3, 4, 4, 5, 5],
+            ],
+            pixels(&transformed)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::time::timeout;
+
+use crate::{collector::UpdateReceiver, Framebuffer};
+
+/// A pixel reserved to show updates are arriving
+///
+/// The pixel at `x` and `y` on the canvas alternates between `color` and a dim `color` on each
+/// update.  Once no update has arrived for a period it fades to `stale` over the next two periods.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Heartbeat {
+    pub x: u16,
+    pub y: u16,
+    pub color: [u8; 3],
+    pub stale: [u8; 3],
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            color: [0, 255, 0],
+            stale: [255, 0, 0],
+        }
+    }
+}
+
+impl Heartbeat {
+    /// The pixel color after `beats` updates, the last one `age` ago
+    pub fn color_at(&self, beats: u64, age: Duration, period: Duration) -> [u8; 3] {
+        let color = if beats.is_multiple_of(2) {
+            self.color.map(|channel| channel / 4)
+        } else {
+            self.color
+        };
+
+        let overdue = age.saturating_sub(period).as_secs_f32();
+        let fade = (overdue / (2.0 * period.as_secs_f32().max(f32::EPSILON))).min(1.0);
+
+        std::array::from_fn(|index| {
+            let from = f32::from(color[index]);
+            let to = f32::from(self.stale[index]);
+
+            (from + (to - from) * fade).round() as u8
+        })
+    }
+
+    /// Draw the pixel for `pulse` on the display canvas
+    pub fn render(&self, pulse: &Pulse, framebuffer: &mut Framebuffer) {
+        let color = self.color_at(pulse.beats, pulse.last.elapsed(), pulse.period);
+
+        framebuffer.set(self.x, self.y, color);
+    }
+}
+
+/// Counts updates as they are received for a [`Heartbeat`]
+pub struct Pulse {
+    beats: u64,
+    last: Instant,
+    period: Duration,
+}
+
+impl Pulse {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            beats: 0,
+            last: Instant::now(),
+            period,
+        }
+    }
+
+    /// Time since the last update
+    pub fn since(&self) -> Duration {
+        self.last.elapsed()
+    }
+
+    /// Record an update
+    pub fn beat(&mut self) {
+        self.beats += 1;
+        self.last = Instant::now();
+    }
+
+    /// Wait for the next update, or for one period so a stale frame can be redrawn
+    pub async fn wait(&mut self, updates: &mut UpdateReceiver) -> Result<()> {
+        if let Ok(changed) = timeout(self.period, updates.changed()).await {
+            changed?;
+
+            self.beat();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_at() {
+        let heartbeat = Heartbeat {
+            color: [0, 200, 0],
+            stale: [100, 0, 0],
+            ..Default::default()
+        };
+        let period = Duration::from_secs(10);
+
+        assert_eq!([0, 200, 0], heartbeat.color_at(1, Duration::ZERO, period));
+        assert_eq!([0, 50, 0], heartbeat.color_at(2, Duration::ZERO, period));
+        assert_eq!([0, 200, 0], heartbeat.color_at(3, period, period));
+        assert_eq!(
+            [50, 100, 0],
+            heartbeat.color_at(3, Duration::from_secs(20), period)
+        );
+        assert_eq!(
+            [100, 0, 0],
+            heartbeat.color_at(4, Duration::from_secs(300), period)
+        );
+    }
+}
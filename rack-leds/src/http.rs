@@ -1,40 +1,69 @@
-use std::{future::Future, net::SocketAddr, pin::Pin, time::Duration};
+mod recent;
+
+use std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
 
 use bytes::Bytes;
 use eyre::Result;
 use http_body_util::Full;
 use httpdate::fmt_http_date;
 use hyper::{
-    body::Incoming, header::ALLOW, server::conn::http1, service::Service, Method, Request,
-    Response, StatusCode,
+    body::Incoming,
+    header::{ALLOW, CONTENT_TYPE},
+    server::conn::http1,
+    service::Service,
+    Method, Request, Response, StatusCode,
 };
 use hyper_util::rt::TokioIo;
 use tokio::net::{TcpListener, TcpStream};
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 use crate::png_builder::PngReceiver;
+pub use recent::Recent;
 
+/// Serve PNGs of each display
+///
+/// `/current.png` is the first display, every display is at `/displays/{name}.png`.
 pub struct Http {
     addr: SocketAddr,
+    displays: Vec<String>,
     png: PngReceiver,
+    recent: Recent,
     period: Duration,
 }
 
 impl Http {
-    pub fn new(addr: SocketAddr, png: PngReceiver, period: Duration) -> Result<Self> {
-        Ok(Self { addr, png, period })
+    pub fn new(
+        addr: SocketAddr,
+        displays: Vec<String>,
+        png: PngReceiver,
+        recent: Recent,
+        period: Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            addr,
+            displays,
+            png,
+            recent,
+            period,
+        })
     }
 
     #[instrument(name = "http", skip_all, fields(addr = ?self.addr))]
     pub async fn run(self) -> Result<()> {
-        let Self { addr, png, period } = self;
+        let Self {
+            addr,
+            displays,
+            png,
+            recent,
+            period,
+        } = self;
 
         let listener = TcpListener::bind(addr).await?;
 
         info!("listening");
         let mut task_id = 0usize;
 
-        let service = PngService::new(png, period);
+        let service = PngService::new(displays, png, recent, period);
 
         loop {
             let (stream, _) = listener.accept().await?;
@@ -65,13 +94,64 @@ async fn http_client(_id: usize, io: TokioIo<TcpStream>, service: PngService) ->
 
 #[derive(Clone)]
 struct PngService {
+    displays: Arc<Vec<String>>,
     png: PngReceiver,
+    recent: Recent,
     period: Duration,
 }
 
 impl PngService {
-    fn new(png: PngReceiver, period: Duration) -> Self {
-        Self { png, period }
+    fn new(displays: Vec<String>, png: PngReceiver, recent: Recent, period: Duration) -> Self {
+        Self {
+            displays: Arc::new(displays),
+            png,
+            recent,
+            period,
+        }
+    }
+
+    /// The display name for a PNG path
+    fn display<'a>(&'a self, path: &'a str) -> Option<&'a str> {
+        if path == "/current.png" {
+            return self.displays.first().map(|name| name.as_str());
+        }
+
+        let name = path.strip_prefix("/displays/")?.strip_suffix(".png")?;
+
+        self.displays
+            .iter()
+            .any(|display| display == name)
+            .then_some(name)
+    }
+
+    fn recent(&self) -> Response<Full<Bytes>> {
+        let animation = match self.recent.animation() {
+            Ok(Some(animation)) => animation,
+            Ok(None) => {
+                return Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .body(Full::new(Bytes::new()))
+                    .unwrap()
+            }
+            Err(e) => {
+                error!(?e, "unable to build recent animation");
+
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Full::new(Bytes::new()))
+                    .unwrap();
+            }
+        };
+
+        let mut response = Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "image/apng");
+
+        if let Some(updated) = self.recent.updated() {
+            response = response.header("Expires", fmt_http_date(updated + self.period));
+        }
+
+        response.body(Full::new(animation)).unwrap()
     }
 }
 
@@ -99,14 +179,23 @@ impl Service<Request<Incoming>> for PngService {
         if req.uri().path() == "/" {
             let refresh = self.period.as_secs();
 
+            let images: String = self
+                .displays
+                .iter()
+                .map(|name| {
+                    format!(
+                        "<img style=\"width: 100%\" src=\"/displays/{name}.png\" alt=\"{name}\">\n"
+                    )
+                })
+                .collect();
+
             let body = Bytes::from(format!(
                 "<!DOCTYPE html>
 <head>
 <title>Rack LEDS</title>
 <meta http-equiv=\"refresh\" content=\"{refresh}\">
 <body style=\"background: black\">
-<img style=\"width: 100%\" src=\"/current.png\">
-"
+{images}"
             ));
 
             return Box::pin(async move {
@@ -117,16 +206,26 @@ impl Service<Request<Incoming>> for PngService {
             });
         }
 
-        if req.uri().path() != "/current.png" {
+        if req.uri().path() == "/recent.apng" {
+            let response = self.recent();
+
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let Some(display) = self.display(req.uri().path()) else {
             return Box::pin(async {
                 Ok(Response::builder()
                     .status(StatusCode::NOT_FOUND)
                     .body(Full::new(Bytes::new()))
                     .unwrap())
             });
-        }
+        };
+
+        let (current_png, updated) = {
+            let (pngs, updated) = &*self.png.borrow();
 
-        let (current_png, updated) = self.png.borrow().clone();
+            (pngs.get(display).cloned().unwrap_or_default(), *updated)
+        };
 
         let expires = updated + self.period;
 
@@ -0,0 +1,167 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use bytes::Bytes;
+use eyre::{Context, Result};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, instrument};
+
+use crate::{collector::UpdateReceiver, heartbeat::Pulse, render, Display, PngBuilder};
+
+/// A rendered frame, shared so it can be encoded without holding the lock
+type Frame = (Arc<PngBuilder>, SystemTime);
+
+/// The most recently rendered frames of the first display for `/recent.apng`
+///
+/// Each frame is shown for the time until the next update.  The newest frame is shown for one
+/// period.
+#[derive(Clone)]
+pub struct Recent {
+    capacity: usize,
+    frames: Arc<Mutex<VecDeque<Frame>>>,
+    period: Duration,
+}
+
+impl Recent {
+    pub fn new(capacity: usize, period: Duration) -> Self {
+        Self {
+            capacity,
+            frames: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            period,
+        }
+    }
+
+    /// Add a frame, dropping the oldest frame when full
+    pub fn push(&self, frame: PngBuilder, time: SystemTime) {
+        let mut frames = self.frames.lock().unwrap();
+
+        if frames.len() >= self.capacity {
+            frames.pop_front();
+        }
+
+        frames.push_back((Arc::new(frame), time));
+    }
+
+    /// Time of the newest frame
+    pub fn updated(&self) -> Option<SystemTime> {
+        self.frames.lock().unwrap().back().map(|(_, time)| *time)
+    }
+
+    /// Build an animated PNG of the recent frames
+    ///
+    /// Returns `None` when nothing has been rendered yet.  The frames are copied out so encoding
+    /// doesn't block new frames.
+    pub fn animation(&self) -> Result<Option<Bytes>> {
+        let frames = self.frames.lock().unwrap().clone();
+
+        if frames.is_empty() {
+            return Ok(None);
+        }
+
+        let delays = frames
+            .iter()
+            .zip(frames.iter().skip(1))
+            .map(|((_, time), (_, next))| next.duration_since(*time).unwrap_or_default())
+            .chain([self.period]);
+
+        let frames: Vec<_> = frames
+            .iter()
+            .map(|(frame, _)| frame.as_ref())
+            .zip(delays)
+            .collect();
+
+        let animation = PngBuilder::build_animation_with_delays(&frames)
+            .wrap_err("Unable to build recent animation")?;
+
+        Ok(Some(animation))
+    }
+
+    /// Render a frame for each update
+    #[instrument(name = "recent", skip_all, fields(capacity = self.capacity))]
+    pub async fn run(self, display: Display, mut updates: UpdateReceiver) -> Result<()> {
+        info!("started");
+
+        let mut pulse = Pulse::new(self.period);
+
+        loop {
+            updates.changed().await?;
+            pulse.beat();
+
+            let (frame, time) = {
+                let (updates, time) = &*updates.borrow_and_update();
+
+                (render::frame(&display, updates, *time, Some(&pulse)), *time)
+            };
+
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    error!(?e, "error rendering recent frame");
+                    continue;
+                }
+            };
+
+            self.push(frame, time);
+
+            debug!(?time, "rendered recent frame");
+        }
+    }
+
+    pub fn run_on(
+        self,
+        join_set: &mut JoinSet<Result<()>>,
+        display: Display,
+        updates: UpdateReceiver,
+    ) -> Result<()> {
+        join_set
+            .build_task()
+            .name("recent frames")
+            .spawn(async move { self.run(display, updates).await })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(red: u8) -> PngBuilder {
+        PngBuilder::from_fn(1, 1, |_, _| [red, 0, 0])
+    }
+
+    #[test]
+    fn animation() {
+        let recent = Recent::new(2, Duration::from_secs(15));
+
+        assert!(recent.animation().unwrap().is_none());
+
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        recent.push(frame(1), start);
+        recent.push(frame(2), start + Duration::from_secs(10));
+        recent.push(frame(3), start + Duration::from_secs(25));
+
+        assert_eq!(Some(start + Duration::from_secs(25)), recent.updated());
+
+        let png = recent.animation().unwrap().unwrap();
+
+        let mut reader = png::Decoder::new(png.as_ref()).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+
+        assert_eq!(2, reader.info().animation_control().unwrap().num_frames);
+
+        reader.next_frame(&mut data).unwrap();
+        let control = reader.info().frame_control().unwrap();
+        assert_eq!(2, data[0]);
+        assert_eq!(15_000, control.delay_num);
+
+        reader.next_frame(&mut data).unwrap();
+        let control = reader.info().frame_control().unwrap();
+        assert_eq!(3, data[0]);
+        assert_eq!(15_000, control.delay_num);
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 
 use ratatui_tracing::{EnvFilterResult, EventReceiver, RatatuiTracing, Reloadable};
 
-use crate::Args;
+use crate::{args::Command, Args};
 use clap::Parser;
 use color_eyre::config::HookBuilder;
 use eyre::Result;
@@ -76,7 +76,7 @@ pub(crate) fn eyre() -> Result<()> {
 }
 
 pub(crate) fn tracing(args: &Args) -> (Arc<AtomicBool>, EventReceiver, Reloadable) {
-    let (gui_active, reader, reloadable, log) = log_layer();
+    let (gui_active, reader, reloadable, log) = log_layer(args);
 
     let registry = tracing_subscriber::registry()
         .with(log)
@@ -94,7 +94,9 @@ pub(crate) fn tracing(args: &Args) -> (Arc<AtomicBool>, EventReceiver, Reloadabl
 /// A layer for logging either to stdout or ratatui depending on which is active
 ///
 /// The layer will be filtered by RUST_LOG if available
-fn log_layer() -> (
+fn log_layer(
+    args: &Args,
+) -> (
     Arc<AtomicBool>,
     EventReceiver,
     Reloadable,
@@ -104,7 +106,7 @@ fn log_layer() -> (
 
     let gui_active = Arc::new(AtomicBool::new(false));
 
-    let stdout = stdout_layer(&gui_active);
+    let stdout = stdout_layer(args, &gui_active);
 
     let (reader, tui) = ratatui_layer(&gui_active);
 
@@ -131,10 +133,25 @@ fn log_filter() -> (reload::Layer<EnvFilter, Registry>, Reloadable) {
 }
 
 /// Log to stdout when gui_active is false
-fn stdout_layer(gui_active: &Arc<AtomicBool>) -> Box<dyn Layer<Registry> + Send + Sync> {
-    let stdout = fmt::layer()
-        .with_ansi(std::io::stdout().is_terminal())
-        .with_timer(OffsetTime::local_rfc_3339().expect("could not get local offset!"));
+///
+/// Logs go to stderr instead when a rendered PNG is written to stdout
+fn stdout_layer(
+    args: &Args,
+    gui_active: &Arc<AtomicBool>,
+) -> Box<dyn Layer<Registry> + Send + Sync> {
+    let timer = OffsetTime::local_rfc_3339().expect("could not get local offset!");
+
+    let stdout = match &args.command {
+        Some(Command::Render(render)) if render.to_stdout() => fmt::layer()
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_timer(timer)
+            .with_writer(std::io::stderr)
+            .boxed(),
+        _ => fmt::layer()
+            .with_ansi(std::io::stdout().is_terminal())
+            .with_timer(timer)
+            .boxed(),
+    };
 
     let stdout_gui_active = gui_active.clone();
 
@@ -1,9 +1,10 @@
 use eyre::{OptionExt, Result};
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-use crate::collector::prometheus;
+use crate::collector::MetricsSource;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum Layout {
     AccessPoint,
     SwitchFive,
@@ -14,21 +15,17 @@ pub enum Layout {
 }
 
 impl Layout {
-    pub async fn new(connection: &prometheus::Connection, labels: impl Display) -> Result<Self> {
+    pub async fn new(source: &impl MetricsSource, labels: impl Display) -> Result<Self> {
         let query = format!("sysDescr{{{labels}}}");
-        let description = connection.get_label(query, "sysDescr").await?;
-
-        if description.starts_with("USW-8-150W,") {
-            return Ok(Self::SwitchEightPlusTwo);
-        } else if description.starts_with("US-8,") {
-            return Ok(Self::SwitchEight);
-        } else if description.starts_with("USW-Flex ") {
-            return Ok(Self::SwitchFive);
+        let description = source.get_label(&query, "sysDescr").await?;
+
+        if let Some(layout) = Self::described(&description) {
+            return Ok(layout);
         }
 
         let query = format!("count(ifHCInOctets{{{labels}, ifAlias=~\"(Port|SFP) .*\"}})");
-        let result: Vec<_> = connection
-            .get_values(query)
+        let result: Vec<_> = source
+            .get_values(&query)
             .await?
             .iter()
             .map(|v| *v as u64)
@@ -36,16 +33,24 @@ impl Layout {
 
         let interfaces = result.first().ok_or_eyre("No interfaces found")?;
 
-        match interfaces {
-            5 => Ok(Self::SwitchFive),
-            8 => Ok(Self::SwitchEight),
-            10 => Ok(Self::SwitchEightPlusTwo),
-            18 => Ok(Self::SwitchSixteenPlusTwo),
-            _ => Ok(Self::Unknown),
+        Ok(Self::ports(*interfaces as usize))
+    }
+
+    /// The layout for a known model from a system description like `sysDescr` or a model name
+    /// like `entPhysicalModelName`
+    pub fn described(description: &str) -> Option<Self> {
+        let model = description.split([',', ' ']).next()?;
+
+        match model {
+            "USW-8-150W" => Some(Self::SwitchEightPlusTwo),
+            "US-8" => Some(Self::SwitchEight),
+            "USW-Flex" => Some(Self::SwitchFive),
+            _ => None,
         }
     }
 
-    pub fn simulate(ports: usize) -> Self {
+    /// The layout for a switch with `ports` ports
+    pub fn ports(ports: usize) -> Self {
         match ports {
             5 => Self::SwitchFive,
             8 => Self::SwitchEight,
@@ -55,25 +60,33 @@ impl Layout {
         }
     }
 
-    pub fn coordinate(&self, index: usize) -> (f64, f64) {
-        match self {
+    pub fn simulate(ports: usize) -> Self {
+        Self::ports(ports)
+    }
+
+    /// Pixel of port `index` from the top left of the device
+    pub fn coordinate(&self, index: usize) -> (u16, u16) {
+        let (x, y) = match self {
             Layout::AccessPoint => match index {
-                0..=2 => (index as f64, 0.0),
-                3..=4 => ((index + 1) as f64, 0.0),
-                5.. => ((index + 2) as f64, 0.0),
+                0..=2 => (index, 0),
+                3..=4 => (index + 1, 0),
+                5.. => (index + 2, 0),
             },
-            Layout::SwitchFive | Layout::SwitchEight => (index as f64, 0.0),
-            Layout::SwitchEightPlusTwo => (if index < 8 { index } else { index + 1 } as f64, 0.0),
+            Layout::SwitchFive | Layout::SwitchEight => (index, 0),
+            Layout::SwitchEightPlusTwo => (if index < 8 { index } else { index + 1 }, 0),
+            // Even ports are on the bottom row
             Layout::SwitchSixteenPlusTwo => (
                 if index < 16 {
                     index / 2
                 } else {
                     (index / 2) + 1
-                } as f64,
-                (index % 2) as f64,
+                },
+                1 - index % 2,
             ),
-            Layout::Unknown => (0.0, 0.0),
-        }
+            Layout::Unknown => (0, 0),
+        };
+
+        (x as u16, y as u16)
     }
 
     pub fn height(&self) -> u16 {
@@ -95,79 +108,118 @@ impl Layout {
             Layout::Unknown => 1,
         }
     }
-
-    pub fn x_bound(&self) -> f64 {
-        self.width().saturating_sub(1).into()
-    }
-
-    pub fn y_bound(&self) -> f64 {
-        if self.height() == 1 {
-            1.0
-        } else {
-            self.height().saturating_sub(1).into()
-        }
-    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::collector::Fake;
+
+    #[tokio::test]
+    async fn new_described() {
+        let mut source = Fake::default();
+        source.insert(
+            "sysDescr{instance=\"switch\"}",
+            1.0,
+            &[("sysDescr", "US-8, 6.6.61.15220, Linux 3.6.5")],
+        );
+
+        let layout = Layout::new(&source, "instance=\"switch\"").await.unwrap();
+
+        assert!(matches!(layout, Layout::SwitchEight));
+    }
+
+    #[tokio::test]
+    async fn new_ports() {
+        let mut source = Fake::default();
+        source.insert(
+            "sysDescr{instance=\"switch\"}",
+            1.0,
+            &[("sysDescr", "Linux switch 4.4.153")],
+        );
+        source.insert(
+            "count(ifHCInOctets{instance=\"switch\", ifAlias=~\"(Port|SFP) .*\"})",
+            18.0,
+            &[],
+        );
+
+        let layout = Layout::new(&source, "instance=\"switch\"").await.unwrap();
+
+        assert!(matches!(layout, Layout::SwitchSixteenPlusTwo));
+    }
+
+    #[test]
+    fn described() {
+        assert!(matches!(
+            Layout::described("USW-8-150W, 6.6.61.15220, Linux 3.6.5"),
+            Some(Layout::SwitchEightPlusTwo)
+        ));
+        assert!(matches!(
+            Layout::described("USW-8-150W"),
+            Some(Layout::SwitchEightPlusTwo)
+        ));
+        assert!(matches!(
+            Layout::described("USW-Flex 2.1.5"),
+            Some(Layout::SwitchFive)
+        ));
+        assert!(Layout::described("USW-16-POE, 6.6.61").is_none());
+    }
 
     #[test]
     fn coordinate_access_point() {
         let layout = Layout::AccessPoint;
 
-        assert_eq!((0.0, 0.0), layout.coordinate(0));
-        assert_eq!((2.0, 0.0), layout.coordinate(2));
+        assert_eq!((0, 0), layout.coordinate(0));
+        assert_eq!((2, 0), layout.coordinate(2));
 
-        assert_eq!((4.0, 0.0), layout.coordinate(3));
-        assert_eq!((5.0, 0.0), layout.coordinate(4));
+        assert_eq!((4, 0), layout.coordinate(3));
+        assert_eq!((5, 0), layout.coordinate(4));
 
-        assert_eq!((7.0, 0.0), layout.coordinate(5));
+        assert_eq!((7, 0), layout.coordinate(5));
     }
 
     #[test]
     fn coordinate_switch_five() {
         let layout = Layout::SwitchFive;
 
-        assert_eq!((0.0, 0.0), layout.coordinate(0));
-        assert_eq!((1.0, 0.0), layout.coordinate(1));
-        assert_eq!((4.0, 0.0), layout.coordinate(4));
+        assert_eq!((0, 0), layout.coordinate(0));
+        assert_eq!((1, 0), layout.coordinate(1));
+        assert_eq!((4, 0), layout.coordinate(4));
     }
 
     #[test]
     fn coordinate_switch_eight() {
         let layout = Layout::SwitchEight;
 
-        assert_eq!((0.0, 0.0), layout.coordinate(0));
-        assert_eq!((1.0, 0.0), layout.coordinate(1));
-        assert_eq!((7.0, 0.0), layout.coordinate(7));
+        assert_eq!((0, 0), layout.coordinate(0));
+        assert_eq!((1, 0), layout.coordinate(1));
+        assert_eq!((7, 0), layout.coordinate(7));
     }
 
     #[test]
     fn coordinate_switch_eight_plus_two() {
         let layout = Layout::SwitchEightPlusTwo;
 
-        assert_eq!((0.0, 0.0), layout.coordinate(0));
-        assert_eq!((1.0, 0.0), layout.coordinate(1));
-        assert_eq!((7.0, 0.0), layout.coordinate(7));
+        assert_eq!((0, 0), layout.coordinate(0));
+        assert_eq!((1, 0), layout.coordinate(1));
+        assert_eq!((7, 0), layout.coordinate(7));
 
-        assert_eq!((9.0, 0.0), layout.coordinate(8));
-        assert_eq!((10.0, 0.0), layout.coordinate(9));
+        assert_eq!((9, 0), layout.coordinate(8));
+        assert_eq!((10, 0), layout.coordinate(9));
     }
 
     #[test]
     fn coordinate_switch_sixteen_plus_two() {
         let layout = Layout::SwitchSixteenPlusTwo;
 
-        assert_eq!((0.0, 0.0), layout.coordinate(0));
-        assert_eq!((0.0, 1.0), layout.coordinate(1));
-        assert_eq!((1.0, 0.0), layout.coordinate(2));
-        assert_eq!((1.0, 1.0), layout.coordinate(3));
-        assert_eq!((7.0, 0.0), layout.coordinate(14));
-        assert_eq!((7.0, 1.0), layout.coordinate(15));
+        assert_eq!((0, 1), layout.coordinate(0));
+        assert_eq!((0, 0), layout.coordinate(1));
+        assert_eq!((1, 1), layout.coordinate(2));
+        assert_eq!((1, 0), layout.coordinate(3));
+        assert_eq!((7, 1), layout.coordinate(14));
+        assert_eq!((7, 0), layout.coordinate(15));
 
-        assert_eq!((9.0, 0.0), layout.coordinate(16));
-        assert_eq!((9.0, 1.0), layout.coordinate(17));
+        assert_eq!((9, 1), layout.coordinate(16));
+        assert_eq!((9, 0), layout.coordinate(17));
     }
 }
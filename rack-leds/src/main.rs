@@ -1,30 +1,58 @@
 mod args;
+mod brightness;
 mod collector;
 mod column;
 mod columns;
 mod config;
 mod device;
 mod devices;
+mod display;
+mod font;
+mod framebuffer;
+mod geometry;
+mod heartbeat;
 mod http;
 mod init;
 mod layout;
+mod output;
+mod overlay;
+mod palette;
 mod png_builder;
+mod recorder;
+mod render;
+mod replay;
 mod simulator;
 mod ui;
+mod units;
 mod update;
 
-use std::sync::{atomic::AtomicBool, Arc};
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 pub use args::Args;
-use collector::Collector;
+use args::Command;
+pub use brightness::Brightness;
+use collector::{snmp::Snmp, Collector, History, Status, UpdateReceiver};
 pub use column::Column;
 pub use columns::Columns;
 pub use devices::Devices;
+pub use display::Display;
 use eyre::Result;
-pub use http::Http;
+pub use framebuffer::Framebuffer;
+pub use geometry::Geometry;
+pub use heartbeat::Heartbeat;
+pub use http::{Http, Recent};
 pub use layout::Layout;
+pub use output::Output;
+pub use overlay::Overlay;
+pub use palette::Palette;
 pub use png_builder::PngBuilder;
 use ratatui_tracing::{EventReceiver, Reloadable};
+use recorder::Recorder;
+use render::Renderer;
+use replay::Replay;
 pub use simulator::Simulator;
 use tokio::{
     signal::{
@@ -57,42 +85,92 @@ async fn tokio_main(
 ) -> Result<()> {
     debug!("args: {:#?}", args);
 
-    let devices: Devices = args.config()?.into();
-
-    let mut tasks = JoinSet::new();
+    match &args.command {
+        Some(Command::Render(render)) => return render::once(&args, render).await,
+        Some(Command::RenderRange(render)) => return render::range(&args, render).await,
+        None => (),
+    }
 
-    let updates = if args.simulate {
-        let simulator = Simulator::new(&args, &devices)?;
-        let updates = simulator.subscribe();
+    let devices = devices(&args).await?;
 
-        simulator.run_on(&mut tasks)?;
+    let mut tasks = JoinSet::new();
 
-        updates
-    } else {
-        let collector = Collector::new(&args, &devices)?;
-        let updates = collector.subscribe();
+    let (updates, status) = start_updates(&args, &devices, &mut tasks)?;
 
-        collector.run_on(&mut tasks)?;
+    if let Some(path) = &args.record {
+        Recorder::new(path, updates.clone(), devices.addresses()).run_on(&mut tasks)?;
+    }
 
-        updates
-    };
+    let recent = Recent::new(args.recent_frames, args.period());
+    recent
+        .clone()
+        .run_on(&mut tasks, devices.display(None)?.clone(), updates.clone())?;
+
+    for display in devices.displays() {
+        for output in display.outputs() {
+            output::Sender::new(
+                display.clone(),
+                output.clone(),
+                updates.clone(),
+                args.period(),
+            )
+            .run_on(&mut tasks)?;
+        }
+    }
 
     let (png_sender, png_receiver) = png_builder::update_channel();
-    let http = Http::new(args.server_address, png_receiver, args.period())?;
+    Renderer::new(
+        devices.displays(),
+        updates.clone(),
+        png_sender,
+        args.period(),
+    )
+    .run_on(&mut tasks)?;
+
+    let names = devices
+        .displays()
+        .iter()
+        .map(|display| display.name().to_string())
+        .collect();
+    let http = Http::new(
+        args.server_address,
+        names,
+        png_receiver,
+        recent,
+        args.period(),
+    )?;
     tasks.build_task().name("http server").spawn(http.run())?;
 
     if !args.headless {
         info!("starting TUI");
+
+        let history = History::new(args.history);
+        history.clone().run_on(&mut tasks, updates.clone())?;
+
+        // For testing queries in the TUI
+        let prometheus = match args.source() {
+            Ok(_) => Some(Arc::new(
+                collector::prometheus::Manager::new(&args)?.client()?,
+            )),
+            Err(_) => None,
+        };
+
         let mut app = App::new(
             gui_active,
             event_receiver,
             reloadable,
             args.tick_rate,
             args.frame_rate,
-            devices.columns().clone(),
+            devices.displays().to_vec(),
+            devices.devices(),
             updates,
-            png_sender,
-        )?;
+            status,
+            history,
+            prometheus,
+            args.config_path().ok().map(Path::to_path_buf),
+            args.colors,
+        )?
+        .mouse(!args.no_mouse);
 
         app.run().await?;
 
@@ -119,6 +197,60 @@ async fn tokio_main(
     Ok(())
 }
 
+/// Load the display config, discovering devices from Prometheus when it is the update source
+async fn devices(args: &Args) -> Result<Devices> {
+    let config = args.config()?;
+
+    let config = if args.simulate || args.snmp || !args.scrape.is_empty() || args.replay.is_some() {
+        config
+    } else {
+        let prometheus = collector::prometheus::Manager::new(args)?.client()?;
+
+        config.discover(&prometheus).await
+    };
+
+    config.try_into()
+}
+
+/// Start sending updates from the source chosen in `args`, along with the status of each device
+fn start_updates(
+    args: &Args,
+    devices: &Devices,
+    tasks: &mut JoinSet<Result<()>>,
+) -> Result<(UpdateReceiver, Status)> {
+    let updates = if let Some(path) = &args.replay {
+        let replay = Replay::new(path, args.replay_speed, devices.ids())?;
+        let updates = replay.subscribe();
+
+        replay.run_on(tasks)?;
+
+        (updates, Status::default())
+    } else if args.simulate {
+        let simulator = Simulator::new(args, devices)?;
+        let updates = simulator.subscribe();
+
+        simulator.run_on(tasks)?;
+
+        (updates, Status::default())
+    } else if args.snmp {
+        let snmp = Snmp::new(args, devices)?;
+        let updates = (snmp.subscribe(), snmp.status());
+
+        snmp.run_on(tasks)?;
+
+        updates
+    } else {
+        let collector = Collector::new(args, devices)?;
+        let updates = (collector.subscribe(), collector.status());
+
+        collector.run_on(tasks)?;
+
+        updates
+    };
+
+    Ok(updates)
+}
+
 #[instrument(skip_all)]
 fn wait_for_sigint(
     tasks: &mut JoinSet<Result<()>>,
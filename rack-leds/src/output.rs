@@ -0,0 +1,384 @@
+mod artnet;
+mod ddp;
+mod e131;
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use eyre::{Context, OptionExt, Result};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{lookup_host, UdpSocket},
+    task::JoinSet,
+    time::{self, MissedTickBehavior},
+};
+use tracing::{debug, error, info, instrument, warn};
+
+use crate::{collector::UpdateReceiver, heartbeat::Pulse, render, Display, Overlay};
+
+/// First wait before retrying an output that couldn't be connected, doubled on each failure
+const RETRY_MIN: Duration = Duration::from_secs(1);
+
+/// Longest wait before retrying an output that couldn't be connected
+const RETRY_MAX: Duration = Duration::from_secs(60);
+
+/// Shortest time between frames sent for scrolling overlays
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Push frames of a display to an LED controller over UDP
+///
+/// `target` is a host with an optional port, the protocol's port is used by default.  Pixels are
+/// sent row by row after the display geometry is applied.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Output {
+    /// Distributed Display Protocol starting `offset` pixels into the controller's pixels
+    Ddp {
+        target: String,
+        #[serde(default)]
+        offset: u32,
+        #[serde(default)]
+        order: Order,
+    },
+    /// E1.31 streaming ACN, 170 pixels per universe starting at `universe`
+    E131 {
+        target: String,
+        #[serde(default = "default_e131_universe")]
+        universe: u16,
+        #[serde(default)]
+        order: Order,
+    },
+    /// Art-Net, 170 pixels per universe starting at port-address `universe`
+    ArtNet {
+        target: String,
+        #[serde(default)]
+        universe: u16,
+        #[serde(default)]
+        order: Order,
+    },
+}
+
+fn default_e131_universe() -> u16 {
+    1
+}
+
+impl Output {
+    fn target(&self) -> &str {
+        match self {
+            Output::Ddp { target, .. } => target,
+            Output::E131 { target, .. } => target,
+            Output::ArtNet { target, .. } => target,
+        }
+    }
+
+    /// The target with the protocol's default port added when it has none
+    fn host(&self) -> String {
+        let target = self.target();
+        let port = self.default_port();
+
+        if target.parse::<SocketAddr>().is_ok() {
+            return target.to_string();
+        }
+
+        // Bare IPv6 addresses contain colons, with or without brackets
+        let ip = target
+            .strip_prefix('[')
+            .and_then(|target| target.strip_suffix(']'))
+            .unwrap_or(target);
+
+        if let Ok(ip) = ip.parse::<IpAddr>() {
+            return SocketAddr::new(ip, port).to_string();
+        }
+
+        match target.rsplit_once(':') {
+            Some((_, port)) if port.parse::<u16>().is_ok() => target.to_string(),
+            _ => format!("{target}:{port}"),
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        match self {
+            Output::Ddp { .. } => ddp::PORT,
+            Output::E131 { .. } => e131::PORT,
+            Output::ArtNet { .. } => artnet::PORT,
+        }
+    }
+
+    fn order(&self) -> Order {
+        match self {
+            Output::Ddp { order, .. } => *order,
+            Output::E131 { order, .. } => *order,
+            Output::ArtNet { order, .. } => *order,
+        }
+    }
+
+    /// UDP packets for one frame of RGB `pixels`
+    fn packets(&self, pixels: &[u8], sequence: u8, cid: &[u8; 16]) -> Vec<Vec<u8>> {
+        let data = self.order().reorder(pixels);
+
+        match self {
+            Output::Ddp { offset, .. } => ddp::packets(&data, offset * 3, sequence),
+            Output::E131 { universe, .. } => e131::packets(&data, *universe, sequence, cid),
+            Output::ArtNet { universe, .. } => artnet::packets(&data, *universe, sequence),
+        }
+    }
+}
+
+/// Order of color channels the LEDs expect
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Order {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl Order {
+    fn reorder(&self, pixels: &[u8]) -> Vec<u8> {
+        let [first, second, third] = match self {
+            Order::Rgb => [0, 1, 2],
+            Order::Rbg => [0, 2, 1],
+            Order::Grb => [1, 0, 2],
+            Order::Gbr => [1, 2, 0],
+            Order::Brg => [2, 0, 1],
+            Order::Bgr => [2, 1, 0],
+        };
+
+        pixels
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[first], pixel[second], pixel[third]])
+            .collect()
+    }
+}
+
+/// Send every update of a display to an [`Output`], and once a period without updates
+///
+/// Displays with scrolling overlays are also sent each time the text moves a pixel.
+pub struct Sender {
+    display: Display,
+    output: Output,
+    updates: UpdateReceiver,
+    pulse: Pulse,
+}
+
+impl Sender {
+    pub fn new(
+        display: Display,
+        output: Output,
+        updates: UpdateReceiver,
+        period: Duration,
+    ) -> Self {
+        Self {
+            display,
+            output,
+            updates,
+            pulse: Pulse::new(period),
+        }
+    }
+
+    /// Resolve `host` and connect a socket to it
+    async fn connect(host: &str) -> Result<(UdpSocket, SocketAddr)> {
+        let address = lookup_host(host)
+            .await
+            .wrap_err_with(|| format!("Unable to resolve output {host}"))?
+            .next()
+            .ok_or_eyre(format!("No address for output {host}"))?;
+
+        let bind = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+
+        let socket = UdpSocket::bind(bind)
+            .await
+            .wrap_err_with(|| format!("Unable to bind a socket for output {host}"))?;
+
+        socket
+            .connect(address)
+            .await
+            .wrap_err_with(|| format!("Unable to connect to output {address}"))?;
+
+        Ok((socket, address))
+    }
+
+    /// Send frames, retrying with backoff until the target can be resolved and connected
+    #[instrument(name = "output", skip_all, fields(display = self.display.name(), target = self.output.target()))]
+    pub async fn run(mut self) -> Result<()> {
+        let host = self.output.host();
+        let mut retry = RETRY_MIN;
+
+        let (socket, address) = loop {
+            match Self::connect(&host).await {
+                Ok(connected) => break connected,
+                Err(e) => {
+                    error!(?e, ?retry, "unable to connect output, retrying");
+
+                    time::sleep(retry).await;
+                    retry = (retry * 2).min(RETRY_MAX);
+                }
+            }
+        };
+
+        let cid = rand::random();
+        let mut sequence = 0u8;
+
+        let mut ticks = self
+            .display
+            .overlays()
+            .iter()
+            .filter_map(Overlay::frame_interval)
+            .min()
+            .map(|interval| {
+                let mut ticks = time::interval(interval.max(MIN_FRAME_INTERVAL));
+                ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                ticks
+            });
+
+        info!(%address, "started");
+
+        loop {
+            match ticks.as_mut() {
+                Some(ticks) => tokio::select! {
+                    waited = self.pulse.wait(&mut self.updates) => waited?,
+                    _ = ticks.tick() => {
+                        // An update that arrived with the tick is still a beat
+                        if self.updates.has_changed()? {
+                            self.pulse.beat();
+                        }
+                    },
+                },
+                None => self.pulse.wait(&mut self.updates).await?,
+            }
+
+            let frame = {
+                let (updates, time) = &*self.updates.borrow_and_update();
+
+                // Frames between updates move on from the update's time so overlays scroll
+                let time = *time + self.pulse.since();
+
+                render::frame(&self.display, updates, time, Some(&self.pulse))
+            };
+
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    error!(?e, "error rendering frame");
+                    continue;
+                }
+            };
+
+            sequence = sequence.wrapping_add(1);
+
+            let packets = self.output.packets(frame.data(), sequence, &cid);
+
+            for packet in packets.iter() {
+                if let Err(e) = socket.send(packet).await {
+                    warn!(?e, "send failed");
+                    break;
+                }
+            }
+
+            debug!(packets = packets.len(), "sent");
+        }
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>) -> Result<()> {
+        let name = format!("output {} {}", self.display.name(), self.output.target());
+
+        join_set
+            .build_task()
+            .name(&name)
+            .spawn(async move { self.run().await })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, time::SystemTime};
+
+    use tokio::sync::watch;
+
+    use super::*;
+    use crate::{geometry::Panel, palette::Palette, Columns, Geometry};
+
+    #[test]
+    fn reorder() {
+        let pixels = [1, 2, 3, 4, 5, 6];
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], Order::Rgb.reorder(&pixels));
+        assert_eq!(vec![2, 1, 3, 5, 4, 6], Order::Grb.reorder(&pixels));
+        assert_eq!(vec![3, 2, 1, 6, 5, 4], Order::Bgr.reorder(&pixels));
+    }
+
+    #[test]
+    fn host() {
+        let output = |target: &str| Output::Ddp {
+            target: target.to_string(),
+            offset: 0,
+            order: Order::Rgb,
+        };
+
+        assert_eq!("leds:4048", output("leds").host());
+        assert_eq!("leds:1234", output("leds:1234").host());
+        assert_eq!("10.0.0.2:4048", output("10.0.0.2").host());
+        assert_eq!("[fd00::2]:4048", output("fd00::2").host());
+        assert_eq!("[fd00::2]:4048", output("[fd00::2]").host());
+        assert_eq!("[fd00::2]:1234", output("[fd00::2]:1234").host());
+    }
+
+    #[tokio::test]
+    async fn send_ddp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+
+        let geometry = Geometry {
+            panel: Panel::Custom {
+                width: 2,
+                height: 1,
+            },
+            ..Default::default()
+        };
+
+        let display = Display::new(
+            "test".into(),
+            Columns::new(vec![]),
+            geometry,
+            Palette::default(),
+        );
+
+        let output = Output::Ddp {
+            target,
+            offset: 4,
+            order: Order::Grb,
+        };
+
+        let (update_sender, updates) = watch::channel((HashMap::default(), SystemTime::now()));
+
+        tokio::spawn(Sender::new(display, output, updates, Duration::from_secs(60)).run());
+
+        let mut packet = [0; 64];
+
+        let length = loop {
+            update_sender.send_replace((HashMap::default(), SystemTime::now()));
+
+            let received =
+                tokio::time::timeout(Duration::from_millis(100), listener.recv(&mut packet)).await;
+
+            if let Ok(length) = received {
+                break length.unwrap();
+            }
+        };
+
+        let packet = &packet[..length];
+
+        assert_eq!(0x41, packet[0]);
+        assert_eq!([0x0b, 1, 0, 0, 0, 12, 0, 6, 0, 0, 0, 0, 0, 0], packet[2..]);
+    }
+}
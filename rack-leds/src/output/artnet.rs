@@ -0,0 +1,65 @@
+//! Art-Net ArtDmx packets
+
+pub const PORT: u16 = 6454;
+
+/// Channels used per universe, 170 RGB pixels
+pub const CHANNELS: usize = 510;
+
+const ID: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+const PROTOCOL_VERSION: u16 = 14;
+
+/// Packets for one frame of `data`, one universe per packet starting at port-address `universe`
+///
+/// `sequence` 0 disables reordering in receivers so it wraps from 255 to 1.
+pub fn packets(data: &[u8], universe: u16, sequence: u8) -> Vec<Vec<u8>> {
+    let sequence = sequence.max(1);
+
+    data.chunks(CHANNELS)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let universe = universe.wrapping_add(index as u16) & 0x7fff;
+
+            // Lengths must be even
+            let length = chunk.len() + chunk.len() % 2;
+
+            let mut packet = Vec::with_capacity(18 + length);
+            packet.extend_from_slice(ID);
+            packet.extend_from_slice(&OP_DMX.to_le_bytes());
+            packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+            packet.push(sequence);
+            packet.push(0); // physical
+            packet.extend_from_slice(&universe.to_le_bytes()); // SubUni then Net
+            packet.extend_from_slice(&(length as u16).to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet.resize(18 + length, 0);
+
+            packet
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packets_universes() {
+        let data = vec![5; CHANNELS + 3];
+
+        let packets = packets(&data, 0x01ff, 0);
+
+        assert_eq!(2, packets.len());
+
+        assert_eq!(
+            [
+                b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x50, 0, 14, 1, 0, 0xff, 0x01,
+                0x01, 0xfe
+            ],
+            packets[0][..18]
+        );
+        assert_eq!(18 + CHANNELS, packets[0].len());
+
+        assert_eq!([0x00, 0x02, 0, 4, 5, 5, 5, 0], packets[1][14..]);
+    }
+}
@@ -0,0 +1,65 @@
+//! Distributed Display Protocol, <http://www.3waylabs.com/ddp/>
+
+pub const PORT: u16 = 4048;
+
+/// Data bytes per packet, 480 RGB pixels
+const MAX_DATA: usize = 1440;
+
+const VERSION_1: u8 = 0x40;
+const PUSH: u8 = 0x01;
+/// RGB with 8 bits per element
+const DATA_TYPE: u8 = 0x0b;
+/// The default output device
+const DESTINATION: u8 = 0x01;
+
+/// Packets for one frame of `data` starting `offset` bytes into the controller's pixels
+///
+/// The last packet has the push flag set so the controller displays the whole frame at once.
+/// `sequence` wraps from 15 to 1, 0 means sequence numbers are not used.
+pub fn packets(data: &[u8], offset: u32, sequence: u8) -> Vec<Vec<u8>> {
+    let sequence = sequence % 15 + 1;
+    let chunks = data.chunks(MAX_DATA).count();
+
+    data.chunks(MAX_DATA)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let flags = if index + 1 == chunks {
+                VERSION_1 | PUSH
+            } else {
+                VERSION_1
+            };
+
+            let offset = offset + (index * MAX_DATA) as u32;
+
+            let mut packet = Vec::with_capacity(10 + chunk.len());
+            packet.extend_from_slice(&[flags, sequence, DATA_TYPE, DESTINATION]);
+            packet.extend_from_slice(&offset.to_be_bytes());
+            packet.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+            packet.extend_from_slice(chunk);
+
+            packet
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packets_split() {
+        let data = vec![7; MAX_DATA + 3];
+
+        let packets = packets(&data, 6, 15);
+
+        assert_eq!(2, packets.len());
+
+        assert_eq!([0x40, 1, 0x0b, 1, 0, 0, 0, 6, 0x05, 0xa0], packets[0][..10]);
+        assert_eq!(10 + MAX_DATA, packets[0].len());
+
+        assert_eq!(
+            [0x41, 1, 0x0b, 1, 0, 0, 0x05, 0xa6, 0, 3, 7, 7, 7],
+            packets[1][..]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! E1.31 streaming ACN (sACN) DMX data packets
+
+pub const PORT: u16 = 5568;
+
+/// Channels used per universe, 170 RGB pixels
+pub const CHANNELS: usize = 510;
+
+const HEADER: usize = 126;
+
+const ACN_PACKET_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_E131_DATA: u32 = 0x04;
+const VECTOR_E131_DATA_PACKET: u32 = 0x02;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const PRIORITY: u8 = 100;
+
+/// Packets for one frame of `data`, one universe per packet starting at `universe`
+///
+/// `cid` identifies this source to receivers.
+pub fn packets(data: &[u8], universe: u16, sequence: u8, cid: &[u8; 16]) -> Vec<Vec<u8>> {
+    data.chunks(CHANNELS)
+        .enumerate()
+        .map(|(index, chunk)| packet(chunk, universe.wrapping_add(index as u16), sequence, cid))
+        .collect()
+}
+
+fn packet(channels: &[u8], universe: u16, sequence: u8, cid: &[u8; 16]) -> Vec<u8> {
+    let length = HEADER + channels.len();
+
+    let mut packet = Vec::with_capacity(length);
+
+    // Root layer
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0x0000u16.to_be_bytes());
+    packet.extend_from_slice(ACN_PACKET_IDENTIFIER);
+    packet.extend_from_slice(&flags_and_length(length - 16));
+    packet.extend_from_slice(&VECTOR_ROOT_E131_DATA.to_be_bytes());
+    packet.extend_from_slice(cid);
+
+    // Framing layer
+    packet.extend_from_slice(&flags_and_length(length - 38));
+    packet.extend_from_slice(&VECTOR_E131_DATA_PACKET.to_be_bytes());
+    let mut source_name = [0; 64];
+    source_name[..9].copy_from_slice(b"rack-leds");
+    packet.extend_from_slice(&source_name);
+    packet.push(PRIORITY);
+    packet.extend_from_slice(&0u16.to_be_bytes()); // synchronization address
+    packet.push(sequence);
+    packet.push(0); // options
+    packet.extend_from_slice(&universe.to_be_bytes());
+
+    // DMP layer
+    packet.extend_from_slice(&flags_and_length(length - 115));
+    packet.push(VECTOR_DMP_SET_PROPERTY);
+    packet.push(0xa1); // address and data type
+    packet.extend_from_slice(&0u16.to_be_bytes()); // first property address
+    packet.extend_from_slice(&1u16.to_be_bytes()); // address increment
+    packet.extend_from_slice(&(channels.len() as u16 + 1).to_be_bytes());
+    packet.push(0); // DMX start code
+    packet.extend_from_slice(channels);
+
+    packet
+}
+
+fn flags_and_length(length: usize) -> [u8; 2] {
+    (0x7000 | length as u16).to_be_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packets_universes() {
+        let data = vec![9; CHANNELS + 6];
+        let cid = [0xcc; 16];
+
+        let packets = packets(&data, 3, 42, &cid);
+
+        assert_eq!(2, packets.len());
+
+        let first = &packets[0];
+        assert_eq!(HEADER + CHANNELS, first.len());
+        assert_eq!(b"ASC-E1.17\0\0\0", &first[4..16]);
+        assert_eq!([0x72, 0x6c], first[16..18]); // 110 + 510
+        assert_eq!(cid, first[22..38]);
+        assert_eq!([0x72, 0x56], first[38..40]); // 88 + 510
+        assert_eq!(b"rack-leds\0", &first[44..54]);
+        assert_eq!(42, first[111]);
+        assert_eq!([0, 3], first[113..115]);
+        assert_eq!([0x72, 0x09], first[115..117]); // 11 + 510
+        assert_eq!([0x01, 0xff], first[123..125]); // 511 values
+        assert_eq!(0, first[125]);
+
+        let second = &packets[1];
+        assert_eq!(HEADER + 6, second.len());
+        assert_eq!([0, 4], second[113..115]);
+        assert_eq!([9; 6], second[126..]);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{brightness, device::Id, font, units, Display, Framebuffer, Update};
+
+/// A row of text drawn over the rack
+///
+/// The overlay is [`font::HEIGHT`] pixels tall at `x` and `y` on the canvas, and extends to the
+/// right edge of the canvas unless `width` is set.  Text wider than the overlay scrolls from right
+/// to left at `scroll` pixels per second by the clock rather than the update time, or is cut off
+/// when `scroll` is 0.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Overlay {
+    #[serde(default)]
+    pub x: u16,
+    #[serde(default)]
+    pub y: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+    pub content: Content,
+    #[serde(default = "default_color")]
+    pub color: [u8; 3],
+    #[serde(default)]
+    pub background: [u8; 3],
+    #[serde(default)]
+    pub scroll: f32,
+}
+
+fn default_color() -> [u8; 3] {
+    [255, 255, 255]
+}
+
+/// What an [`Overlay`] shows
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Content {
+    /// Fixed text
+    Text(String),
+    /// The local time of the update as `HH:MM`
+    Time,
+    /// Count of devices on the display without an update
+    DevicesDown,
+    /// Receive plus transmit bits per second of a device on the display with the `address`, for
+    /// all ports or one `port`, as collected rather than the change drawn on the LEDs
+    Rate {
+        device: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        port: Option<usize>,
+        #[serde(skip)]
+        id: Option<Id>,
+    },
+}
+
+impl Overlay {
+    /// Find the devices a [`Content::Rate`] refers to from configured addresses
+    pub fn resolve(&mut self, ids: &HashMap<String, Id>) {
+        if let Content::Rate { device, id, .. } = &mut self.content {
+            *id = ids.get(device).copied();
+        }
+    }
+
+    /// Time to scroll one pixel, `None` when the text doesn't scroll
+    ///
+    /// Frames are redrawn at least this often so scrolling text moves smoothly between updates.
+    pub fn frame_interval(&self) -> Option<Duration> {
+        (self.scroll > 0.0).then(|| Duration::from_secs_f32(1.0 / self.scroll))
+    }
+
+    /// Draw on the display canvas, `time` is the time of the frame
+    pub fn render(
+        &self,
+        display: &Display,
+        updates: &HashMap<Id, Update>,
+        time: SystemTime,
+        framebuffer: &mut Framebuffer,
+    ) {
+        let width = self
+            .width
+            .unwrap_or_else(|| framebuffer.width().saturating_sub(self.x));
+
+        let area = Rect::new(self.x, self.y, width, font::HEIGHT).intersection(framebuffer.area());
+
+        if area.is_empty() {
+            return;
+        }
+
+        framebuffer.fill(area, self.background);
+
+        let text = self.text(display, updates, time);
+        let left = self.left(&text, area.width, time);
+
+        for (text_x, text_y) in font::pixels(&text) {
+            let pixel_x = left + i32::from(text_x);
+
+            if pixel_x < 0 {
+                continue;
+            }
+
+            framebuffer.set_in(area, pixel_x as u16, text_y, self.color);
+        }
+    }
+
+    /// Offset of the text from the left of the overlay at `now`
+    fn left(&self, text: &str, width: u16, now: SystemTime) -> i32 {
+        let text_width = font::width(text);
+
+        if self.scroll <= 0.0 || text_width <= width {
+            return 0;
+        }
+
+        let seconds = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
+        // The text enters from the right edge and leaves completely before it enters again
+        let cycle = u64::from(text_width) + u64::from(width);
+        let travelled = (seconds * f64::from(self.scroll)) as u64 % cycle;
+
+        i32::from(width) - travelled as i32
+    }
+
+    fn text(&self, display: &Display, updates: &HashMap<Id, Update>, time: SystemTime) -> String {
+        match &self.content {
+            Content::Text(text) => text.clone(),
+            Content::Time => {
+                let time = OffsetDateTime::from(time).to_offset(brightness::local_offset_at(time));
+
+                format!("{:02}:{:02}", time.hour(), time.minute())
+            }
+            Content::DevicesDown => {
+                let down = display
+                    .columns()
+                    .columns()
+                    .flat_map(|column| column.ids().collect::<Vec<_>>())
+                    .filter(|id| !updates.contains_key(id))
+                    .count();
+
+                format!("{down} down")
+            }
+            Content::Rate { id, port, .. } => {
+                let Some(update) = id.and_then(|id| updates.get(&id)) else {
+                    return "?".to_string();
+                };
+
+                let (receive, transmit) = update.rates();
+
+                let octets: u64 = match port {
+                    Some(port) => {
+                        receive.get(*port).copied().unwrap_or_default()
+                            + transmit.get(*port).copied().unwrap_or_default()
+                    }
+                    None => receive.iter().chain(transmit.iter()).sum(),
+                };
+
+                units::bits(octets * 8)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{update::Switch, Column, Columns, Geometry, Layout, Palette};
+
+    #[test]
+    fn left() {
+        let overlay = Overlay {
+            x: 0,
+            y: 0,
+            width: None,
+            content: Content::Text("1234".into()),
+            color: default_color(),
+            background: [0, 0, 0],
+            scroll: 2.0,
+        };
+
+        let at = |seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+
+        // Fits, so it doesn't scroll
+        assert_eq!(0, overlay.left("1234", 15, at(1)));
+
+        assert_eq!(10, overlay.left("1234", 10, at(0)));
+        assert_eq!(8, overlay.left("1234", 10, at(1)));
+        assert_eq!(-14, overlay.left("1234", 10, at(12)));
+        assert_eq!(10, overlay.left("1234", 10, at(25)));
+    }
+
+    #[test]
+    fn render_scroll() {
+        let overlay = Overlay {
+            x: 0,
+            y: 0,
+            width: Some(10),
+            content: Content::Text("1234".into()),
+            color: [255, 255, 255],
+            background: [0, 0, 0],
+            scroll: 2.0,
+        };
+
+        let display = Display::new(
+            "test".into(),
+            Columns::new(vec![]),
+            Geometry::default(),
+            Palette::default(),
+        );
+
+        let at = |seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+
+        // The leftmost column with text in it
+        let leftmost = |time| {
+            let mut framebuffer = Framebuffer::new(10, font::HEIGHT);
+
+            overlay.render(&display, &HashMap::new(), time, &mut framebuffer);
+
+            (0..10).find(|x| (0..font::HEIGHT).any(|y| framebuffer.get(*x, y) != Some([0, 0, 0])))
+        };
+
+        let start = leftmost(at(3)).unwrap();
+
+        assert_eq!(Some(start), leftmost(at(3)));
+        assert_eq!(Some(start - 2), leftmost(at(4)));
+        assert_eq!(None, leftmost(at(0)));
+    }
+
+    #[test]
+    fn render() {
+        let mut overlay = Overlay {
+            x: 1,
+            y: 2,
+            width: Some(8),
+            content: Content::Rate {
+                device: "core".into(),
+                port: Some(1),
+                id: None,
+            },
+            color: [255, 0, 0],
+            background: [0, 0, 32],
+            scroll: 0.0,
+        };
+
+        let id: Id = serde_json::from_str("7").unwrap();
+
+        overlay.resolve(&HashMap::from([("core".to_string(), id)]));
+
+        let display = Display::new(
+            "test".into(),
+            Columns::new(vec![Column::new(vec![id])]),
+            Geometry::default(),
+            Palette::default(),
+        );
+
+        let updates = HashMap::from([(
+            id,
+            Update::Switch {
+                id,
+                device: Switch::new(vec![0, 0], vec![0, 0], vec![0, 0])
+                    .with_rates(vec![0, 100], vec![0, 25]),
+                layout: Layout::SwitchEight,
+            },
+        )]);
+
+        let mut framebuffer = Framebuffer::new(12, 8);
+
+        overlay.render(&display, &updates, SystemTime::UNIX_EPOCH, &mut framebuffer);
+
+        let pixel = |x, y| framebuffer.get(x, y).unwrap();
+
+        // "1.0K" is cut off at 8 pixels wide
+        assert_eq!([0, 0, 0], pixel(0, 2));
+        assert_eq!([0, 0, 32], pixel(1, 2));
+        assert_eq!([255, 0, 0], pixel(2, 2));
+        assert_eq!([0, 0, 32], pixel(8, 6));
+        assert_eq!([255, 0, 0], pixel(6, 6));
+        assert_eq!([0, 0, 0], pixel(9, 6));
+        assert_eq!([0, 0, 0], pixel(1, 7));
+    }
+}
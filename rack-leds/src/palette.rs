@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Colors of the traffic shown on a display
+///
+/// Channel utilization always goes from green to red.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Palette {
+    pub receive: Shade,
+    pub transmit: Shade,
+    pub poe: Shade,
+    pub stations: Shade,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            receive: Shade::new(210.0, 1.0),
+            transmit: Shade::new(150.0, 1.0),
+            poe: Shade::new(0.0, 0.5),
+            stations: Shade {
+                hue: 0.0,
+                saturation: 0.0,
+                dark: 0.25,
+                light: 0.75,
+            },
+        }
+    }
+}
+
+/// A color from dark at the lowest value to light at the highest value
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Shade {
+    /// Hue in degrees
+    pub hue: f32,
+    /// Saturation from 0.0 to 1.0
+    pub saturation: f32,
+    /// Lightness of the lowest value from 0.0 to 1.0
+    #[serde(default = "Shade::default_dark")]
+    pub dark: f32,
+    /// Lightness of the highest value from 0.0 to 1.0
+    #[serde(default = "Shade::default_light")]
+    pub light: f32,
+}
+
+impl Shade {
+    pub fn new(hue: f32, saturation: f32) -> Self {
+        Self {
+            hue,
+            saturation,
+            dark: Self::default_dark(),
+            light: Self::default_light(),
+        }
+    }
+
+    fn default_dark() -> f32 {
+        0.12
+    }
+
+    fn default_light() -> f32 {
+        0.5
+    }
+}
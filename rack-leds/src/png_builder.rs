@@ -1,19 +1,22 @@
 use std::{
+    collections::HashMap,
     sync::{Arc, Mutex},
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use bytes::{Bytes, BytesMut};
-use eyre::{Context, Result};
-use ratatui::{layout::Rect, style::Color, Frame};
+use eyre::{Context, OptionExt, Result};
 use tokio::sync::watch;
 
+use crate::Framebuffer;
+
 pub fn update_channel() -> (PngSender, PngReceiver) {
-    watch::channel((Bytes::new(), UNIX_EPOCH))
+    watch::channel((HashMap::default(), UNIX_EPOCH))
 }
 
-pub type PngReceiver = watch::Receiver<(Bytes, SystemTime)>;
-pub type PngSender = watch::Sender<(Bytes, SystemTime)>;
+/// PNGs of each display by name
+pub type PngReceiver = watch::Receiver<(HashMap<String, Bytes>, SystemTime)>;
+pub type PngSender = watch::Sender<(HashMap<String, Bytes>, SystemTime)>;
 
 pub struct PngBuilder {
     height: u32,
@@ -22,34 +25,89 @@ pub struct PngBuilder {
 }
 
 impl PngBuilder {
-    pub fn new(frame: &mut Frame, area: &Rect) -> Self {
-        let buffer = frame.buffer_mut();
-
-        let data = area.positions().fold(vec![], |mut png, position| {
-            let color = buffer
-                .cell(position)
-                .map(|cell| cell.fg)
-                .unwrap_or(Color::Black);
-
-            let (r, g, b) = match color {
-                Color::Rgb(r, g, b) => (r, g, b),
-                _ => (0, 0, 0),
-            };
-
-            png.push(r);
-            png.push(g);
-            png.push(b);
-
-            png
-        });
+    /// Create an image by calling `f` with the coordinates of each pixel
+    pub fn from_fn(width: u32, height: u32, f: impl Fn(u32, u32) -> [u8; 3]) -> Self {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| f(x, y))
+            .collect();
 
         Self {
+            height,
+            width,
             data,
-            height: area.height as u32,
-            width: area.width as u32,
         }
     }
 
+    /// RGB pixels row by row
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The RGB color at `x`, `y` if it is in the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 3]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let offset = ((y * self.width + x) * 3) as usize;
+
+        self.data[offset..offset + 3].try_into().ok()
+    }
+
+    /// Build an animated PNG from `frames`, each shown for `delay`
+    pub fn build_animation(frames: Vec<PngBuilder>, delay: Duration) -> Result<Bytes> {
+        let frames: Vec<_> = frames.iter().map(|frame| (frame, delay)).collect();
+
+        Self::build_animation_with_delays(&frames)
+    }
+
+    /// Build an animated PNG from `frames`, each shown for its own delay
+    pub fn build_animation_with_delays(frames: &[(&PngBuilder, Duration)]) -> Result<Bytes> {
+        let (first, _) = frames.first().ok_or_eyre("No frames to animate")?;
+
+        let png_writer = PngWriter::default();
+
+        let mut encoder = png::Encoder::new(png_writer.clone(), first.width, first.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frames.len().try_into()?, 0)
+            .wrap_err("Unable to set animation info")?;
+
+        let mut writer = encoder
+            .write_header()
+            .wrap_err("Unable to write PNG header")?;
+
+        for (frame, delay) in frames.iter() {
+            // Delays are a u16 fraction so long delays lose sub-second precision
+            let (numerator, denominator) = match delay.as_millis().try_into() {
+                Ok(millis) => (millis, 1000),
+                Err(_) => (delay.as_secs().try_into().unwrap_or(u16::MAX), 1),
+            };
+
+            writer
+                .set_frame_delay(numerator, denominator)
+                .wrap_err("Unable to set frame delay")?;
+
+            writer
+                .write_image_data(&frame.data)
+                .wrap_err("Unable to write PNG frame")?;
+        }
+
+        writer.finish().wrap_err("Unable to write PNG")?;
+
+        Ok(png_writer.into())
+    }
+
     pub fn build(self) -> Result<Bytes> {
         let png_writer = PngWriter::default();
 
@@ -70,6 +128,16 @@ impl PngBuilder {
     }
 }
 
+impl From<&Framebuffer> for PngBuilder {
+    fn from(framebuffer: &Framebuffer) -> Self {
+        Self {
+            data: framebuffer.pixels().iter().flatten().copied().collect(),
+            height: framebuffer.height() as u32,
+            width: framebuffer.width() as u32,
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 struct PngWriter {
     data: Arc<Mutex<BytesMut>>,
@@ -97,3 +165,39 @@ impl std::io::Write for PngWriter {
         std::io::Result::Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build_animation() {
+        let frames = [[255, 0, 0], [0, 255, 0]]
+            .into_iter()
+            .map(|color| {
+                let mut framebuffer = Framebuffer::new(3, 2);
+                framebuffer.fill(framebuffer.area(), color);
+
+                PngBuilder::from(&framebuffer)
+            })
+            .collect();
+
+        let png = PngBuilder::build_animation(frames, Duration::from_millis(250)).unwrap();
+
+        let decoder = png::Decoder::new(png.as_ref());
+        let mut reader = decoder.read_info().unwrap();
+
+        let animation = reader.info().animation_control().unwrap();
+        assert_eq!(2, animation.num_frames);
+
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!([255, 0, 0], data[..3]);
+
+        let control = reader.info().frame_control().unwrap();
+        assert_eq!((250, 1000), (control.delay_num, control.delay_den));
+
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!([0, 255, 0], data[..3]);
+    }
+}
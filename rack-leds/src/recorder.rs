@@ -0,0 +1,114 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncWriteExt, BufWriter},
+    task::JoinSet,
+};
+use tracing::{debug, info, instrument};
+
+use crate::{collector::UpdateReceiver, device::Id, Update};
+
+/// One set of updates as recorded, one per line of a recording
+///
+/// Updates are keyed by device address as [`Id`]s depend on the order devices are configured and
+/// discovered.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Frame {
+    pub time: SystemTime,
+    pub updates: BTreeMap<String, Update>,
+}
+
+impl Frame {
+    /// Record `updates` under the address of each device in `addresses`
+    pub fn new(
+        updates: &HashMap<Id, Update>,
+        time: SystemTime,
+        addresses: &HashMap<Id, String>,
+    ) -> Self {
+        let updates = updates
+            .iter()
+            .filter_map(|(id, update)| Some((addresses.get(id)?.clone(), update.clone())))
+            .collect();
+
+        Self { time, updates }
+    }
+
+    /// Updates for the devices in `ids` by address, recorded devices that are not configured
+    /// are left out
+    pub fn resolve(self, ids: &HashMap<String, Id>) -> (HashMap<Id, Update>, SystemTime) {
+        let updates = self
+            .updates
+            .into_iter()
+            .filter_map(|(address, update)| {
+                let id = *ids.get(&address)?;
+
+                Some((id, update.with_id(id)))
+            })
+            .collect();
+
+        (updates, self.time)
+    }
+}
+
+/// Append every update sent to a file as newline-delimited JSON [`Frame`]s
+pub struct Recorder {
+    path: PathBuf,
+    updates: UpdateReceiver,
+    addresses: HashMap<Id, String>,
+}
+
+impl Recorder {
+    pub fn new(path: &Path, updates: UpdateReceiver, addresses: HashMap<Id, String>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            updates,
+            addresses,
+        }
+    }
+
+    #[instrument(name = "recorder", skip_all, fields(path = ?self.path))]
+    pub async fn run(mut self) -> Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .wrap_err_with(|| format!("Unable to open recording {}", self.path.display()))?;
+
+        let mut writer = BufWriter::new(file);
+
+        info!("started");
+
+        loop {
+            self.updates.changed().await?;
+
+            let mut line = {
+                let (updates, time) = &*self.updates.borrow_and_update();
+
+                serde_json::to_string(&Frame::new(updates, *time, &self.addresses))?
+            };
+            line.push('\n');
+
+            writer.write_all(line.as_bytes()).await?;
+            writer.flush().await?;
+
+            debug!(bytes = line.len(), "recorded");
+        }
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>) -> Result<()> {
+        join_set
+            .build_task()
+            .name("recorder")
+            .spawn(async move { self.run().await })?;
+
+        Ok(())
+    }
+}
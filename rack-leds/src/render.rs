@@ -0,0 +1,284 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use eyre::{bail, Context, Result};
+use tokio::task::JoinSet;
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    args::{Format, Render, RenderRange},
+    collector::{
+        prometheus::{self, Range},
+        UpdateReceiver,
+    },
+    device::Id,
+    heartbeat::Pulse,
+    png_builder::PngSender,
+    ui::Rack,
+    Args, Devices, Display, Framebuffer, PngBuilder, Update,
+};
+
+/// Draw the canvas of a display before brightness and the geometry are applied
+///
+/// The heartbeat is only drawn with a `pulse`.
+pub fn canvas(
+    display: &Display,
+    updates: &HashMap<Id, Update>,
+    time: SystemTime,
+    pulse: Option<&Pulse>,
+) -> Result<Framebuffer> {
+    let geometry = display.geometry();
+    let canvas = geometry.canvas();
+    let mut framebuffer = Framebuffer::new(canvas.width, canvas.height);
+
+    Rack::new(display.columns(), display.palette(), updates)
+        .paint(&mut framebuffer, geometry.rack())?;
+
+    for overlay in display.overlays() {
+        overlay.render(display, updates, time, &mut framebuffer);
+    }
+
+    if let (Some(heartbeat), Some(pulse)) = (display.heartbeat(), pulse) {
+        heartbeat.render(pulse, &mut framebuffer);
+    }
+
+    Ok(framebuffer)
+}
+
+/// Render a display offscreen as it is sent to LEDs at `time`
+///
+/// The heartbeat is only drawn with a `pulse`.
+pub fn frame(
+    display: &Display,
+    updates: &HashMap<Id, Update>,
+    time: SystemTime,
+    pulse: Option<&Pulse>,
+) -> Result<PngBuilder> {
+    let mut canvas = canvas(display, updates, time, pulse)?;
+
+    display.brightness().apply(&mut canvas, time);
+
+    Ok(display.geometry().transform(&PngBuilder::from(&canvas)))
+}
+
+/// Render a PNG of every display for each update, and once a period without updates
+pub struct Renderer {
+    displays: Vec<Display>,
+    updates: UpdateReceiver,
+    png_sender: PngSender,
+    pulse: Pulse,
+}
+
+impl Renderer {
+    pub fn new(
+        displays: &[Display],
+        updates: UpdateReceiver,
+        png_sender: PngSender,
+        period: Duration,
+    ) -> Self {
+        Self {
+            displays: displays.to_vec(),
+            updates,
+            png_sender,
+            pulse: Pulse::new(period),
+        }
+    }
+
+    #[instrument(name = "renderer", skip_all, fields(displays = self.displays.len()))]
+    pub async fn run(mut self) -> Result<()> {
+        info!("started");
+
+        loop {
+            self.pulse.wait(&mut self.updates).await?;
+
+            let (updates, time) = self.updates.borrow_and_update().clone();
+
+            let pngs = self
+                .displays
+                .iter()
+                .filter_map(|display| {
+                    match frame(display, &updates, time, Some(&self.pulse))
+                        .and_then(|frame| frame.build())
+                    {
+                        Ok(png) => Some((display.name().to_string(), png)),
+                        Err(e) => {
+                            let name = display.name();
+
+                            error!(?e, name, "error building PNG");
+                            None
+                        }
+                    }
+                })
+                .collect();
+
+            self.png_sender.send_replace((pngs, time));
+        }
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>) -> Result<()> {
+        join_set
+            .build_task()
+            .name("renderer")
+            .spawn(async move { self.run().await })?;
+
+        Ok(())
+    }
+}
+
+/// Updates from the source chosen in `args` each device needs before its rates are real
+///
+/// Every rate is drawn as the difference from the previous update, so a device needs one update
+/// to compare against.  SNMP and scrape rates are calculated from counters, so their first update
+/// has no rate either.  Replays and the simulator send finished updates.
+fn updates_needed(args: &Args) -> usize {
+    if args.replay.is_some() || args.simulate {
+        1
+    } else if args.snmp || !args.scrape.is_empty() {
+        3
+    } else {
+        2
+    }
+}
+
+/// Render the display once every device on it has real rates
+///
+/// Devices that fail to update are waited for up to twice as many updates as needed, then the
+/// display is rendered without them.
+#[instrument(skip_all, fields(out = ?render.out))]
+pub async fn once(args: &Args, render: &Render) -> Result<()> {
+    let devices = crate::devices(args).await?;
+    let display = devices.display(render.display.as_deref())?;
+
+    let mut tasks = JoinSet::new();
+
+    let (mut updates, _) = crate::start_updates(args, &devices, &mut tasks)?;
+
+    let needed = updates_needed(args);
+    let wanted: HashSet<Id> = display
+        .columns()
+        .columns()
+        .flat_map(|column| column.ids().collect::<Vec<_>>())
+        .collect();
+    let mut counts: HashMap<Id, usize> = HashMap::new();
+
+    let waiting_for = |counts: &HashMap<Id, usize>| {
+        wanted
+            .iter()
+            .filter(|id| counts.get(id).copied().unwrap_or_default() < needed)
+            .count()
+    };
+
+    for _ in 0..needed * 2 {
+        tokio::select! {
+            changed = updates.changed() => changed?,
+            Some(result) = tasks.join_next() => {
+                result??;
+
+                bail!("Updates stopped before rendering");
+            }
+        }
+
+        for id in updates.borrow().0.keys() {
+            *counts.entry(*id).or_default() += 1;
+        }
+
+        if waiting_for(&counts) == 0 {
+            break;
+        }
+    }
+
+    tasks.abort_all();
+
+    let waiting = waiting_for(&counts);
+
+    if waiting > 0 {
+        warn!(waiting, "rendering without devices that did not update");
+    }
+
+    let png = {
+        let (updates, time) = &*updates.borrow();
+
+        frame(display, updates, *time, None)?.build()?
+    };
+
+    if render.to_stdout() {
+        let mut stdout = io::stdout().lock();
+
+        stdout.write_all(&png)?;
+        stdout.flush()?;
+    } else {
+        fs::write(&render.out, png)
+            .wrap_err_with(|| format!("Unable to write {}", render.out.display()))?;
+
+        info!("rendered");
+    }
+
+    Ok(())
+}
+
+/// Render the display for each step of a time range
+#[instrument(skip_all, fields(start = %render.start, end = %render.end, step = ?render.step))]
+pub async fn range(args: &Args, render: &RenderRange) -> Result<()> {
+    let prometheus = prometheus::Manager::new(args)?.client()?;
+    let config = args.config()?.discover(&prometheus).await;
+    let devices: Devices = config.try_into()?;
+    let display = devices.display(render.display.as_deref())?;
+
+    let mut devices_by_id: Vec<_> = devices.devices().into_values().collect();
+    devices_by_id.sort_by_cached_key(|device| device.id());
+
+    let range = Range::new(
+        prometheus,
+        render.start.unix_timestamp(),
+        render.end.unix_timestamp(),
+        render.step,
+    )?;
+
+    let mut frames = vec![];
+
+    for time in range.steps() {
+        let at = range.at(time);
+        let mut updates = HashMap::with_capacity(devices_by_id.len());
+
+        for device in devices_by_id.iter() {
+            match device.update(&at).await {
+                Ok(update) => {
+                    updates.insert(update.id(), update);
+                }
+                Err(e) => warn!(?e, %device, time, "device update error"),
+            }
+        }
+
+        let time = UNIX_EPOCH + Duration::from_secs(time.max(0) as u64);
+
+        frames.push(frame(display, &updates, time, None)?);
+    }
+
+    info!(count = frames.len(), out = ?render.out, "rendered frames");
+
+    match render.format {
+        Format::Apng => {
+            let png = PngBuilder::build_animation(frames, render.frame_delay)?;
+
+            fs::write(&render.out, png)
+                .wrap_err_with(|| format!("Unable to write {}", render.out.display()))?;
+        }
+        Format::Sequence => {
+            fs::create_dir_all(&render.out)
+                .wrap_err_with(|| format!("Unable to create {}", render.out.display()))?;
+
+            for (index, frame) in frames.into_iter().enumerate() {
+                let path = render.out.join(format!("{index:05}.png"));
+
+                fs::write(&path, frame.build()?)
+                    .wrap_err_with(|| format!("Unable to write {}", path.display()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
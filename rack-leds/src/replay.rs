@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eyre::{ensure, Context, Result};
+use tokio::{sync::watch, task::JoinSet, time};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    collector::{UpdateReceiver, UpdateSender},
+    device::Id,
+    recorder::Frame,
+};
+
+/// Send updates from a recording made with [`Recorder`]
+///
+/// Frames are sent with the delays between them in the recording divided by `speed`.  After the
+/// last frame it stays on display.  Recorded devices are matched to configured devices by address.
+///
+/// [`Recorder`]: crate::recorder::Recorder
+pub struct Replay {
+    frames: Vec<Frame>,
+    speed: f64,
+    ids: HashMap<String, Id>,
+    update_sender: UpdateSender,
+}
+
+impl Replay {
+    pub fn new(path: &Path, speed: f64, ids: HashMap<String, Id>) -> Result<Self> {
+        ensure!(speed > 0.0, "replay speed {speed} must be positive");
+
+        let recording = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Unable to read recording {}", path.display()))?;
+
+        let frames = parse(&recording)
+            .wrap_err_with(|| format!("Unable to parse recording {}", path.display()))?;
+
+        let unknown: BTreeSet<_> = frames
+            .iter()
+            .flat_map(|frame| frame.updates.keys())
+            .filter(|address| !ids.contains_key(*address))
+            .collect();
+
+        if !unknown.is_empty() {
+            warn!(
+                ?unknown,
+                "recorded devices are not in the config, leaving them out"
+            );
+        }
+
+        let (update_sender, _) = watch::channel((HashMap::default(), UNIX_EPOCH));
+
+        Ok(Self {
+            frames,
+            speed,
+            ids,
+            update_sender,
+        })
+    }
+
+    #[instrument(name = "replay", skip_all)]
+    pub async fn run(self) -> Result<()> {
+        info!(frames = self.frames.len(), speed = self.speed, "started");
+
+        let mut previous: Option<SystemTime> = None;
+
+        for frame in self.frames {
+            if let Some(previous) = previous {
+                let delay = frame.time.duration_since(previous).unwrap_or_default();
+
+                time::sleep(delay.div_f64(self.speed)).await;
+            }
+
+            previous = Some(frame.time);
+
+            self.update_sender.send_replace(frame.resolve(&self.ids));
+        }
+
+        info!("finished");
+
+        // Keep the last frame on display
+        std::future::pending().await
+    }
+
+    pub fn run_on(self, join_set: &mut JoinSet<Result<()>>) -> Result<()> {
+        join_set
+            .build_task()
+            .name("replay")
+            .spawn(async move { self.run().await })?;
+
+        Ok(())
+    }
+
+    pub fn subscribe(&self) -> UpdateReceiver {
+        self.update_sender.subscribe()
+    }
+}
+
+fn parse(recording: &str) -> Result<Vec<Frame>> {
+    recording
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).wrap_err_with(|| format!("line {}", number + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        device::{Device, Id, Switch},
+        update, Layout, Update,
+    };
+
+    fn updates(id: Id, receive: u64) -> HashMap<Id, Update> {
+        let update = Update::Switch {
+            id,
+            device: update::Switch::new(vec![receive; 8], vec![0; 8], vec![0; 8]),
+            layout: Layout::SwitchEight,
+        };
+
+        HashMap::from([(id, update)])
+    }
+
+    // A paused clock only moves when every task waits, so no frame is replaced before it is read
+    #[tokio::test(start_paused = true)]
+    async fn replay() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        // The config changed between recording and replay so the switch has a new id
+        let recorded = Device::switch(Switch::new("switch", "", "", "", "")).id();
+        let replayed = Device::switch(Switch::new("switch", "", "", "", "")).id();
+
+        let recording: String = [(0, 10), (15, 20), (30, 30)]
+            .into_iter()
+            .map(|(offset, receive)| {
+                let frame = Frame::new(
+                    &updates(recorded, receive),
+                    start + Duration::from_secs(offset),
+                    &HashMap::from([(recorded, "switch".to_string())]),
+                );
+
+                serde_json::to_string(&frame).unwrap() + "\n"
+            })
+            .collect();
+
+        let replay = Replay {
+            frames: parse(&recording).unwrap(),
+            speed: 1.0,
+            ids: HashMap::from([("switch".to_string(), replayed)]),
+            update_sender: watch::channel((HashMap::default(), UNIX_EPOCH)).0,
+        };
+
+        let mut updates = replay.subscribe();
+
+        tokio::spawn(replay.run());
+
+        let mut received = vec![];
+
+        for _ in 0..3 {
+            updates.changed().await.unwrap();
+
+            let (updates, time) = &*updates.borrow_and_update();
+
+            let Some(Update::Switch { id, device, .. }) = updates.get(&replayed) else {
+                panic!("expected a switch update");
+            };
+
+            assert_eq!(replayed, *id);
+
+            received.push((*time, device.receive()[0]));
+        }
+
+        assert_eq!(
+            vec![
+                (start, 10),
+                (start + Duration::from_secs(15), 20),
+                (start + Duration::from_secs(30), 30),
+            ],
+            received
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = parse("\n{\"time\": 1}\n").unwrap_err();
+
+        assert_eq!("line 2", error.to_string());
+    }
+}
@@ -118,13 +118,15 @@ impl Simulated {
     pub fn simulate(&self, rng: &mut SmallRng) -> Update {
         match self {
             Simulated::Switch { id, ports, weights } => {
-                let receive = weights.iter().map(|weight| weight.sample(rng)).collect();
+                let receive: Vec<_> = weights.iter().map(|weight| weight.sample(rng)).collect();
 
-                let transmit = weights.iter().map(|weight| weight.sample(rng)).collect();
+                let transmit: Vec<_> = weights.iter().map(|weight| weight.sample(rng)).collect();
 
                 let poe = weights.iter().map(|weight| weight.sample(rng)).collect();
 
-                let device = update::Switch::new(receive, transmit, poe);
+                // Simulated values are drawn directly, so they are their own rates
+                let device = update::Switch::new(receive.clone(), transmit.clone(), poe)
+                    .with_rates(receive, transmit);
                 let layout = Layout::simulate(*ports);
 
                 Update::Switch {
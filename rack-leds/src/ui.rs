@@ -1,5 +1,6 @@
 mod action;
 mod app;
+mod colors;
 mod components;
 mod config;
 mod gradient;
@@ -8,7 +9,9 @@ mod widgets;
 
 pub use action::Action;
 pub use app::App;
+pub use colors::Colors;
 pub use components::Component;
 pub use config::Config;
 pub use gradient::Gradient;
 pub use tui::Tui;
+pub use widgets::Rack;
@@ -6,6 +6,10 @@ use strum::Display;
 pub enum Action {
     ClearScreen,
     Error(String),
+    #[strum(props(Help = "Next display"))]
+    DisplayNext,
+    #[strum(props(Help = "Previous display"))]
+    DisplayPrevious,
     #[strum(props(Help = "Show detail"))]
     EventLogDetailShow,
     #[strum(props(Help = "Create filter"))]
@@ -82,13 +86,81 @@ pub enum Action {
     FormatRowPrevious,
     #[strum(props(Help = "Show format dialog"))]
     FormatShow,
+    #[strum(props(Help = "Hide collector status", Back = "true"))]
+    HealthHide,
+    #[strum(props(Help = "Show collector status"))]
+    HealthShow,
     Input(KeyEvent),
+    #[strum(props(Help = "Move down"))]
+    InspectDown,
+    #[strum(props(Help = "Stop inspecting", Back = "true"))]
+    InspectHide,
+    #[strum(props(Help = "Move left"))]
+    InspectLeft,
+    #[strum(props(Help = "Move right"))]
+    InspectRight,
+    #[strum(props(Help = "Inspect devices"))]
+    InspectShow,
+    #[strum(props(Help = "Move up"))]
+    InspectUp,
     #[strum(props(Help = "Hide help", Back = "true"))]
     HelpHide,
     #[strum(props(Help = "Show help"))]
     HelpShow,
+    #[strum(props(Help = "Remove spacer"))]
+    LayoutDelete,
+    #[strum(props(Help = "Select entry below"))]
+    LayoutDown,
+    #[strum(props(Help = "Stop editing layout", Back = "true"))]
+    LayoutHide,
+    #[strum(props(Help = "Select column to the left"))]
+    LayoutLeft,
+    #[strum(props(Help = "Move entry down"))]
+    LayoutMoveDown,
+    #[strum(props(Help = "Move entry to the column on the left"))]
+    LayoutMoveLeft,
+    #[strum(props(Help = "Move entry to the column on the right"))]
+    LayoutMoveRight,
+    #[strum(props(Help = "Move entry up"))]
+    LayoutMoveUp,
+    #[strum(props(Help = "Select column to the right"))]
+    LayoutRight,
+    #[strum(props(Help = "Save layout to the config"))]
+    LayoutSave,
+    #[strum(props(Help = "Edit layout"))]
+    LayoutShow,
+    #[strum(props(Help = "Add spacer or make it taller"))]
+    LayoutSpacer,
+    #[strum(props(Help = "Select entry above"))]
+    LayoutUp,
+    #[strum(props(Help = "Apply query to the device"))]
+    QueryApply,
+    #[strum(props(Help = "Edit query"))]
+    QueryEdit,
+    #[strum(props(Help = "Stop editing", Back = "true"))]
+    QueryEditDone,
+    #[strum(props(Help = "Hide queries", Back = "true"))]
+    QueryHide,
+    #[strum(props(Help = "Next query"))]
+    QueryNext,
+    #[strum(props(Help = "Previous query"))]
+    QueryPrevious,
+    /// Result of the query run `generation`
+    QueryResult(usize, Result<Vec<String>, String>),
+    #[strum(props(Help = "Run query"))]
+    QueryRun,
+    #[strum(props(Help = "Save query to the config"))]
+    QuerySave,
+    #[strum(props(Help = "Edit queries"))]
+    QueryShow,
     #[strum(props(Help = "Quit"))]
     Quit,
+    #[strum(props(Help = "Step to the next display snapshot"))]
+    SnapshotNext,
+    #[strum(props(Help = "Pause or resume the display"))]
+    SnapshotPause,
+    #[strum(props(Help = "Step to the previous display snapshot"))]
+    SnapshotPrevious,
     Render,
     Resize(u16, u16),
     Resume,
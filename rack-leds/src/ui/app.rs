@@ -1,4 +1,8 @@
-use std::sync::{atomic::AtomicBool, Arc, Mutex};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+};
 
 use color_eyre::Result;
 use crossterm::event::KeyEvent;
@@ -9,15 +13,20 @@ use tokio::sync::mpsc;
 use tracing::{debug, error, field, instrument, trace, warn};
 
 use crate::{
-    collector::UpdateReceiver,
-    png_builder::PngSender,
+    collector::{History, Prometheus, Status, UpdateReceiver},
+    device::{Device, Id},
     ui::{
         action::Action,
-        components::{fps::FpsCounter, home::Home, Component, Help},
+        components::{
+            fps::FpsCounter,
+            home::{Collected, Home},
+            Component, Health, Help, LayoutEditor, QueryEditor,
+        },
         config::Config,
         tui::{Event, Tui},
+        Colors,
     },
-    Columns,
+    Display,
 };
 
 pub struct App {
@@ -25,6 +34,7 @@ pub struct App {
     config: Config,
     tick_rate: f64,
     frame_rate: f64,
+    mouse: bool,
     components: Vec<Box<dyn Component>>,
     should_quit: bool,
     should_suspend: bool,
@@ -53,8 +63,36 @@ pub enum Mode {
     FilterSubmit,
     #[strum(serialize = "Format")]
     Format,
+    #[strum(serialize = "Collector status")]
+    Health,
     #[strum(serialize = "Help")]
     Help,
+    #[strum(serialize = "Inspect")]
+    Inspect,
+    #[strum(serialize = "Layout")]
+    Layout,
+    #[strum(serialize = "Query")]
+    Query,
+    #[strum(serialize = "Query edit")]
+    QueryEdit,
+}
+
+impl Mode {
+    /// Modes drawn as a popup over the rest of the screen
+    fn is_modal(&self) -> bool {
+        matches!(
+            self,
+            Mode::EventLogFilterCreate
+                | Mode::Filter
+                | Mode::FilterEdit
+                | Mode::FilterSubmit
+                | Mode::Format
+                | Mode::Health
+                | Mode::Help
+                | Mode::Query
+                | Mode::QueryEdit
+        )
+    }
 }
 
 impl App {
@@ -65,12 +103,23 @@ impl App {
         reloadable: Reloadable,
         tick_rate: f64,
         frame_rate: f64,
-        columns: Columns,
+        displays: Vec<Display>,
+        devices: HashMap<Id, Arc<Device>>,
         updates: UpdateReceiver,
-        png_sender: PngSender,
+        status: Status,
+        history: History,
+        prometheus: Option<Arc<Prometheus>>,
+        config_path: Option<PathBuf>,
+        colors: Colors,
     ) -> Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
 
+        let colors = match colors {
+            Colors::Auto => Tui::color_support(),
+            colors => colors,
+        };
+        debug!(?colors, "drawing LEDs");
+
         render_on_update(events.resubscribe(), updates.clone(), action_tx.clone())?;
 
         let mode = Arc::new(Mutex::new(Mode::Home));
@@ -80,9 +129,24 @@ impl App {
             gui_active,
             tick_rate,
             frame_rate,
+            mouse: true,
             components: vec![
-                Box::new(Home::new(columns, updates, png_sender, events, reloadable)),
+                Box::new(Home::new(
+                    Collected {
+                        displays,
+                        devices: devices.clone(),
+                        updates,
+                        status: status.clone(),
+                        history,
+                    },
+                    QueryEditor::new(prometheus, config_path.clone()),
+                    LayoutEditor::new(config_path),
+                    colors,
+                    events,
+                    reloadable,
+                )),
                 Box::new(FpsCounter::default()),
+                Box::new(Health::new(devices, status)),
                 Box::new(Help::new(previous_mode.clone())),
             ],
             should_quit: false,
@@ -96,13 +160,19 @@ impl App {
         })
     }
 
+    /// Capture the mouse to inspect ports by clicking, stopping the terminal from selecting text
+    pub fn mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
     pub fn action_tx(&self) -> mpsc::UnboundedSender<Action> {
         self.action_tx.clone()
     }
 
     pub async fn run(&mut self) -> Result<()> {
         let mut tui = Tui::new(self.gui_active.clone())?
-            // .mouse(true) // uncomment this line to enable mouse support
+            .mouse(self.mouse)
             .tick_rate(self.tick_rate)
             .frame_rate(self.frame_rate);
 
@@ -131,7 +201,6 @@ impl App {
                 tui.suspend()?;
                 action_tx.send(Action::Resume)?;
                 action_tx.send(Action::ClearScreen)?;
-                // tui.mouse(true);
                 tui.enter()?;
             } else if self.should_quit {
                 tui.stop()?;
@@ -165,6 +234,11 @@ impl App {
             }
         }
 
+        // Modals don't use the mouse, and the screen underneath shouldn't react to it
+        if matches!(event, Event::Mouse(_)) && self.mode().is_modal() {
+            return Ok(());
+        }
+
         for component in self.components.iter_mut() {
             if let Some(action) = component.handle_events(Some(event.clone()))? {
                 action_tx.send(action)?;
@@ -188,7 +262,7 @@ impl App {
                 action_tx.send(action.clone())?;
             }
             _ => {
-                if self.mode() == Mode::FilterEdit {
+                if matches!(self.mode(), Mode::FilterEdit | Mode::QueryEdit) {
                     action_tx.send(Action::Input(key))?;
 
                     return Ok(());
@@ -223,7 +297,12 @@ impl App {
                 Action::EventLogFilterCreate => {
                     self.set_mode(Mode::EventLogFilterCreate);
                 }
-                Action::EventLogListShow | Action::FilterHide | Action::FormatHide => {
+                Action::EventLogListShow
+                | Action::FilterHide
+                | Action::FormatHide
+                | Action::HealthHide
+                | Action::InspectHide
+                | Action::LayoutHide => {
                     self.set_mode(Mode::Home);
                 }
                 Action::FilterAdd | Action::FilterEdit => {
@@ -238,6 +317,9 @@ impl App {
                 Action::FormatShow => {
                     self.set_mode(Mode::Format);
                 }
+                Action::HealthShow => {
+                    self.set_mode(Mode::Health);
+                }
                 Action::HelpShow => {
                     {
                         let mut guard = self.previous_mode.lock().unwrap();
@@ -256,6 +338,18 @@ impl App {
 
                     self.set_mode(previous);
                 }
+                Action::InspectShow | Action::QueryHide => {
+                    self.set_mode(Mode::Inspect);
+                }
+                Action::LayoutShow => {
+                    self.set_mode(Mode::Layout);
+                }
+                Action::QueryEdit => {
+                    self.set_mode(Mode::QueryEdit);
+                }
+                Action::QueryEditDone | Action::QueryShow => {
+                    self.set_mode(Mode::Query);
+                }
                 Action::Quit => self.should_quit = true,
                 Action::Render => self.render(tui)?,
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
@@ -0,0 +1,185 @@
+use clap::ValueEnum;
+use ratatui::style::{Color, Style};
+
+/// How LED colours are drawn in the terminal
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Colors {
+    /// Detect from COLORTERM, TERM and NO_COLOR
+    #[default]
+    Auto,
+    /// 24-bit colour
+    Truecolor,
+    /// The xterm 256 colour palette
+    #[value(name = "256")]
+    Indexed,
+    /// The 16 ANSI colours
+    #[value(name = "16")]
+    Ansi,
+    /// Shaded blocks by brightness, without colour
+    Glyph,
+}
+
+/// Shades from off to full brightness
+const GLYPHS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Approximate RGB of the 16 ANSI colours, as xterm draws them
+const ANSI: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::Red, [205, 0, 0]),
+    (Color::Green, [0, 205, 0]),
+    (Color::Yellow, [205, 205, 0]),
+    (Color::Blue, [0, 0, 238]),
+    (Color::Magenta, [205, 0, 205]),
+    (Color::Cyan, [0, 205, 205]),
+    (Color::Gray, [229, 229, 229]),
+    (Color::DarkGray, [127, 127, 127]),
+    (Color::LightRed, [255, 0, 0]),
+    (Color::LightGreen, [0, 255, 0]),
+    (Color::LightYellow, [255, 255, 0]),
+    (Color::LightBlue, [92, 92, 255]),
+    (Color::LightMagenta, [255, 0, 255]),
+    (Color::LightCyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+/// Channel levels of the 6×6×6 colour cube in the xterm 256 colour palette
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Colors {
+    /// What a terminal supports from its `COLORTERM` and `TERM`, or glyphs when `no_color` is set
+    pub fn detect(colorterm: Option<&str>, term: Option<&str>, no_color: bool) -> Self {
+        let term = term.unwrap_or_default();
+
+        if no_color {
+            Self::Glyph
+        } else if matches!(colorterm, Some("truecolor" | "24bit"))
+            || term.contains("truecolor")
+            || term.contains("direct")
+        {
+            Self::Truecolor
+        } else if term.contains("256color") {
+            Self::Indexed
+        } else if term.is_empty() || term == "dumb" || term.starts_with("vt") {
+            Self::Glyph
+        } else {
+            Self::Ansi
+        }
+    }
+
+    /// The character and style drawn for an LED of `color`
+    pub fn cell(self, color: [u8; 3]) -> (char, Style) {
+        let style = Style::new().bg(Color::Black);
+
+        match self {
+            Self::Auto | Self::Truecolor => {
+                let [r, g, b] = color;
+
+                ('█', style.fg(Color::Rgb(r, g, b)))
+            }
+            Self::Indexed => ('█', style.fg(Color::Indexed(indexed(color)))),
+            Self::Ansi => ('█', style.fg(ansi(color))),
+            Self::Glyph => (glyph(color), Style::new().fg(Color::Reset).bg(Color::Reset)),
+        }
+    }
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+/// The nearest colour in the xterm 256 colour cube or grey ramp
+fn indexed(color: [u8; 3]) -> u8 {
+    let level = |channel: u8| {
+        CUBE.iter()
+            .enumerate()
+            .min_by_key(|(_, level)| level.abs_diff(channel))
+            .map(|(index, _)| index as u8)
+            .unwrap_or_default()
+    };
+
+    let [r, g, b] = color.map(level);
+    let cube = [CUBE[r as usize], CUBE[g as usize], CUBE[b as usize]];
+
+    let average = color.iter().map(|&channel| u16::from(channel)).sum::<u16>() / 3;
+    let grey = ((average.saturating_sub(3)) / 10).min(23) as u8;
+    let grey_level = 8 + grey * 10;
+
+    if distance(color, [grey_level; 3]) < distance(color, cube) {
+        232 + grey
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+/// The nearest of the 16 ANSI colours
+fn ansi(color: [u8; 3]) -> Color {
+    ANSI.iter()
+        .min_by_key(|(_, rgb)| distance(color, *rgb))
+        .map(|(ansi, _)| *ansi)
+        .unwrap_or(Color::Reset)
+}
+
+/// A shade for the brightness of `color`, anything lit is at least the lightest shade
+fn glyph(color: [u8; 3]) -> char {
+    let [r, g, b] = color.map(u32::from);
+    let luma = (2126 * r + 7152 * g + 722 * b) / 10000;
+
+    if luma == 0 && color != [0, 0, 0] {
+        return GLYPHS[1];
+    }
+
+    GLYPHS[(luma as usize * GLYPHS.len() / 256).min(GLYPHS.len() - 1)]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        assert_eq!(
+            Colors::Truecolor,
+            Colors::detect(Some("truecolor"), Some("tmux-256color"), false)
+        );
+        assert_eq!(
+            Colors::Indexed,
+            Colors::detect(None, Some("tmux-256color"), false)
+        );
+        assert_eq!(Colors::Ansi, Colors::detect(None, Some("linux"), false));
+        assert_eq!(Colors::Glyph, Colors::detect(None, Some("vt220"), false));
+        assert_eq!(Colors::Glyph, Colors::detect(None, None, false));
+        assert_eq!(
+            Colors::Glyph,
+            Colors::detect(Some("truecolor"), Some("xterm-256color"), true)
+        );
+    }
+
+    #[test]
+    fn indexed() {
+        assert_eq!(16, super::indexed([0, 0, 0]));
+        assert_eq!(196, super::indexed([255, 0, 0]));
+        assert_eq!(231, super::indexed([255, 255, 255]));
+        assert_eq!(244, super::indexed([128, 128, 128]));
+        assert_eq!(34, super::indexed([25, 178, 25]));
+    }
+
+    #[test]
+    fn ansi() {
+        assert_eq!(Color::Black, super::ansi([0, 0, 0]));
+        assert_eq!(Color::Red, super::ansi([178, 25, 25]));
+        assert_eq!(Color::Green, super::ansi([25, 178, 25]));
+        assert_eq!(Color::LightYellow, super::ansi([240, 240, 20]));
+    }
+
+    #[test]
+    fn glyph() {
+        assert_eq!(' ', super::glyph([0, 0, 0]));
+        assert_eq!('░', super::glyph([1, 0, 0]));
+        assert_eq!('░', super::glyph([178, 25, 25]));
+        assert_eq!('▒', super::glyph([25, 178, 25]));
+        assert_eq!('█', super::glyph([255, 255, 255]));
+    }
+}
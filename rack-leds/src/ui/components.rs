@@ -1,13 +1,19 @@
 pub mod fps;
+mod health;
 mod help;
 pub mod home;
+mod layout;
 mod log;
+mod query;
 
 use crate::ui::{tui::Event, Action, Config};
 use color_eyre::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
+pub use health::Health;
 pub use help::Help;
+pub use layout::LayoutEditor;
 pub use log::Log;
+pub use query::QueryEditor;
 use ratatui::{
     layout::{Rect, Size},
     Frame,
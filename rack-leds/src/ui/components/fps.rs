@@ -3,13 +3,13 @@ use std::time::Instant;
 use color_eyre::Result;
 use ratatui::{
     layout::{Constraint, Layout, Rect},
-    style::{Style, Stylize},
+    style::Style,
     text::Span,
     widgets::Paragraph,
     Frame,
 };
 
-use crate::ui::{Action, Component};
+use crate::ui::{app::Mode, Action, Component, Config};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FpsCounter {
@@ -20,6 +20,8 @@ pub struct FpsCounter {
     last_frame_update: Instant,
     frame_count: u32,
     frames_per_second: f64,
+
+    style: Style,
 }
 
 impl Default for FpsCounter {
@@ -37,6 +39,7 @@ impl FpsCounter {
             last_frame_update: Instant::now(),
             frame_count: 0,
             frames_per_second: 0.0,
+            style: Style::default(),
         }
     }
 
@@ -66,6 +69,11 @@ impl FpsCounter {
 }
 
 impl Component for FpsCounter {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.style = config.style(Mode::Home, "fps");
+        Ok(())
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::Tick => self.app_tick()?,
@@ -81,7 +89,7 @@ impl Component for FpsCounter {
             "{:.2} ticks/sec, {:.2} FPS",
             self.ticks_per_second, self.frames_per_second
         );
-        let span = Span::styled(message, Style::new().dim());
+        let span = Span::styled(message, self.style);
         let paragraph = Paragraph::new(span).right_aligned();
         frame.render_widget(paragraph, top);
         Ok(())
@@ -0,0 +1,168 @@
+use std::{collections::HashMap, sync::Arc};
+
+use color_eyre::Result;
+use itertools::Itertools;
+use ratatui::{
+    prelude::*,
+    widgets::{Cell, Clear, Row, Table},
+    Frame,
+};
+
+use crate::{
+    collector::{Round, Status},
+    device::{Device, Id},
+    ui::{app::Mode, widgets::Border, Action, Component, Config},
+    units,
+};
+
+/// Collector status of every configured device
+pub struct Health {
+    devices: Vec<Arc<Device>>,
+    status: Status,
+    config: Config,
+    render: bool,
+}
+
+impl Health {
+    pub fn new(devices: HashMap<Id, Arc<Device>>, status: Status) -> Self {
+        let devices = devices
+            .into_values()
+            .sorted_by_key(|device| device.id())
+            .collect();
+
+        Self {
+            devices,
+            status,
+            config: Config::default(),
+            render: false,
+        }
+    }
+
+    fn rows(&self) -> Vec<Row<'static>> {
+        self.devices
+            .iter()
+            .map(|device| {
+                let status = self.status.device(device.id());
+
+                let error = status
+                    .last_error
+                    .map(|(error, time)| format!("{} {error}", units::clock(time)))
+                    .unwrap_or_default();
+
+                Row::new(vec![
+                    Cell::new(device.to_string()),
+                    Cell::new(optional(status.last_success, units::clock)),
+                    Cell::new(optional(status.latency, units::duration)),
+                    Cell::new(optional(status.layout, |layout| format!("{layout:?}"))),
+                    Cell::new(optional(status.ports, |ports| ports.to_string())),
+                    Cell::new(error).style(self.config.style(Mode::Health, "error")),
+                ])
+            })
+            .collect()
+    }
+}
+
+/// `-` when nothing has been recorded yet
+fn optional<T>(value: Option<T>, format: impl Fn(T) -> String) -> String {
+    value.map(format).unwrap_or_else(|| "-".to_string())
+}
+
+/// Period, last round duration and pool size
+fn summary(round: &Round) -> String {
+    let period = optional(round.period, units::duration);
+    let last = optional(round.last, |(took, finished)| {
+        format!("{} at {}", units::duration(took), units::clock(finished))
+    });
+    let pool = optional(round.pool, |(size, max)| format!("{size}/{max}"));
+
+    format!("period {period}, last round {last}, pool {pool}")
+}
+
+impl Component for Health {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if !self.render {
+            return Ok(());
+        }
+
+        let border = Border::new()
+            .name("Collector status")
+            .detail(summary(&self.status.round()))
+            .help("Esc to dismiss")
+            .uniform(1);
+
+        let header = Row::new([
+            "Device",
+            "Last success",
+            "Latency",
+            "Layout",
+            "Ports",
+            "Last error",
+        ])
+        .style(self.config.style(Mode::Health, "header"));
+
+        let table = Table::new(
+            self.rows(),
+            [
+                Constraint::Length(32),
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Length(20),
+                Constraint::Length(5),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .block(border.build());
+
+        let area = area.inner(Margin::new(2, 1));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::HealthShow => self.render = true,
+            Action::HealthHide => self.render = false,
+            _ => (),
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let round = Round {
+            period: Some(Duration::from_secs(15)),
+            last: None,
+            pool: Some((2, 16)),
+        };
+
+        assert_eq!(
+            "period 15.0 s, last round -, pool 2/16",
+            super::summary(&round)
+        );
+
+        let round = Round {
+            last: Some((Duration::from_millis(250), SystemTime::now())),
+            ..round
+        };
+
+        assert!(super::summary(&round).contains("last round 250 ms at "));
+    }
+}
@@ -84,7 +84,13 @@ impl Component for Help {
             .uniform(1);
 
         let Some(keys) = self.active_keys() else {
-            return draw_no_help(mode, &border, area, frame);
+            let style = self
+                .config
+                .as_ref()
+                .map(|config| config.style(Mode::Help, "mode"))
+                .unwrap_or_default();
+
+            return draw_no_help(mode, style, &border, area, frame);
         };
 
         let rows: Vec<_> = keys
@@ -159,11 +165,17 @@ fn center_exactly(area: Rect, width: u16, height: usize) -> Rect {
     area
 }
 
-fn draw_no_help(mode: &str, border: &Border<'_>, area: Rect, frame: &mut Frame<'_>) -> Result<()> {
+fn draw_no_help(
+    mode: &str,
+    mode_style: Style,
+    border: &Border<'_>,
+    area: Rect,
+    frame: &mut Frame<'_>,
+) -> Result<()> {
     let mut line = Line::default();
 
     line.push_span(Span::raw("Missing key map for current mode "));
-    line.push_span(Span::styled(mode, Style::default().italic()));
+    line.push_span(Span::styled(mode, mode_style));
 
     let width = line.width() + 4;
     let width = width.try_into().unwrap_or(u16::MAX);
@@ -1,147 +1,711 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
 
-use bytes::Bytes;
 use color_eyre::Result;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{prelude::*, widgets::*};
 use ratatui_tracing::{EventReceiver, Reloadable};
 use tokio::sync::mpsc::UnboundedSender;
-use tracing::error;
 
 use crate::{
-    collector::UpdateReceiver,
-    device::Id,
-    png_builder::PngSender,
-    ui::{components::Log, widgets::Display, Action, Component, Config},
-    Columns, PngBuilder, Update,
+    collector::{History, Status, UpdateReceiver},
+    device::{Device, Id},
+    render,
+    ui::{
+        app::Mode,
+        components::{LayoutEditor, Log, QueryEditor},
+        widgets::{Leds, Legend},
+        Action, Colors, Component, Config, Rack,
+    },
+    units, Display, Update,
 };
 
+/// Width of the inspector panel
+const INSPECTOR_WIDTH: u16 = 48;
+
+/// Height of the history panel below the inspector, a title and two rows for each sparkline
+const HISTORY_HEIGHT: u16 = 3 * 3 + 2;
+
+/// The displays and devices shown, and everything collected about them
+pub struct Collected {
+    pub displays: Vec<Display>,
+    pub devices: HashMap<Id, Arc<Device>>,
+    pub updates: UpdateReceiver,
+    pub status: Status,
+    pub history: History,
+}
+
 pub struct Home<'a> {
-    columns: Columns,
+    displays: Vec<Display>,
+    devices: HashMap<Id, Arc<Device>>,
+    selected: usize,
+    /// Inspector cursor on the display canvas
+    inspect: Option<Position>,
+    /// History id of the snapshot shown instead of the newest updates
+    paused: Option<usize>,
+    /// Where the canvas and event log were last drawn, for the mouse
+    canvas_area: Rect,
+    log_area: Rect,
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
     updates: UpdateReceiver,
-    png_sender: PngSender,
+    status: Status,
+    history: History,
+    query: QueryEditor<'a>,
+    layout: LayoutEditor,
+    colors: Colors,
     log: Log<'a>,
 }
 
 impl<'a> Home<'a> {
     pub fn new(
-        columns: Columns,
-        updates: UpdateReceiver,
-        png_sender: PngSender,
+        collected: Collected,
+        query: QueryEditor<'a>,
+        layout: LayoutEditor,
+        colors: Colors,
         events: EventReceiver,
         reloadable: Reloadable,
     ) -> Self {
+        let Collected {
+            displays,
+            devices,
+            updates,
+            status,
+            history,
+        } = collected;
         let log = Log::new(events, reloadable);
 
         Self {
-            columns,
+            displays,
+            devices,
+            selected: 0,
+            inspect: None,
+            paused: None,
+            canvas_area: Rect::default(),
+            log_area: Rect::default(),
             command_tx: Default::default(),
             config: Default::default(),
             updates,
-            png_sender,
+            status,
+            history,
+            query,
+            layout,
+            colors,
             log,
         }
     }
+
+    /// Move the inspector cursor, staying on the canvas of the selected display
+    fn inspect_move(&mut self, x: i32, y: i32) {
+        let (Some(cursor), Some(display)) = (self.inspect, self.displays.get(self.selected)) else {
+            return;
+        };
+
+        let canvas = display.geometry().canvas();
+        let clamp = |position: u16, delta: i32, size: u16| {
+            (i32::from(position) + delta).clamp(0, i32::from(size.saturating_sub(1))) as u16
+        };
+
+        self.inspect = Some(Position::new(
+            clamp(cursor.x, x, canvas.width),
+            clamp(cursor.y, y, canvas.height),
+        ));
+    }
+
+    /// Inspect the device and port clicked at `position` on screen
+    fn inspect_click(&mut self, position: Position) -> Option<Action> {
+        let display = self.displays.get(self.selected)?;
+
+        if !self.canvas_area.contains(position) {
+            return None;
+        }
+
+        let cursor = Position::new(
+            position.x - self.canvas_area.x,
+            position.y - self.canvas_area.y,
+        );
+
+        let updates = self.shown().updates;
+
+        Rack::new(display.columns(), display.palette(), &updates)
+            .ports(display.geometry().rack())
+            .contains_key(&cursor)
+            .then(|| {
+                self.inspect = Some(cursor);
+
+                Action::InspectShow
+            })
+    }
+
+    /// The device and port under the inspector cursor
+    fn selected(&self, updates: &HashMap<Id, Update>) -> Option<(Id, usize)> {
+        let display = self.displays.get(self.selected)?;
+        let cursor = self.inspect?;
+
+        Rack::new(display.columns(), display.palette(), updates)
+            .ports(display.geometry().rack())
+            .get(&cursor)
+            .copied()
+    }
+
+    /// Edit the queries of the device under the inspector cursor
+    fn query_show(&mut self) -> Option<Action> {
+        let updates = self.shown().updates;
+
+        let device = self
+            .selected(&updates)
+            .and_then(|(id, _)| self.devices.get(&id))
+            .cloned();
+
+        match device {
+            Some(device) => {
+                self.query.open(device);
+
+                None
+            }
+            None => Some(Action::QueryHide),
+        }
+    }
+
+    /// The updates shown, from the paused snapshot or the newest once it has expired or when live
+    fn shown(&self) -> Shown {
+        let snapshot = self.paused.map(|id| self.history.snapshot(id));
+
+        let playback = match snapshot {
+            Some(Some(snapshot)) => {
+                return Shown {
+                    updates: snapshot.updates,
+                    time: snapshot.time,
+                    playback: Playback::Paused {
+                        index: snapshot.index,
+                        count: snapshot.count,
+                    },
+                }
+            }
+            Some(None) => Playback::Expired,
+            None => Playback::Live,
+        };
+
+        let (updates, time) = self.updates.borrow().clone();
+
+        Shown {
+            updates,
+            time,
+            playback,
+        }
+    }
+
+    /// Show the snapshot `steps` from the one shown, pausing on the newest when live
+    fn snapshot_step(&mut self, steps: isize) {
+        let Some(id) = self
+            .paused
+            .or_else(|| self.history.newest().map(|snapshot| snapshot.id))
+        else {
+            return;
+        };
+
+        self.paused = self.history.step(id, steps).map(|snapshot| snapshot.id);
+    }
+
+    fn snapshot_pause(&mut self) {
+        if self.paused.is_some() {
+            self.paused = None;
+        } else {
+            self.snapshot_step(0);
+        }
+    }
+
+    /// Edit the columns of the selected display
+    fn layout_show(&mut self) -> Option<Action> {
+        let Some(display) = self.displays.get(self.selected) else {
+            return Some(Action::LayoutHide);
+        };
+
+        let ids = self
+            .devices
+            .values()
+            .map(|device| (device.address(), device.id()))
+            .collect();
+
+        self.layout
+            .open(display.name(), display.columns().clone(), ids);
+
+        None
+    }
+
+    /// Show the columns being edited, or the saved ones once editing stops
+    fn layout_update(&mut self, action: Action) {
+        let columns = if action == Action::LayoutHide {
+            self.layout.close()
+        } else {
+            self.layout.update(action);
+            self.layout.preview()
+        };
+
+        if let (Some(columns), Some(display)) = (columns, self.displays.get_mut(self.selected)) {
+            display.set_columns(columns);
+        }
+    }
+
+    /// Start inspecting at the first device on the selected display, unless already inspecting
+    fn inspect_show(&mut self) {
+        if self.inspect.is_some() {
+            return;
+        }
+
+        let rack = self
+            .displays
+            .get(self.selected)
+            .map(|display| display.geometry().rack())
+            .unwrap_or_default();
+
+        self.inspect = Some(rack.as_position());
+    }
+
+    fn display_next(&mut self) {
+        self.selected = (self.selected + 1) % self.displays.len().max(1);
+    }
+
+    fn display_previous(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.displays.len().saturating_sub(1));
+    }
 }
 
 impl Component for Home<'_> {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.query.register_action_handler(tx.clone());
         self.command_tx = Some(tx);
         Ok(())
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.query.register_config_handler(config.clone());
+        self.layout.register_config_handler(config.clone());
+        self.log.register_config_handler(config.clone())?;
         self.config = config;
         Ok(())
     }
 
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        let position = Position::new(mouse.column, mouse.row);
+
+        let action = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.inspect_click(position),
+            MouseEventKind::ScrollDown if self.log_area.contains(position) => {
+                Some(Action::EventLogNext)
+            }
+            MouseEventKind::ScrollUp if self.log_area.contains(position) => {
+                Some(Action::EventLogPrevious)
+            }
+            _ => None,
+        };
+
+        Ok(action)
+    }
+
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
         frame.render_widget(Clear, area);
-        let update_png = self.updates.has_changed().unwrap_or(false);
-        let (updates, updated_at) = self.updates.borrow().clone();
+        let Shown {
+            updates,
+            time,
+            playback,
+        } = &self.shown();
+        let time = *time;
+        // Live overlays scroll with the clock, a paused snapshot is drawn as it was
+        let frame_time = match playback {
+            Playback::Paused { .. } => time,
+            Playback::Live | Playback::Expired => SystemTime::now(),
+        };
+        let full = area;
+
+        let Some(display) = self.displays.get(self.selected) else {
+            self.canvas_area = Rect::default();
+            self.log_area = area;
+
+            return self.log.draw(frame, area);
+        };
+
+        let canvas = display.geometry().canvas();
+
+        let area = if self.layout.is_open() {
+            let [area, editor] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(INSPECTOR_WIDTH)])
+                    .areas(area);
+
+            self.layout.draw(frame, editor);
 
-        let [status, display, debug] = Layout::vertical([
+            area
+        } else if let Some(cursor) = self.inspect {
+            let [area, inspector] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(INSPECTOR_WIDTH)])
+                    .areas(area);
+
+            let selected = self.selected(updates);
+
+            let inspector = if let Some((id, port)) = selected {
+                let [inspector, history] =
+                    Layout::vertical([Constraint::Min(0), Constraint::Length(HISTORY_HEIGHT)])
+                        .areas(inspector);
+
+                draw_history(frame, history, &self.history, id, port);
+
+                inspector
+            } else {
+                inspector
+            };
+
+            let lines = inspect(
+                &self.config,
+                &self.devices,
+                updates,
+                &self.status,
+                cursor,
+                selected,
+            );
+            let paragraph = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::new().title("Inspect").borders(Borders::ALL));
+
+            frame.render_widget(paragraph, inspector);
+
+            area
+        } else {
+            area
+        };
+
+        let [status, display_area, debug] = Layout::vertical([
             Constraint::Length(1),
-            Constraint::Length(13),
+            Constraint::Length(canvas.height + 2),
             Constraint::Min(20),
         ])
         .areas(area);
 
-        frame.render_widget(Paragraph::new("rack-leds"), status);
+        frame.render_widget(
+            Paragraph::new("rack-leds").style(self.config.style(Mode::Home, "title")),
+            status,
+        );
 
-        let [display] = Layout::horizontal([Constraint::Length(55)]).areas(display);
+        let [display_area, legend_area] =
+            Layout::horizontal([Constraint::Length(canvas.width + 2), Constraint::Fill(1)])
+                .areas(display_area);
 
-        if let Some(png) = draw_display(display, frame, &self.columns, updates, update_png) {
-            self.png_sender.send_replace((png, updated_at));
+        let selected = self.selected(updates).map(|(id, _)| id);
+        draw_legend(frame, legend_area, display, updates, selected, self.colors)?;
+
+        let theme = DisplayTheme {
+            cursor: self.config.style(Mode::Inspect, "cursor"),
+            colors: self.colors,
         };
 
+        self.canvas_area = draw_display(
+            display_area,
+            frame,
+            display,
+            self.displays.len(),
+            (updates, frame_time),
+            self.inspect,
+            &theme,
+        )?;
+
+        let indicator = match playback {
+            Playback::Paused { index, count } => Line::from(format!(
+                " PAUSED {} ({}/{}) ",
+                units::clock(time),
+                index + 1,
+                count
+            ))
+            .style(self.config.style(Mode::Home, "paused")),
+            Playback::Expired => Line::from(format!(
+                " PAUSED snapshot expired, live {} ",
+                units::clock(time)
+            ))
+            .style(self.config.style(Mode::Home, "paused")),
+            Playback::Live => Line::from(format!(" Live {} ", units::clock(time)))
+                .style(self.config.style(Mode::Home, "live")),
+        };
+
+        // Keep clear of the display title, leaving a gap after it
+        let title = Line::raw(display_title(display, self.displays.len())).width() as u16;
+        let [_, indicator_area] =
+            Layout::horizontal([Constraint::Length(title + 1), Constraint::Fill(1)])
+                .areas(display_area.inner(Margin::new(1, 0)));
+
+        if indicator.width() <= usize::from(indicator_area.width) {
+            frame.render_widget(
+                indicator.right_aligned(),
+                indicator_area.rows().next().unwrap_or_default(),
+            );
+        }
+
+        self.log_area = debug;
         self.log.draw(frame, debug)?;
 
+        self.query.draw(frame, full);
+
         Ok(())
     }
 
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        self.log.update(action)
+        match action {
+            Action::DisplayNext => self.display_next(),
+            Action::DisplayPrevious => self.display_previous(),
+            Action::InspectDown => self.inspect_move(0, 1),
+            Action::InspectHide => self.inspect = None,
+            Action::InspectLeft => self.inspect_move(-1, 0),
+            Action::InspectRight => self.inspect_move(1, 0),
+            Action::InspectShow => self.inspect_show(),
+            Action::InspectUp => self.inspect_move(0, -1),
+            Action::LayoutShow => return Ok(self.layout_show()),
+            Action::SnapshotNext => self.snapshot_step(1),
+            Action::SnapshotPause => self.snapshot_pause(),
+            Action::SnapshotPrevious => self.snapshot_step(-1),
+            Action::LayoutDelete
+            | Action::LayoutDown
+            | Action::LayoutHide
+            | Action::LayoutLeft
+            | Action::LayoutMoveDown
+            | Action::LayoutMoveLeft
+            | Action::LayoutMoveRight
+            | Action::LayoutMoveUp
+            | Action::LayoutRight
+            | Action::LayoutSave
+            | Action::LayoutSpacer
+            | Action::LayoutUp => self.layout_update(action),
+            Action::QueryShow => return Ok(self.query_show()),
+            Action::Input(_) if self.query.is_editing() => self.query.update(action),
+            Action::QueryApply
+            | Action::QueryEdit
+            | Action::QueryEditDone
+            | Action::QueryHide
+            | Action::QueryNext
+            | Action::QueryPrevious
+            | Action::QueryResult(..)
+            | Action::QueryRun
+            | Action::QuerySave => self.query.update(action),
+            action => return self.log.update(action),
+        }
+
+        Ok(None)
     }
 }
 
+/// Updates drawn on the display and where they came from
+struct Shown {
+    updates: HashMap<Id, Update>,
+    time: SystemTime,
+    playback: Playback,
+}
+
+enum Playback {
+    Live,
+    /// Showing snapshot `index` of the `count` kept
+    Paused {
+        index: usize,
+        count: usize,
+    },
+    /// The paused snapshot was dropped from the history, so the newest updates are shown
+    Expired,
+}
+
+/// How the display is drawn in the terminal
+struct DisplayTheme {
+    /// Style of the inspector cursor
+    cursor: Style,
+    colors: Colors,
+}
+
+/// Title of the display block, naming `display` when there are `count` > 1
+fn display_title(display: &Display, count: usize) -> String {
+    if count > 1 {
+        format!("Display {}", display.name())
+    } else {
+        "Display".to_string()
+    }
+}
+
+/// Draw `display` with the shown updates at the frame's time, returning where its canvas is
 fn draw_display(
     display_outer: Rect,
     frame: &mut Frame<'_>,
-    columns: &Columns,
-    updates: HashMap<Id, Update>,
-    update_png: bool,
-) -> Option<Bytes> {
-    let display = Block::new().title("Display").borders(Borders::ALL);
-    let display_inner = display.inner(display_outer);
-    frame.render_widget(display, display_outer);
-
-    let widths: Vec<_> = columns
-        .columns()
-        .map(|column| {
-            column
-                .ids()
-                .filter_map(|id| updates.get(&id))
-                .map(|update| update.width())
-                .max()
-                .unwrap_or(0)
-        })
-        .collect();
-
-    let column_rects = Layout::horizontal(Constraint::from_lengths(widths)).split(display_inner);
-
-    column_rects
-        .iter()
-        .zip(columns.columns())
-        .for_each(|(area, column)| {
-            let updates: Vec<_> = column.ids().filter_map(|id| updates.get(&id)).collect();
-
-            let heights: Vec<_> = updates.iter().map(|update| update.height()).collect();
-
-            let layout = Layout::vertical(heights).split(*area);
-
-            layout
-                .iter()
-                .zip(updates.iter())
-                .for_each(|(area, update)| {
-                    let [area] = Layout::horizontal([update.width()]).split(*area)[..] else {
-                        unreachable!("Constraints removed from layout");
-                    };
-
-                    frame.render_widget(Display::new(update), area);
-                });
-        });
-
-    if update_png {
-        match PngBuilder::new(frame, &display_inner).build() {
-            Ok(png) => Some(png),
-            Err(e) => {
-                error!(?e, "error building PNG");
-                None
-            }
-        }
-    } else {
-        None
+    display: &Display,
+    count: usize,
+    (updates, time): (&HashMap<Id, Update>, SystemTime),
+    cursor: Option<Position>,
+    theme: &DisplayTheme,
+) -> Result<Rect> {
+    let block = Block::new()
+        .title(display_title(display, count))
+        .borders(Borders::ALL);
+    let display_inner = block.inner(display_outer);
+    frame.render_widget(block, display_outer);
+
+    let mut canvas = render::canvas(display, updates, time, None)?;
+
+    let brightness = display.brightness();
+
+    if brightness.preview {
+        brightness.apply(&mut canvas, time);
+    }
+
+    frame.render_widget(Leds::new(&canvas, theme.colors), display_inner);
+
+    let cursor =
+        cursor.map(|cursor| Position::new(display_inner.x + cursor.x, display_inner.y + cursor.y));
+
+    if let Some(cell) = cursor
+        .filter(|cursor| display_inner.contains(*cursor))
+        .and_then(|cursor| frame.buffer_mut().cell_mut(cursor))
+    {
+        let fg = cell.fg;
+
+        cell.set_char('▒').set_style(theme.cursor).set_bg(fg);
+    }
+
+    Ok(display_inner)
+}
+
+/// What the colours mean for the `selected` device, or every device on `display`
+fn draw_legend(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    display: &Display,
+    updates: &HashMap<Id, Update>,
+    selected: Option<Id>,
+    colors: Colors,
+) -> Result<()> {
+    let (title, ids): (_, Vec<_>) = match selected {
+        Some(id) => ("Legend — selected device", vec![id]),
+        None => (
+            "Legend",
+            display
+                .columns()
+                .columns()
+                .flat_map(|column| column.ids().collect::<Vec<_>>())
+                .collect(),
+        ),
+    };
+
+    let legend = Legend::new(
+        ids.iter().filter_map(|id| updates.get(id)),
+        display.palette(),
+        colors,
+    )?;
+
+    let block = Block::new().title(title).borders(Borders::ALL);
+    let [area] = Layout::vertical([Constraint::Max(legend.height() + 2)]).areas(area);
+
+    frame.render_widget(&legend, block.inner(area));
+    frame.render_widget(block, area);
+
+    Ok(())
+}
+
+/// Details of the device and port under `cursor`
+fn inspect(
+    config: &Config,
+    devices: &HashMap<Id, Arc<Device>>,
+    updates: &HashMap<Id, Update>,
+    status: &Status,
+    cursor: Position,
+    selected: Option<(Id, usize)>,
+) -> Vec<Line<'static>> {
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{name}: "), config.style(Mode::Inspect, "field")),
+            Span::raw(value),
+        ])
+    };
+
+    let mut lines = vec![field("cursor", format!("{}, {}", cursor.x, cursor.y))];
+
+    let Some((id, port)) = selected else {
+        lines.push(Line::raw("No device"));
+
+        return lines;
+    };
+
+    let Some(device) = devices.get(&id) else {
+        lines.push(field("device", id.to_string()));
+
+        return lines;
+    };
+
+    lines.push(field("device", device.to_string()));
+    lines.push(field("address", device.address()));
+
+    if let Some(update) = updates.get(&id) {
+        lines.push(field("layout", format!("{:?}", update.layout())));
+
+        lines.extend(
+            update
+                .details(port)
+                .into_iter()
+                .map(|(name, value)| field(name, value)),
+        );
+    }
+
+    let device_status = status.device(id);
+
+    if let Some(time) = device_status.last_success {
+        lines.push(field("last update", units::clock(time)));
+    }
+
+    if let Some((error, time)) = device_status.last_error {
+        lines.push(field(
+            "last error",
+            format!("{} {error}", units::clock(time)),
+        ));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        "Queries",
+        config.style(Mode::Inspect, "heading"),
+    ));
+
+    lines.extend(
+        device
+            .queries()
+            .into_iter()
+            .map(|(name, query)| field(name, query.get())),
+    );
+
+    lines
+}
+
+/// Sparklines of the collected values of `port` over the whole history window
+fn draw_history(frame: &mut Frame<'_>, area: Rect, history: &History, id: Id, port: usize) {
+    let window = history.window();
+    let block = Block::new()
+        .title(format!("Last {} minutes", window.as_secs() / 60))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let port = history.port(id, port);
+    let end = port.times.last().copied().unwrap_or_else(SystemTime::now);
+    let rows = port.downsample(end, window, usize::from(inner.width));
+
+    let areas = Layout::vertical(vec![Constraint::Length(3); rows.len()]).split(inner);
+
+    for ((area, (scale, values)), (_, collected)) in areas.iter().zip(rows).zip(&port.rows) {
+        let max = values.iter().max().copied().unwrap_or_default();
+        let last = collected.last().copied().unwrap_or_default();
+
+        let title = format!(
+            "{} {}, max {}",
+            scale.name(),
+            scale.format(last),
+            scale.format(max)
+        );
+
+        let sparkline = Sparkline::default()
+            .block(Block::new().title(title))
+            .data(&values)
+            .max(max.max(1));
+
+        frame.render_widget(sparkline, *area);
     }
 }
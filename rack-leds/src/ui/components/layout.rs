@@ -0,0 +1,458 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use eyre::{OptionExt, Result};
+use ratatui::{
+    prelude::*,
+    widgets::{Clear, List, ListItem, Paragraph, Wrap},
+};
+
+use crate::{
+    column::Slot,
+    config::{Column, Config, Device, Entry},
+    device::Id,
+    ui::{self, app::Mode, widgets::Border, Action},
+    Columns,
+};
+
+/// Rearrange the columns of a display, previewed on the TUI and saved to the config
+pub struct LayoutEditor {
+    config_path: Option<PathBuf>,
+    /// Styles and key bindings, not the config being edited
+    ui_config: ui::Config,
+    /// The config being edited and the name of the display
+    config: Option<(Config, String)>,
+    /// Running device for each configured address
+    ids: HashMap<String, Id>,
+    /// Discovered devices by column, they aren't in the config so they stay below the edited
+    /// devices
+    discovered: Vec<Vec<Id>>,
+    /// Columns of the display when it was opened or last saved
+    saved: Option<Columns>,
+    cursor: Cursor,
+    message: Option<Result<String, String>>,
+}
+
+/// Selected column and entry within it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Cursor {
+    column: usize,
+    row: usize,
+}
+
+impl LayoutEditor {
+    pub fn new(config_path: Option<PathBuf>) -> Self {
+        Self {
+            config_path,
+            ui_config: ui::Config::default(),
+            config: None,
+            ids: HashMap::default(),
+            discovered: vec![],
+            saved: None,
+            cursor: Cursor::default(),
+            message: None,
+        }
+    }
+
+    pub fn register_config_handler(&mut self, config: ui::Config) {
+        self.ui_config = config;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Edit the columns of `display`, currently showing `columns`
+    pub fn open(&mut self, display: &str, columns: Columns, ids: HashMap<String, Id>) {
+        self.ids = ids;
+        self.cursor = Cursor::default();
+        self.message = None;
+
+        self.config = match self.read(display) {
+            Ok(config) => Some((config, display.to_string())),
+            Err(e) => {
+                self.message = Some(Err(format!("{e:#}")));
+
+                None
+            }
+        };
+
+        let configured: HashSet<Id> = self
+            .config
+            .iter()
+            .flat_map(|(config, _)| {
+                self.ids
+                    .iter()
+                    .filter(|(address, _)| config.is_configured(address))
+                    .map(|(_, id)| *id)
+            })
+            .collect();
+
+        self.discovered = columns
+            .columns()
+            .map(|column| column.ids().filter(|id| !configured.contains(id)).collect())
+            .collect();
+
+        self.saved = Some(columns);
+    }
+
+    fn read(&self, display: &str) -> Result<Config> {
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_eyre("No --config to edit")?;
+
+        let config = Config::read(path)?;
+        config
+            .columns(display)
+            .ok_or_eyre("Display is not in the config, it may have no columns")?;
+
+        Ok(config)
+    }
+
+    /// Stop editing, returning the columns to show
+    pub fn close(&mut self) -> Option<Columns> {
+        self.config = None;
+
+        self.saved.take()
+    }
+
+    fn columns_mut(&mut self) -> Option<&mut Vec<Column>> {
+        let (config, display) = self.config.as_mut()?;
+
+        config.columns_mut(display)
+    }
+
+    /// The columns being edited as shown on the display, with discovered devices
+    pub fn preview(&self) -> Option<Columns> {
+        let (config, display) = self.config.as_ref()?;
+        let configured = config.columns(display)?;
+
+        let columns = (0..configured.len().max(self.discovered.len()))
+            .map(|index| {
+                let mut slots = configured
+                    .get(index)
+                    .map(|column| {
+                        column
+                            .resolve(|device| self.ids.get(device.address()).copied())
+                            .slots()
+                            .to_vec()
+                    })
+                    .unwrap_or_default();
+
+                let discovered = self.discovered.get(index).into_iter().flatten();
+                slots.extend(discovered.copied().map(Slot::Device));
+
+                crate::Column::with_slots(slots)
+            })
+            .collect();
+
+        Some(Columns::new(columns))
+    }
+
+    fn save(&mut self) -> Result<String> {
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_eyre("No --config to save to")?;
+        let (config, display) = self.config.as_ref().ok_or_eyre("Nothing to save")?;
+        let columns = config
+            .columns(display)
+            .ok_or_eyre("Display is not in the config")?;
+
+        Config::save_columns(path, display, columns)?;
+        self.saved = self.preview();
+
+        Ok(format!(
+            "Saved to {}, restart to update the LEDs",
+            path.display()
+        ))
+    }
+
+    pub fn update(&mut self, action: Action) {
+        if action == Action::LayoutSave {
+            self.message = Some(self.save().map_err(|e| format!("{e:#}")));
+
+            return;
+        }
+
+        let cursor = self.cursor;
+
+        let Some(columns) = self.columns_mut() else {
+            return;
+        };
+
+        self.cursor = match action {
+            Action::LayoutDelete => delete(columns, cursor),
+            Action::LayoutDown => select(columns, cursor, 0, 1),
+            Action::LayoutLeft => select(columns, cursor, -1, 0),
+            Action::LayoutMoveDown => shift(columns, cursor, 0, 1),
+            Action::LayoutMoveLeft => shift(columns, cursor, -1, 0),
+            Action::LayoutMoveRight => shift(columns, cursor, 1, 0),
+            Action::LayoutMoveUp => shift(columns, cursor, 0, -1),
+            Action::LayoutRight => select(columns, cursor, 1, 0),
+            Action::LayoutSpacer => spacer(columns, cursor),
+            Action::LayoutUp => select(columns, cursor, 0, -1),
+            _ => cursor,
+        };
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        if !self.is_open() {
+            return;
+        }
+
+        let style = |name| self.ui_config.style(Mode::Layout, name);
+
+        let border = Border::new()
+            .name("Layout")
+            .help("Esc to dismiss")
+            .uniform(1)
+            .build();
+        let inner = border.inner(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(border, area);
+
+        let [help_area, message_area, list_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        let help = Paragraph::new(
+            "Arrows select, Shift-arrows move, s adds a spacer, Backspace removes it, w saves",
+        )
+        .wrap(Wrap { trim: true })
+        .style(style("help"));
+
+        frame.render_widget(help, help_area);
+
+        if let Some(message) = &self.message {
+            let message = match message {
+                Ok(message) => Paragraph::new(message.as_str()).style(style("ok")),
+                Err(message) => Paragraph::new(message.as_str()).style(style("error")),
+            };
+
+            frame.render_widget(message.wrap(Wrap { trim: true }), message_area);
+        }
+
+        let Some((config, display)) = &self.config else {
+            return;
+        };
+
+        let columns = config.columns(display).unwrap_or_default();
+        let cursor = self.cursor;
+
+        let items: Vec<_> = columns
+            .iter()
+            .enumerate()
+            .flat_map(|(index, column)| {
+                let heading = ListItem::new(Line::styled(
+                    format!("Column {}", index + 1),
+                    style("heading"),
+                ));
+
+                let entries = column
+                    .entries()
+                    .iter()
+                    .enumerate()
+                    .map(move |(row, entry)| {
+                        let item = ListItem::new(format!("  {}", label(entry)));
+
+                        if cursor == (Cursor { column: index, row }) {
+                            item.style(style("selected"))
+                        } else {
+                            item
+                        }
+                    });
+
+                std::iter::once(heading).chain(entries)
+            })
+            .collect();
+
+        frame.render_widget(List::new(items), list_area);
+    }
+}
+
+fn label(entry: &Entry) -> String {
+    match entry {
+        Entry::Device(device) => match device.as_ref() {
+            Device::AccessPoint { address, name, .. } => format!("AP {name} ({address})"),
+            Device::Switch { address, .. } => format!("switch {address}"),
+        },
+        Entry::Spacer { spacer } => format!("spacer of {spacer}"),
+    }
+}
+
+/// Keep `cursor` on an entry of `columns`, or the top of an empty column
+fn clamp(columns: &[Column], cursor: Cursor) -> Cursor {
+    let column = cursor.column.min(columns.len().saturating_sub(1));
+    let rows = columns
+        .get(column)
+        .map(|column| column.entries().len())
+        .unwrap_or_default();
+
+    Cursor {
+        column,
+        row: cursor.row.min(rows.saturating_sub(1)),
+    }
+}
+
+fn offset(position: usize, delta: i32) -> usize {
+    position.saturating_add_signed(delta as isize)
+}
+
+/// Move the cursor by `x` columns and `y` entries
+fn select(columns: &[Column], cursor: Cursor, x: i32, y: i32) -> Cursor {
+    clamp(
+        columns,
+        Cursor {
+            column: offset(cursor.column, x),
+            row: offset(cursor.row, y),
+        },
+    )
+}
+
+/// Move the selected entry by `x` columns or `y` entries
+///
+/// Moving right of the last column starts a new one and columns left empty are removed.
+fn shift(columns: &mut Vec<Column>, cursor: Cursor, x: i32, y: i32) -> Cursor {
+    let Some(rows) = columns
+        .get(cursor.column)
+        .map(|column| column.entries().len())
+        .filter(|rows| cursor.row < *rows)
+    else {
+        return cursor;
+    };
+
+    if x == 0 {
+        let row = offset(cursor.row, y).min(rows - 1);
+        columns[cursor.column].entries_mut().swap(cursor.row, row);
+
+        return Cursor { row, ..cursor };
+    }
+
+    let Some(mut column) = cursor.column.checked_add_signed(x as isize) else {
+        return cursor;
+    };
+
+    if column == columns.len() {
+        columns.push(Column::default());
+    }
+
+    let entry = columns[cursor.column].entries_mut().remove(cursor.row);
+    let entries = columns[column].entries_mut();
+    let row = cursor.row.min(entries.len());
+    entries.insert(row, entry);
+
+    if columns[cursor.column].entries().is_empty() {
+        columns.remove(cursor.column);
+
+        if column > cursor.column {
+            column -= 1;
+        }
+    }
+
+    Cursor { column, row }
+}
+
+/// Add an empty row above the selected entry, or make the selected spacer taller
+fn spacer(columns: &mut Vec<Column>, cursor: Cursor) -> Cursor {
+    if columns.is_empty() {
+        columns.push(Column::default());
+    }
+
+    let entries = columns[cursor.column].entries_mut();
+
+    match entries.get_mut(cursor.row) {
+        Some(Entry::Spacer { spacer }) => *spacer += 1,
+        _ => entries.insert(cursor.row, Entry::Spacer { spacer: 1 }),
+    }
+
+    cursor
+}
+
+/// Remove the selected spacer, devices stay in the config
+fn delete(columns: &mut [Column], cursor: Cursor) -> Cursor {
+    if let Some(column) = columns.get_mut(cursor.column) {
+        if let Some(Entry::Spacer { .. }) = column.entries().get(cursor.row) {
+            column.entries_mut().remove(cursor.row);
+        }
+    }
+
+    clamp(columns, cursor)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn columns() -> Vec<Column> {
+        serde_json::from_str(
+            r#"[
+                {"devices": [{"Switch": {"address": "core"}}, {"spacer": 2}]},
+                {"devices": [{"Switch": {"address": "edge"}}]}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    fn labels(columns: &[Column]) -> Vec<Vec<String>> {
+        columns
+            .iter()
+            .map(|column| column.entries().iter().map(label).collect())
+            .collect()
+    }
+
+    #[test]
+    fn shift() {
+        let mut columns = columns();
+
+        let cursor = super::shift(&mut columns, Cursor::default(), 0, 1);
+        assert_eq!(Cursor { column: 0, row: 1 }, cursor);
+        assert_eq!(
+            vec![vec!["spacer of 2", "switch core"], vec!["switch edge"]],
+            labels(&columns)
+        );
+
+        let cursor = super::shift(&mut columns, cursor, 1, 0);
+        assert_eq!(Cursor { column: 1, row: 1 }, cursor);
+        assert_eq!(
+            vec![vec!["spacer of 2"], vec!["switch edge", "switch core"]],
+            labels(&columns)
+        );
+
+        let cursor = super::shift(&mut columns, cursor, 1, 0);
+        assert_eq!(Cursor { column: 2, row: 0 }, cursor);
+
+        let cursor = super::shift(&mut columns, Cursor { column: 0, row: 0 }, 1, 0);
+        assert_eq!(Cursor { column: 0, row: 0 }, cursor);
+        assert_eq!(
+            vec![vec!["spacer of 2", "switch edge"], vec!["switch core"]],
+            labels(&columns)
+        );
+    }
+
+    #[test]
+    fn spacer() {
+        let mut columns = columns();
+
+        super::spacer(&mut columns, Cursor { column: 0, row: 1 });
+        super::spacer(&mut columns, Cursor { column: 1, row: 0 });
+
+        let cursor = delete(&mut columns, Cursor { column: 0, row: 0 });
+        assert_eq!(Cursor { column: 0, row: 0 }, cursor);
+
+        let cursor = delete(&mut columns, Cursor { column: 1, row: 0 });
+        assert_eq!(Cursor { column: 1, row: 0 }, cursor);
+
+        assert_eq!(
+            vec![vec!["switch core", "spacer of 3"], vec!["switch edge"]],
+            labels(&columns)
+        );
+    }
+}
@@ -2,16 +2,15 @@ use eyre::Result;
 use ratatui::{
     layout::{Constraint, Layout, Size},
     prelude::Rect,
-    style::{Color, Stylize},
     text::Line,
     Frame,
 };
 use ratatui_tracing::{
-    widgets::{EventLog, EventLogState, Filter, Format},
+    widgets::{CreateFilter, EventLog, EventLogState, Filter, FilterEdit, Format},
     EventReceiver, Reloadable,
 };
 
-use crate::ui::{widgets::Border, Action, Component};
+use crate::ui::{app::Mode, widgets::Border, Action, Component, Config};
 
 #[derive(Default)]
 enum ViewState {
@@ -24,6 +23,7 @@ enum ViewState {
 pub struct Log<'a> {
     pub(crate) log: EventLogState<'a>,
     view_state: ViewState,
+    config: Config,
 }
 
 impl<'a> Log<'a> {
@@ -33,6 +33,7 @@ impl<'a> Log<'a> {
         Self {
             log,
             view_state: Default::default(),
+            config: Default::default(),
         }
     }
 
@@ -62,7 +63,19 @@ impl<'a> Log<'a> {
             .help("Esc to dismiss")
             .build();
 
-        frame.render_stateful_widget(Filter::default().block(block), center, &mut self.log.filter);
+        let style = |name| self.config.style(Mode::Filter, name);
+
+        let filter_edit = FilterEdit::default()
+            .error_text_style(style("error"))
+            .input_line_error_style(style("input_error"))
+            .input_line_ok_style(style("input_ok"));
+
+        let filter = Filter::default()
+            .block(block)
+            .filter_edit(filter_edit)
+            .highlight_style(style("highlight"));
+
+        frame.render_stateful_widget(filter, center, &mut self.log.filter);
     }
 
     fn render_format(&mut self, area: Rect, frame: &mut Frame<'_>) {
@@ -83,7 +96,16 @@ impl<'a> Log<'a> {
             .help("Esc to dismiss")
             .build();
 
-        frame.render_stateful_widget(Format::default().block(block), center, &mut self.log.format);
+        let style = |name| self.config.style(Mode::Format, name);
+
+        let format = Format::default()
+            .block(block)
+            .cell_highlight_style(style("cell_highlight"))
+            .header_style(style("header"))
+            .row_highlight_style(style("row_highlight"))
+            .table_style(style("table"));
+
+        frame.render_stateful_widget(format, center, &mut self.log.format);
     }
 }
 
@@ -94,17 +116,25 @@ impl Component for Log<'_> {
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.config = config;
+
+        Ok(())
+    }
+
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let style = |name| self.config.style(Mode::Home, name);
+
         let status = if self.log.is_live() {
-            Line::from("Live").bold()
+            Line::from("Live").style(style("live"))
         } else {
-            Line::from("PAUSED").bold().fg(Color::Yellow)
+            Line::from("PAUSED").style(style("paused"))
         };
 
         let detail = if self.log.is_live() {
             let total = self.log.total();
 
-            Line::from(format!("{total} events")).bold()
+            Line::from(format!("{total} events")).style(style("live"))
         } else {
             let total = self.log.total();
             let history = self.log.history();
@@ -123,8 +153,7 @@ impl Component for Log<'_> {
                 history.offset(),
                 history.len()
             ))
-            .bold()
-            .fg(Color::Yellow)
+            .style(style("paused"))
         };
 
         let block = Border::new()
@@ -133,7 +162,14 @@ impl Component for Log<'_> {
             .detail(detail)
             .build();
 
-        let event_log = EventLog::default().block(block);
+        let create_filter = CreateFilter::default()
+            .highlight_style(self.config.style(Mode::EventLogFilterCreate, "highlight"));
+
+        let event_log = EventLog::default()
+            .block(block)
+            .create_filter(create_filter)
+            .highlight_style(style("highlight"))
+            .truncated_style(style("truncated"));
 
         frame.render_stateful_widget(event_log, area, &mut self.log);
 
@@ -0,0 +1,351 @@
+use std::{path::PathBuf, sync::Arc};
+
+use eyre::{OptionExt, Result};
+use itertools::Itertools;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+use tui_textarea::{CursorMove, TextArea};
+
+use crate::{
+    collector::{prometheus::Sample, Prometheus},
+    config::Config,
+    device::Device,
+    ui::{self, app::Mode, widgets::Border, Action},
+};
+
+/// Edit the queries of a running device, test them against Prometheus and save them to the config
+pub struct QueryEditor<'a> {
+    prometheus: Option<Arc<Prometheus>>,
+    config_path: Option<PathBuf>,
+    command_tx: Option<UnboundedSender<Action>>,
+    ui_config: ui::Config,
+    device: Option<Arc<Device>>,
+    /// The device was discovered, so its queries can't be saved
+    discovered: bool,
+    selected: usize,
+    editing: bool,
+    text_area: TextArea<'a>,
+    result: Result<Vec<String>, String>,
+    /// Increased for each run and selection, results from earlier generations are dropped
+    generation: usize,
+    message: Option<Result<String, String>>,
+}
+
+impl<'a> QueryEditor<'a> {
+    pub fn new(prometheus: Option<Arc<Prometheus>>, config_path: Option<PathBuf>) -> Self {
+        Self {
+            prometheus,
+            config_path,
+            command_tx: None,
+            ui_config: ui::Config::default(),
+            device: None,
+            discovered: false,
+            selected: 0,
+            editing: false,
+            text_area: TextArea::default(),
+            result: Ok(vec![]),
+            generation: 0,
+            message: None,
+        }
+    }
+
+    pub fn register_action_handler(&mut self, tx: UnboundedSender<Action>) {
+        self.command_tx = Some(tx);
+    }
+
+    pub fn register_config_handler(&mut self, config: ui::Config) {
+        self.ui_config = config;
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.device.is_some() && self.editing
+    }
+
+    /// Edit the queries of `device`
+    pub fn open(&mut self, device: Arc<Device>) {
+        self.discovered = self
+            .config_path
+            .as_ref()
+            .and_then(|path| Config::read(path).ok())
+            .is_some_and(|config| !config.is_configured(&device.address()));
+        self.device = Some(device);
+        self.select(0);
+    }
+
+    pub fn close(&mut self) {
+        self.generation += 1;
+        self.device = None;
+        self.editing = false;
+    }
+
+    /// Select query `index` and discard any unapplied changes
+    fn select(&mut self, index: usize) {
+        let Some(device) = &self.device else {
+            return;
+        };
+
+        let queries = device.queries();
+
+        self.selected = index.min(queries.len().saturating_sub(1));
+        self.editing = false;
+        self.generation += 1;
+        self.result = Ok(vec![]);
+        self.message = self.discovered.then(|| {
+            Err(format!(
+                "{} was discovered, add it to the config to save queries",
+                device.address()
+            ))
+        });
+
+        let query = queries
+            .get(self.selected)
+            .map(|(_, query)| query.get())
+            .unwrap_or_default();
+
+        self.text_area = new_text_area(query);
+    }
+
+    fn text(&self) -> String {
+        self.text_area.lines().join("")
+    }
+
+    /// Run the query being edited, the result arrives as [`Action::QueryResult`]
+    fn run(&mut self) {
+        self.generation += 1;
+
+        let (Some(prometheus), Some(command_tx)) = (&self.prometheus, &self.command_tx) else {
+            self.result = Err("No Prometheus --source to query".to_string());
+
+            return;
+        };
+
+        let prometheus = prometheus.clone();
+        let command_tx = command_tx.clone();
+        let query = self.text();
+        let generation = self.generation;
+
+        self.result = Ok(vec!["running…".to_string()]);
+
+        tokio::spawn(async move {
+            let result = prometheus
+                .get_vector(&query)
+                .await
+                .map(|vector| lines(&vector))
+                .map_err(|e| format!("{e:#}"));
+
+            command_tx
+                .send(Action::QueryResult(generation, result))
+                .ok();
+        });
+    }
+
+    /// Replace the selected query of the running device with the one being edited
+    fn apply(&mut self) -> Option<(&'static str, String)> {
+        let device = self.device.as_ref()?;
+        let (name, query) = device.queries().into_iter().nth(self.selected)?;
+        let text = self.text();
+
+        query.set(text.clone());
+        device.reset();
+
+        Some((name, text))
+    }
+
+    /// Apply the query being edited and write it to the config file
+    fn save(&mut self) -> Result<String> {
+        let address = self
+            .device
+            .as_ref()
+            .ok_or_eyre("No device selected")?
+            .address();
+        let (name, query) = self.apply().ok_or_eyre("No query selected")?;
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_eyre("No --config to save to")?;
+
+        Config::save_query(path, &address, name, &query)?;
+
+        Ok(format!("Saved {name} to {}", path.display()))
+    }
+
+    pub fn update(&mut self, action: Action) {
+        match action {
+            Action::Input(key) => {
+                self.text_area.input(key);
+            }
+            Action::QueryApply => {
+                self.message = self.apply().map(|(name, _)| Ok(format!("Applied {name}")));
+            }
+            Action::QueryEdit => self.editing = true,
+            Action::QueryEditDone => self.editing = false,
+            Action::QueryHide => self.close(),
+            Action::QueryNext => self.select(self.selected + 1),
+            Action::QueryPrevious => self.select(self.selected.saturating_sub(1)),
+            // Results of earlier runs or selections are dropped
+            Action::QueryResult(generation, result) if generation == self.generation => {
+                self.result = result;
+            }
+            Action::QueryRun => self.run(),
+            Action::QuerySave => self.message = Some(self.save().map_err(|e| format!("{e:#}"))),
+            _ => (),
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(device) = &self.device else {
+            return;
+        };
+
+        let queries = device.queries();
+        let style = |name| self.ui_config.style(Mode::Query, name);
+
+        let area = area.inner(Margin::new(4, 2));
+
+        let border = Border::new()
+            .horizontal(1)
+            .name(format!("Queries — {device}"))
+            .help("Esc to dismiss")
+            .build();
+        let inner = border.inner(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(border, area);
+
+        let [list_area, edit_area, message_area, result_area] = Layout::vertical([
+            Constraint::Length(queries.len() as u16),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        let items: Vec<_> = queries
+            .iter()
+            .enumerate()
+            .map(|(index, (name, query))| {
+                let item = ListItem::new(Line::from(vec![
+                    Span::styled(format!("{name}: "), style("field")),
+                    Span::raw(query.get()),
+                ]));
+
+                if index == self.selected {
+                    item.style(style("selected"))
+                } else {
+                    item
+                }
+            })
+            .collect();
+
+        frame.render_widget(List::new(items), list_area);
+
+        let edit_style = if self.editing {
+            style("editing")
+        } else {
+            style("idle")
+        };
+
+        self.text_area.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(edit_style)
+                .title("Enter to edit, r to run, a to apply, w to save"),
+        );
+        frame.render_widget(&self.text_area, edit_area);
+
+        if let Some(message) = &self.message {
+            let line = match message {
+                Ok(message) => Line::from(message.as_str()).style(style("ok")),
+                Err(message) => Line::from(message.as_str()).style(style("error")),
+            };
+
+            frame.render_widget(line, message_area);
+        }
+
+        let result = match &self.result {
+            Ok(lines) => Paragraph::new(
+                lines
+                    .iter()
+                    .map(|line| Line::raw(line.as_str()))
+                    .collect_vec(),
+            ),
+            Err(error) => Paragraph::new(error.as_str()).style(style("error")),
+        };
+
+        frame.render_widget(result.wrap(Wrap { trim: false }), result_area);
+    }
+}
+
+/// One line per sample, labels sorted by name then the value
+fn lines(vector: &[Sample]) -> Vec<String> {
+    if vector.is_empty() {
+        return vec!["empty vector".to_string()];
+    }
+
+    vector
+        .iter()
+        .map(|(labels, value)| {
+            let labels = labels
+                .iter()
+                .sorted()
+                .map(|(name, value)| format!("{name}=\"{value}\""))
+                .join(", ");
+
+            format!("{{{labels}}} {value}")
+        })
+        .collect()
+}
+
+fn new_text_area<'a>(text: String) -> TextArea<'a> {
+    let mut text_area = TextArea::new(vec![text]);
+    text_area.set_cursor_line_style(Style::default());
+    text_area.move_cursor(CursorMove::End);
+
+    text_area
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn result_generation() {
+        let mut editor = QueryEditor::new(None, None);
+
+        editor.update(Action::QueryRun);
+        let running = editor.generation;
+
+        editor.update(Action::QueryResult(running - 1, Ok(vec!["old".into()])));
+        assert!(editor.result.is_err());
+
+        editor.update(Action::QueryResult(running, Ok(vec!["new".into()])));
+        assert_eq!(Ok(vec!["new".to_string()]), editor.result);
+
+        editor.update(Action::QueryHide);
+        editor.update(Action::QueryResult(running, Ok(vec!["late".into()])));
+        assert_eq!(Ok(vec!["new".to_string()]), editor.result);
+    }
+
+    #[test]
+    fn lines() {
+        let vector = vec![(
+            HashMap::from([
+                ("port_num".to_string(), "3".to_string()),
+                ("instance".to_string(), "core".to_string()),
+            ]),
+            0.25,
+        )];
+
+        assert_eq!(
+            vec![r#"{instance="core", port_num="3"} 0.25"#.to_string()],
+            super::lines(&vector)
+        );
+        assert_eq!(vec!["empty vector".to_string()], super::lines(&[]));
+    }
+}
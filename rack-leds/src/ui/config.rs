@@ -13,6 +13,8 @@ use tracing::error;
 use crate::ui::{action::Action, app::Mode};
 
 const CONFIG: &str = include_str!("../../.config/config.toml");
+const DARK: &str = include_str!("../../.config/themes/dark.toml");
+const LIGHT: &str = include_str!("../../.config/themes/light.toml");
 
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
@@ -30,6 +32,35 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub theme: Theme,
+}
+
+/// Styles used for anything not set in `styles`
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+static DARK_STYLES: LazyLock<Styles> = LazyLock::new(|| theme_styles(DARK));
+static LIGHT_STYLES: LazyLock<Styles> = LazyLock::new(|| theme_styles(LIGHT));
+
+fn theme_styles(theme: &str) -> Styles {
+    let theme: Config = toml::from_str(theme).unwrap();
+
+    theme.styles
+}
+
+impl Theme {
+    fn styles(&self) -> &'static Styles {
+        match self {
+            Theme::Dark => &DARK_STYLES,
+            Theme::Light => &LIGHT_STYLES,
+        }
+    }
 }
 
 pub static PROJECT_NAME: LazyLock<String> =
@@ -87,15 +118,22 @@ impl Config {
                     .or_insert_with(|| cmd.clone());
             }
         }
-        for (mode, default_styles) in default_config.styles.iter() {
-            let user_styles = cfg.styles.entry(mode.clone()).or_default();
-            for (style_key, style) in default_styles.iter() {
-                user_styles.entry(style_key.clone()).or_insert(*style);
-            }
-        }
+        // The theme is preferred over built-in defaults for styles the user didn't set
+        let theme = cfg.theme;
+        cfg.styles.fill_from(theme.styles());
+        cfg.styles.fill_from(&default_config.styles);
 
         Ok(cfg)
     }
+
+    /// Style `name` of `mode`, the default style when it is not configured
+    pub fn style(&self, mode: Mode, name: &str) -> Style {
+        self.styles
+            .get(&mode)
+            .and_then(|styles| styles.get(name))
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 pub fn get_data_dir() -> PathBuf {
@@ -323,6 +361,18 @@ pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct Styles(pub HashMap<Mode, HashMap<String, Style>>);
 
+impl Styles {
+    /// Set styles from `fallback` that are not already set
+    fn fill_from(&mut self, fallback: &Styles) {
+        for (mode, fallback_styles) in fallback.iter() {
+            let styles = self.entry(mode.clone()).or_default();
+            for (style_key, style) in fallback_styles.iter() {
+                styles.entry(style_key.clone()).or_insert(*style);
+            }
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Styles {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -368,7 +418,9 @@ fn process_color_string(color_str: &str) -> (String, Modifier) {
         .replace("bright ", "")
         .replace("bold ", "")
         .replace("underline ", "")
-        .replace("inverse ", "");
+        .replace("inverse ", "")
+        .replace("dim ", "")
+        .replace("italic ", "");
 
     let mut modifiers = Modifier::empty();
     if color_str.contains("underline") {
@@ -380,6 +432,12 @@ fn process_color_string(color_str: &str) -> (String, Modifier) {
     if color_str.contains("inverse") {
         modifiers |= Modifier::REVERSED;
     }
+    if color_str.contains("dim") {
+        modifiers |= Modifier::DIM;
+    }
+    if color_str.contains("italic") {
+        modifiers |= Modifier::ITALIC;
+    }
 
     (color, modifiers)
 }
@@ -489,6 +547,14 @@ mod tests {
         assert!(modifiers.contains(Modifier::REVERSED));
     }
 
+    #[test]
+    fn test_process_color_string_dim_italic() {
+        let (color, modifiers) = process_color_string("dim italic red");
+        assert_eq!(color, "red");
+        assert!(modifiers.contains(Modifier::DIM));
+        assert!(modifiers.contains(Modifier::ITALIC));
+    }
+
     #[test]
     fn test_parse_color_rgb() {
         let color = parse_color("rgb123");
@@ -516,6 +582,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_themes() {
+        for theme in [Theme::Dark, Theme::Light] {
+            let styles = theme.styles();
+
+            assert!(styles.contains_key(&Mode::Home), "{theme:?}");
+            assert_ne!(
+                Style::default(),
+                styles[&Mode::Home]["paused"],
+                "{theme:?} paused"
+            );
+        }
+
+        let light: Config = toml::from_str(r#"theme = "light""#).unwrap();
+        assert_eq!(Theme::Light, light.theme);
+    }
+
+    #[test]
+    fn test_fill_styles() {
+        let parse = |styles| toml::from_str::<Config>(styles).unwrap().styles;
+
+        let mut styles = parse(
+            r#"[styles.Home]
+title = "red""#,
+        );
+        let theme = parse(
+            r#"[styles.Home]
+title = "green"
+live = "blue""#,
+        );
+        let defaults = parse(
+            r#"[styles.Home]
+title = "bold"
+live = "bold"
+fps = "dim""#,
+        );
+
+        styles.fill_from(&theme);
+        styles.fill_from(&defaults);
+
+        assert_eq!(parse_style("red"), styles[&Mode::Home]["title"]);
+        assert_eq!(parse_style("blue"), styles[&Mode::Home]["live"]);
+        assert_eq!(parse_style("dim"), styles[&Mode::Home]["fps"]);
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
@@ -4,28 +4,18 @@ use itertools::{
     Itertools,
     MinMaxResult::{MinMax, NoElements, OneElement},
 };
+
+use crate::palette::Shade;
+
 pub struct Gradient {
     inner: LinearGradient,
 }
 
 impl Gradient {
-    pub fn blue(values: &[u64]) -> Result<Self> {
-        let dark = Color::from_hsla(210.0, 1.0, 0.12, 1.0);
-        let light = Color::from_hsla(210.0, 1.0, 0.5, 1.0);
-
-        Self::new(dark, light, values)
-    }
-
-    pub fn green(values: &[u64]) -> Result<Self> {
-        let dark = Color::from_hsla(150.0, 1.0, 0.12, 1.0);
-        let light = Color::from_hsla(150.0, 1.0, 0.5, 1.0);
-
-        Self::new(dark, light, values)
-    }
-
-    pub fn red(values: &[u64]) -> Result<Self> {
-        let dark = Color::from_hsla(0.0, 0.5, 0.12, 1.0);
-        let light = Color::from_hsla(0.0, 0.5, 0.5, 1.0);
+    /// Gradient covering `values` from the dark to the light end of `shade`
+    pub fn shade(shade: &Shade, values: &[u64]) -> Result<Self> {
+        let dark = Color::from_hsla(shade.hue, shade.saturation, shade.dark, 1.0);
+        let light = Color::from_hsla(shade.hue, shade.saturation, shade.light, 1.0);
 
         Self::new(dark, light, values)
     }
@@ -48,13 +38,6 @@ impl Gradient {
         Ok(Self { inner })
     }
 
-    pub fn white(values: &[u64]) -> Result<Self> {
-        let dark = Color::from_hsla(0.0, 0.0, 0.25, 1.0);
-        let light = Color::from_hsla(0.0, 0.0, 0.75, 1.0);
-
-        Self::new(dark, light, values)
-    }
-
     fn new(dark: Color, light: Color, values: &[u64]) -> Result<Self> {
         let domain = domain(values);
         let inner = GradientBuilder::new()
@@ -69,6 +52,27 @@ impl Gradient {
         Ok(Self { inner })
     }
 
+    /// Lowest and highest value covered
+    pub fn domain(&self) -> (u64, u64) {
+        let (low, high) = self.inner.domain();
+
+        (low.round() as u64, high.round() as u64)
+    }
+
+    /// `count` evenly spaced colors from the lowest to the highest value
+    pub fn ramp(&self, count: usize) -> Vec<[u8; 3]> {
+        let (low, high) = self.inner.domain();
+        let step = (high - low) / count.saturating_sub(1).max(1) as f32;
+
+        (0..count)
+            .map(|index| {
+                let [r, g, b, _] = self.inner.at(low + step * index as f32).to_rgba8();
+
+                [r, g, b]
+            })
+            .collect()
+    }
+
     /// Look up a color in the gradient domain, use the background color if the value is 0.
     pub fn at(&self, value: u64) -> color_art::Color {
         let color = if value > 0 {
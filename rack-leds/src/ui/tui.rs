@@ -1,6 +1,7 @@
 #![allow(dead_code)] // Remove this once you start using the code
 
 use std::{
+    env,
     io::{stdout, Stdout},
     ops::{Deref, DerefMut},
     sync::{
@@ -30,6 +31,8 @@ use tokio::{
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, instrument};
 
+use crate::ui::Colors;
+
 #[derive(Clone, Debug, Deserialize, strum::IntoStaticStr, Serialize)]
 pub enum Event {
     Init,
@@ -76,6 +79,15 @@ impl Tui {
         })
     }
 
+    /// Colours the terminal supports, detected from the environment
+    pub fn color_support() -> Colors {
+        Colors::detect(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+            env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()),
+        )
+    }
+
     pub fn tick_rate(mut self, tick_rate: f64) -> Self {
         self.tick_rate = tick_rate;
         self
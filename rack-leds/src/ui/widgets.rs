@@ -1,5 +1,9 @@
 mod border;
-mod display;
+mod leds;
+mod legend;
+pub mod rack;
 
 pub use border::Border;
-pub use display::Display;
+pub use leds::Leds;
+pub use legend::Legend;
+pub use rack::Rack;
@@ -0,0 +1,62 @@
+use ratatui::{
+    prelude::{Buffer, Position, Rect},
+    widgets::Widget,
+};
+
+use crate::{ui::Colors, Framebuffer};
+
+/// A framebuffer drawn with the colours the terminal supports, one cell per pixel
+pub struct Leds<'a> {
+    framebuffer: &'a Framebuffer,
+    colors: Colors,
+}
+
+impl<'a> Leds<'a> {
+    pub fn new(framebuffer: &'a Framebuffer, colors: Colors) -> Self {
+        Self {
+            framebuffer,
+            colors,
+        }
+    }
+}
+
+impl Widget for Leds<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+
+        for Position { x, y } in area.positions() {
+            let Some(color) = self.framebuffer.get(x - area.x, y - area.y) else {
+                continue;
+            };
+
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                let (symbol, style) = self.colors.cell(color);
+
+                cell.set_char(symbol).set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::style::Color;
+
+    use super::*;
+
+    #[test]
+    fn render() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.set(1, 0, [255, 0, 0]);
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 2, 2));
+
+        Leds::new(&framebuffer, Colors::Ansi).render(Rect::new(0, 0, 2, 1), &mut buffer);
+        Leds::new(&framebuffer, Colors::Glyph).render(Rect::new(0, 1, 2, 1), &mut buffer);
+
+        assert_eq!(Color::Black, buffer[(0, 0)].fg);
+        assert_eq!(Color::LightRed, buffer[(1, 0)].fg);
+        assert_eq!(" ", buffer[(0, 1)].symbol());
+        assert_eq!("░", buffer[(1, 1)].symbol());
+    }
+}
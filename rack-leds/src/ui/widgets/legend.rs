@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use ratatui::{
+    prelude::{Buffer, Constraint, Layout, Rect},
+    text::Line,
+    widgets::Widget,
+};
+
+use crate::{ui::Colors, update::Scale, Palette, Update};
+
+/// Width of the name column
+const NAME_WIDTH: u16 = 12;
+
+/// Width of the colour ramp
+const RAMP_WIDTH: u16 = 12;
+
+/// What each colour means, from the gradients used to paint `updates`
+///
+/// Every device is scaled to its own values, so a scale has one row for each distinct range.
+pub struct Legend {
+    rows: Vec<Row>,
+    colors: Colors,
+}
+
+struct Row {
+    scale: Scale,
+    low: u64,
+    high: u64,
+    ramp: Vec<[u8; 3]>,
+}
+
+impl Legend {
+    pub fn new<'a>(
+        updates: impl IntoIterator<Item = &'a Update>,
+        palette: &Palette,
+        colors: Colors,
+    ) -> Result<Self> {
+        let mut ranges = BTreeMap::new();
+
+        for update in updates {
+            for (scale, gradient) in update.gradients(palette)? {
+                let (low, high) = gradient.domain();
+
+                ranges
+                    .entry((scale, low, high))
+                    .or_insert_with(|| gradient.ramp(RAMP_WIDTH.into()));
+            }
+        }
+
+        let rows = ranges
+            .into_iter()
+            .map(|((scale, low, high), ramp)| Row {
+                scale,
+                low,
+                high,
+                ramp,
+            })
+            .collect();
+
+        Ok(Self { rows, colors })
+    }
+
+    /// Lines needed to show every range
+    pub fn height(&self) -> u16 {
+        self.rows.len() as u16
+    }
+
+    fn range(row: &Row) -> String {
+        let range = format!(
+            "{} – {}",
+            row.scale.format(row.low),
+            row.scale.format(row.high)
+        );
+
+        if row.scale.is_change() {
+            format!("{range} change")
+        } else {
+            range
+        }
+    }
+}
+
+impl Widget for &Legend {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let areas = Layout::vertical(vec![Constraint::Length(1); self.rows.len()]).split(area);
+        let mut previous = None;
+
+        for (area, row) in areas.iter().zip(&self.rows) {
+            let [name, ramp, range] = Layout::horizontal([
+                Constraint::Length(NAME_WIDTH),
+                Constraint::Length(RAMP_WIDTH + 1),
+                Constraint::Fill(1),
+            ])
+            .areas(*area);
+
+            if previous != Some(row.scale) {
+                Line::raw(row.scale.name()).render(name, buf);
+            }
+            previous = Some(row.scale);
+
+            for (x, color) in (ramp.x..ramp.right()).zip(&row.ramp) {
+                if let Some(cell) = buf.cell_mut((x, ramp.y)) {
+                    let (symbol, style) = self.colors.cell(*color);
+
+                    cell.set_char(symbol).set_style(style);
+                }
+            }
+
+            Line::raw(Legend::range(row)).render(range, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        device::Id,
+        update::{AccessPoint, Switch},
+        Layout,
+    };
+
+    #[test]
+    fn new() {
+        let id: Id = serde_json::from_str("1").unwrap();
+        let updates = [
+            Update::Switch {
+                id,
+                device: Switch::new(vec![0, 1_000, 2_000], vec![0, 0, 0], vec![0, 0, 3_000]),
+                layout: Layout::SwitchEight,
+            },
+            Update::Switch {
+                id,
+                device: Switch::new(vec![500, 125_000, 0], vec![0, 0, 0], vec![0, 0, 0]),
+                layout: Layout::SwitchEight,
+            },
+        ];
+
+        let legend = Legend::new(&updates, &Palette::default(), Colors::Truecolor).unwrap();
+
+        let ranges: Vec<_> = legend
+            .rows
+            .iter()
+            .map(|row| (row.scale, Legend::range(row)))
+            .collect();
+        assert_eq!(
+            vec![
+                (Scale::Receive, "4.0Kb/s – 1.0Mb/s change".to_string()),
+                (Scale::Receive, "8.0Kb/s – 16Kb/s change".to_string()),
+                (Scale::Transmit, "0b/s – 8b/s change".to_string()),
+                (Scale::Poe, "0.00 A – 0.00 A".to_string()),
+                (Scale::Poe, "0.00 A – 3.00 A".to_string()),
+            ],
+            ranges
+        );
+        assert!(legend
+            .rows
+            .iter()
+            .all(|row| row.ramp.len() == RAMP_WIDTH as usize));
+
+        let access_point = Update::AccessPoint {
+            id,
+            device: AccessPoint::new(10, 50, 0, 0, 0, 1, 2, 0, 0, 0),
+            layout: Layout::AccessPoint,
+        };
+
+        let legend = Legend::new([&access_point], &Palette::default(), Colors::Truecolor).unwrap();
+
+        let utilization = legend
+            .rows
+            .iter()
+            .find(|row| row.scale == Scale::Utilization)
+            .unwrap();
+        assert_eq!("0% – 100%", Legend::range(utilization));
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+use ratatui::prelude::{Constraint, Layout, Position, Rect};
+
+use crate::{column::Slot, device::Id, Columns, Framebuffer, Palette, Update};
+
+/// Every device update arranged in columns as shown on the LED display
+pub struct Rack<'a> {
+    columns: &'a Columns,
+    palette: &'a Palette,
+    updates: &'a HashMap<Id, Update>,
+}
+
+impl<'a> Rack<'a> {
+    pub fn new(
+        columns: &'a Columns,
+        palette: &'a Palette,
+        updates: &'a HashMap<Id, Update>,
+    ) -> Self {
+        Self {
+            columns,
+            palette,
+            updates,
+        }
+    }
+
+    /// Draw every device in `area` of `framebuffer`
+    pub fn paint(&self, framebuffer: &mut Framebuffer, area: Rect) -> Result<()> {
+        for (area, update) in self.areas(area) {
+            update.paint(framebuffer, area, self.palette)?;
+        }
+
+        Ok(())
+    }
+
+    /// The device and port drawn at each position when the rack is drawn in `area`
+    pub fn ports(&self, area: Rect) -> HashMap<Position, (Id, usize)> {
+        self.areas(area)
+            .into_iter()
+            .flat_map(|(area, update)| {
+                let layout = update.layout();
+
+                (0..update.ports()).filter_map(move |port| {
+                    let (x, y) = layout.coordinate(port);
+                    let position = Position::new(area.x + x, area.y + y);
+
+                    area.contains(position)
+                        .then_some((position, (update.id(), port)))
+                })
+            })
+            .collect()
+    }
+
+    /// The area of each update within `area`
+    fn areas(&self, area: Rect) -> Vec<(Rect, &'a Update)> {
+        let widths: Vec<_> = self
+            .columns
+            .columns()
+            .map(|column| {
+                column
+                    .ids()
+                    .filter_map(|id| self.updates.get(&id))
+                    .map(|update| update.width())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let column_rects = Layout::horizontal(Constraint::from_lengths(widths)).split(area);
+
+        column_rects
+            .iter()
+            .zip(self.columns.columns())
+            .flat_map(|(area, column)| {
+                // Spacers take up rows, devices without an update take up nothing
+                let slots: Vec<_> = column
+                    .slots()
+                    .iter()
+                    .filter_map(|slot| match slot {
+                        Slot::Device(id) => self
+                            .updates
+                            .get(id)
+                            .map(|update| (update.height(), Some(update))),
+                        Slot::Spacer(height) => Some((*height, None)),
+                    })
+                    .collect();
+
+                let heights: Vec<_> = slots.iter().map(|(height, _)| *height).collect();
+
+                let layout = Layout::vertical(heights).split(*area);
+
+                layout
+                    .iter()
+                    .zip(slots)
+                    .filter_map(|(area, (_, update))| {
+                        let update = update?;
+
+                        let [area] = Layout::horizontal([update.width()]).split(*area)[..] else {
+                            unreachable!("Constraints removed from layout");
+                        };
+
+                        Some((area, update))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{update::Switch, Column};
+
+    #[test]
+    fn ports() {
+        let first: Id = serde_json::from_str("1").unwrap();
+        let second: Id = serde_json::from_str("2").unwrap();
+
+        let columns = Columns::new(vec![Column::new(vec![first, second])]);
+        let palette = Palette::default();
+        let updates = HashMap::from([
+            (
+                first,
+                Update::Switch {
+                    id: first,
+                    device: Switch::zero(18),
+                    layout: crate::Layout::SwitchSixteenPlusTwo,
+                },
+            ),
+            (
+                second,
+                Update::Switch {
+                    id: second,
+                    device: Switch::zero(5),
+                    layout: crate::Layout::SwitchFive,
+                },
+            ),
+        ]);
+
+        let ports = Rack::new(&columns, &palette, &updates).ports(Rect::new(1, 1, 10, 3));
+
+        assert_eq!(23, ports.len());
+        assert_eq!(Some(&(first, 0)), ports.get(&Position::new(1, 2)));
+        assert_eq!(Some(&(first, 1)), ports.get(&Position::new(1, 1)));
+        assert_eq!(Some(&(first, 17)), ports.get(&Position::new(10, 1)));
+        assert_eq!(None, ports.get(&Position::new(9, 1)));
+        assert_eq!(Some(&(second, 4)), ports.get(&Position::new(5, 3)));
+    }
+
+    #[test]
+    fn spacer() {
+        let first: Id = serde_json::from_str("1").unwrap();
+        let second: Id = serde_json::from_str("2").unwrap();
+
+        let columns = Columns::new(vec![Column::with_slots(vec![
+            Slot::Device(first),
+            Slot::Spacer(1),
+            Slot::Device(second),
+        ])]);
+        let palette = Palette::default();
+        let updates = HashMap::from([
+            (
+                first,
+                Update::Switch {
+                    id: first,
+                    device: Switch::zero(18),
+                    layout: crate::Layout::SwitchSixteenPlusTwo,
+                },
+            ),
+            (
+                second,
+                Update::Switch {
+                    id: second,
+                    device: Switch::zero(5),
+                    layout: crate::Layout::SwitchFive,
+                },
+            ),
+        ]);
+
+        let ports = Rack::new(&columns, &palette, &updates).ports(Rect::new(1, 1, 10, 4));
+
+        assert_eq!(None, ports.get(&Position::new(5, 3)));
+        assert_eq!(Some(&(second, 4)), ports.get(&Position::new(5, 4)));
+    }
+}
@@ -0,0 +1,76 @@
+//! Human readable quantities
+
+use std::time::{Duration, SystemTime};
+
+use time::OffsetDateTime;
+
+use crate::brightness;
+
+/// Short SI text for a number of bits
+pub fn bits(bits: u64) -> String {
+    const PREFIXES: [(u64, &str); 3] = [(1_000_000_000, "G"), (1_000_000, "M"), (1_000, "K")];
+
+    let Some((scale, prefix)) = PREFIXES.iter().find(|(scale, _)| bits >= *scale) else {
+        return format!("{bits}");
+    };
+
+    let value = bits as f64 / *scale as f64;
+
+    if value < 10.0 {
+        format!("{value:.1}{prefix}")
+    } else {
+        format!("{value:.0}{prefix}")
+    }
+}
+
+/// Amperes to two decimal places from milliamps
+pub fn amps(milliamps: u64) -> String {
+    format!("{:.2} A", milliamps as f64 / 1000.0)
+}
+
+/// Local time of day as `HH:MM:SS`
+pub fn clock(time: SystemTime) -> String {
+    let time = OffsetDateTime::from(time).to_offset(brightness::local_offset_at(time));
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Milliseconds under a second, otherwise seconds
+pub fn duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.1} s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    #[test]
+    fn amps() {
+        assert_eq!("0.00 A", super::amps(0));
+        assert_eq!("0.25 A", super::amps(250));
+        assert_eq!("1.50 A", super::amps(1_500));
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!("999", super::bits(999));
+        assert_eq!("1.5K", super::bits(1_500));
+        assert_eq!("12M", super::bits(12_345_678));
+        assert_eq!("2.0G", super::bits(2_000_000_000));
+    }
+
+    #[test]
+    fn duration() {
+        assert_eq!("45 ms", super::duration(Duration::from_micros(45_900)));
+        assert_eq!("2.5 s", super::duration(Duration::from_millis(2_500)));
+    }
+}
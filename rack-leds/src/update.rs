@@ -2,11 +2,14 @@ mod access_point;
 mod switch;
 
 pub use access_point::AccessPoint;
+use eyre::Result;
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
 pub use switch::Switch;
 
-use crate::{device::Id, Layout};
+use crate::{device::Id, ui::Gradient, units, Framebuffer, Layout, Palette};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Update {
     AccessPoint {
         id: Id,
@@ -32,7 +35,16 @@ impl Update {
         }
     }
 
-    fn layout(&self) -> Layout {
+    /// The same update for device `id`
+    pub fn with_id(mut self, new: Id) -> Self {
+        match &mut self {
+            Update::AccessPoint { id, .. } | Update::Switch { id, .. } => *id = new,
+        }
+
+        self
+    }
+
+    pub fn layout(&self) -> Layout {
         match self {
             Update::AccessPoint { layout, .. } => *layout,
             Update::Switch { layout, .. } => *layout,
@@ -43,11 +55,281 @@ impl Update {
         self.layout().width()
     }
 
-    pub fn x_bound(&self) -> f64 {
-        self.layout().x_bound()
+    /// Count of pixels drawn for the device
+    pub fn ports(&self) -> usize {
+        match self {
+            Update::AccessPoint { device, .. } => {
+                device.receive().len()
+                    + device.channel_utilization().len()
+                    + device.stations().len()
+            }
+            Update::Switch { device, .. } => device.receive().len(),
+        }
+    }
+
+    /// Receive and transmit octets per second of each port as collected
+    ///
+    /// The values drawn are the change in these rates since the previous update.
+    pub fn rates(&self) -> (&[u64], &[u64]) {
+        match self {
+            Update::AccessPoint { device, .. } => (device.receive_rate(), device.transmit_rate()),
+            Update::Switch { device, .. } => (device.receive_rate(), device.transmit_rate()),
+        }
+    }
+
+    /// Values of `port` as collected, with the scale each is drawn with
+    pub fn values(&self, port: usize) -> Vec<(Scale, u64)> {
+        let (receive, transmit) = self.rates();
+        let value = |values: &[u64], index: usize| values.get(index).copied().unwrap_or_default();
+
+        match self {
+            Update::AccessPoint { device, .. } => {
+                let rates = device.receive().len();
+                let utilization = device.channel_utilization();
+
+                match port {
+                    _ if port < rates => vec![
+                        (Scale::Receive, value(receive, port)),
+                        (Scale::Transmit, value(transmit, port)),
+                    ],
+                    _ if port < rates + utilization.len() => {
+                        vec![(Scale::Utilization, value(&utilization, port - rates))]
+                    }
+                    _ => vec![(
+                        Scale::Stations,
+                        value(&device.stations(), port - rates - utilization.len()),
+                    )],
+                }
+            }
+            Update::Switch { device, .. } => vec![
+                (Scale::Receive, value(receive, port)),
+                (Scale::Transmit, value(transmit, port)),
+                (Scale::Poe, value(device.poe(), port)),
+            ],
+        }
+    }
+
+    /// Names and values shown for `port`, with rates as collected rather than as drawn
+    pub fn details(&self, port: usize) -> Vec<(&'static str, String)> {
+        let (receive, transmit) = self.rates();
+        let rate = |rates: &[u64]| {
+            let bits = rates.get(port).copied().unwrap_or_default() * 8;
+
+            format!("{bits} bit/s ({})", units::bits(bits))
+        };
+
+        match self {
+            Update::AccessPoint { device, .. } => {
+                let rates = device.receive().len();
+                let utilization = device.channel_utilization().len();
+                let radio = |index| if index == 0 { "2.4 GHz" } else { "5 GHz" };
+
+                match port {
+                    _ if port < rates => vec![
+                        ("port", ["AP", "WAN 2.4 GHz", "WAN 5 GHz"][port].to_string()),
+                        ("receive", rate(receive)),
+                        ("transmit", rate(transmit)),
+                    ],
+                    _ if port < rates + utilization => vec![
+                        ("radio", radio(port - rates).to_string()),
+                        (
+                            "utilization",
+                            format!("{}%", device.channel_utilization()[port - rates]),
+                        ),
+                    ],
+                    _ => {
+                        let index = port - rates - utilization;
+
+                        vec![
+                            ("radio", radio(index).to_string()),
+                            (
+                                "stations",
+                                device
+                                    .stations()
+                                    .get(index)
+                                    .map(u64::to_string)
+                                    .unwrap_or_default(),
+                            ),
+                        ]
+                    }
+                }
+            }
+            Update::Switch { device, .. } => {
+                vec![
+                    ("port", (port + 1).to_string()),
+                    ("receive", rate(receive)),
+                    ("transmit", rate(transmit)),
+                    (
+                        "PoE",
+                        units::amps(device.poe().get(port).copied().unwrap_or_default()),
+                    ),
+                ]
+            }
+        }
+    }
+
+    /// The gradient of each scale drawn for the device, as used by [`Self::paint`]
+    pub fn gradients(&self, palette: &Palette) -> Result<Vec<(Scale, Gradient)>> {
+        let gradients = match self {
+            Update::AccessPoint { device, .. } => access_point_gradients(device, palette)?.into(),
+            Update::Switch { device, .. } => switch_gradients(device, palette)?.into(),
+        };
+
+        Ok(gradients)
+    }
+
+    /// Draw each port in `area` with colors from `palette`
+    pub fn paint(
+        &self,
+        framebuffer: &mut Framebuffer,
+        area: Rect,
+        palette: &Palette,
+    ) -> Result<()> {
+        match self {
+            Update::AccessPoint {
+                device: access_point,
+                layout,
+                ..
+            } => {
+                let [(_, receive), (_, transmit), (_, utilization), (_, stations)] =
+                    access_point_gradients(access_point, palette)?;
+
+                let gradients = access_point::Gradients {
+                    receive,
+                    transmit,
+                    utilization,
+                    stations,
+                };
+
+                access_point.paint(framebuffer, area, *layout, &gradients);
+            }
+            Update::Switch {
+                device: switch,
+                layout,
+                ..
+            } => {
+                let [(_, recv_gradient), (_, tmit_gradient), (_, poe_gradient)] =
+                    switch_gradients(switch, palette)?;
+
+                switch.paint(
+                    framebuffer,
+                    area,
+                    *layout,
+                    &recv_gradient,
+                    &tmit_gradient,
+                    &poe_gradient,
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What the colour of a pixel shows
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Scale {
+    Receive,
+    Transmit,
+    Poe,
+    Utilization,
+    Stations,
+}
+
+impl Scale {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scale::Receive => "receive",
+            Scale::Transmit => "transmit",
+            Scale::Poe => "PoE",
+            Scale::Utilization => "utilization",
+            Scale::Stations => "stations",
+        }
+    }
+
+    /// Whether the LEDs show the change in this rate between updates rather than the rate
+    pub fn is_change(self) -> bool {
+        matches!(self, Scale::Receive | Scale::Transmit)
+    }
+
+    /// `value` in human units, rates are collected in octets per second and PoE in milliamps
+    pub fn format(self, value: u64) -> String {
+        match self {
+            Scale::Receive | Scale::Transmit => format!("{}b/s", units::bits(value * 8)),
+            Scale::Poe => units::amps(value),
+            Scale::Utilization => format!("{value}%"),
+            Scale::Stations if value == 1 => "1 client".to_string(),
+            Scale::Stations => format!("{value} clients"),
+        }
+    }
+}
+
+fn access_point_gradients(
+    access_point: &AccessPoint,
+    palette: &Palette,
+) -> Result<[(Scale, Gradient); 4]> {
+    Ok([
+        (
+            Scale::Receive,
+            Gradient::shade(&palette.receive, &access_point.receive())?,
+        ),
+        (
+            Scale::Transmit,
+            Gradient::shade(&palette.transmit, &access_point.transmit())?,
+        ),
+        (Scale::Utilization, Gradient::percent_gyrr()?),
+        (
+            Scale::Stations,
+            Gradient::shade(&palette.stations, &access_point.stations())?,
+        ),
+    ])
+}
+
+fn switch_gradients(switch: &Switch, palette: &Palette) -> Result<[(Scale, Gradient); 3]> {
+    Ok([
+        (
+            Scale::Receive,
+            Gradient::shade(&palette.receive, switch.receive())?,
+        ),
+        (
+            Scale::Transmit,
+            Gradient::shade(&palette.transmit, switch.transmit())?,
+        ),
+        (Scale::Poe, Gradient::shade(&palette.poe, switch.poe())?),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn details() {
+        let id: Id = serde_json::from_str("1").unwrap();
+        let update = Update::Switch {
+            id,
+            device: Switch::new(vec![0, 0], vec![0, 0], vec![0, 1_250])
+                .with_rates(vec![0, 1_500], vec![0, 25]),
+            layout: Layout::SwitchEight,
+        };
+
+        assert_eq!(
+            vec![
+                ("port", "2".to_string()),
+                ("receive", "12000 bit/s (12K)".to_string()),
+                ("transmit", "200 bit/s (200)".to_string()),
+                ("PoE", "1.25 A".to_string()),
+            ],
+            update.details(1)
+        );
     }
 
-    pub fn y_bound(&self) -> f64 {
-        self.layout().y_bound()
+    #[test]
+    fn scale_format() {
+        assert_eq!("12Mb/s", Scale::Receive.format(1_500_000));
+        assert_eq!("2.00 A", Scale::Poe.format(2_000));
+        assert_eq!("40%", Scale::Utilization.format(40));
+        assert_eq!("1 client", Scale::Stations.format(1));
+        assert_eq!("3 clients", Scale::Stations.format(3));
     }
 }
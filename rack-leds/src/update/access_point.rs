@@ -1,13 +1,19 @@
 use color_art::BlendMode;
 use itertools::multizip;
-use ratatui::{
-    style::Color,
-    widgets::canvas::{Context, Points},
-};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
 
-use crate::ui::Gradient;
+use crate::{ui::Gradient, Framebuffer, Layout};
 
-#[derive(Clone, Debug)]
+/// Colours for each kind of pixel an access point draws
+pub struct Gradients {
+    pub receive: Gradient,
+    pub transmit: Gradient,
+    pub utilization: Gradient,
+    pub stations: Gradient,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AccessPoint {
     channel_utilization_24_ghz: u64,
     channel_utilization_5_ghz: u64,
@@ -19,6 +25,14 @@ pub struct AccessPoint {
     transmit_ap: u64,
     transmit_wan_24_ghz: u64,
     transmit_wan_5_ghz: u64,
+    /// Receive octets per second as collected, the receive fields are the change since the last
+    /// update
+    #[serde(default)]
+    receive_rate: Vec<u64>,
+    /// Transmit octets per second as collected, the transmit fields are the change since the last
+    /// update
+    #[serde(default)]
+    transmit_rate: Vec<u64>,
 }
 
 impl AccessPoint {
@@ -46,9 +60,18 @@ impl AccessPoint {
             transmit_ap,
             transmit_wan_24_ghz,
             transmit_wan_5_ghz,
+            receive_rate: vec![],
+            transmit_rate: vec![],
         }
     }
 
+    /// Set the AP, WAN 2.4 GHz and WAN 5 GHz receive and transmit rates as collected
+    pub fn with_rates(mut self, receive: [u64; 3], transmit: [u64; 3]) -> Self {
+        self.receive_rate = receive.into();
+        self.transmit_rate = transmit.into();
+        self
+    }
+
     pub fn channel_utilization(&self) -> Vec<u64> {
         vec![
             self.channel_utilization_24_ghz,
@@ -58,27 +81,23 @@ impl AccessPoint {
 
     pub fn paint(
         &self,
-        context: &mut Context<'_>,
-        layout: crate::Layout,
-        recv_gradient: &Gradient,
-        tmit_gradient: &Gradient,
-        util_gradient: &Gradient,
-        stations_gradient: &Gradient,
+        framebuffer: &mut Framebuffer,
+        area: Rect,
+        layout: Layout,
+        gradients: &Gradients,
     ) {
         multizip((self.receive().iter(), self.transmit().iter()))
             .enumerate()
             .for_each(|(index, (recv, tmit))| {
-                let coords = &[layout.coordinate(index)];
+                let (x, y) = layout.coordinate(index);
 
                 let mixed = color_art::blend(
-                    &recv_gradient.at(*recv),
-                    &tmit_gradient.at(*tmit),
+                    &gradients.receive.at(*recv),
+                    &gradients.transmit.at(*tmit),
                     BlendMode::Screen,
                 );
 
-                let color = Color::Rgb(mixed.red(), mixed.green(), mixed.blue());
-
-                context.draw(&Points { coords, color });
+                framebuffer.set_in(area, x, y, [mixed.red(), mixed.green(), mixed.blue()]);
             });
 
         let offset = self.receive().len();
@@ -87,12 +106,10 @@ impl AccessPoint {
             .iter()
             .enumerate()
             .for_each(|(index, util)| {
-                let coords = &[layout.coordinate(index + offset)];
-                let color = util_gradient.at(*util);
-
-                let color = Color::Rgb(color.red(), color.green(), color.blue());
+                let (x, y) = layout.coordinate(index + offset);
+                let color = gradients.utilization.at(*util);
 
-                context.draw(&Points { coords, color });
+                framebuffer.set_in(area, x, y, [color.red(), color.green(), color.blue()]);
             });
 
         let offset = offset + self.channel_utilization().len();
@@ -101,12 +118,10 @@ impl AccessPoint {
             .iter()
             .enumerate()
             .for_each(|(index, station)| {
-                let coords = &[layout.coordinate(index + offset)];
-                let color = stations_gradient.at(*station);
+                let (x, y) = layout.coordinate(index + offset);
+                let color = gradients.stations.at(*station);
 
-                let color = Color::Rgb(color.red(), color.green(), color.blue());
-
-                context.draw(&Points { coords, color });
+                framebuffer.set_in(area, x, y, [color.red(), color.green(), color.blue()]);
             });
     }
 
@@ -118,6 +133,10 @@ impl AccessPoint {
         ]
     }
 
+    pub fn receive_rate(&self) -> &Vec<u64> {
+        &self.receive_rate
+    }
+
     pub fn stations(&self) -> Vec<u64> {
         vec![self.stations_24_ghz, self.stations_5_ghz]
     }
@@ -129,4 +148,8 @@ impl AccessPoint {
             self.transmit_wan_5_ghz,
         ]
     }
+
+    pub fn transmit_rate(&self) -> &Vec<u64> {
+        &self.transmit_rate
+    }
 }
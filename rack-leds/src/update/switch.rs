@@ -1,17 +1,21 @@
 use color_art::BlendMode;
 use itertools::multizip;
-use ratatui::{
-    style::Color,
-    widgets::canvas::{Context, Points},
-};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
 
-use crate::{ui::Gradient, Layout};
+use crate::{ui::Gradient, Framebuffer, Layout};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Switch {
     receive: Vec<u64>,
     transmit: Vec<u64>,
     poe: Vec<u64>,
+    /// Receive octets per second as collected, `receive` is the change since the last update
+    #[serde(default)]
+    receive_rate: Vec<u64>,
+    /// Transmit octets per second as collected, `transmit` is the change since the last update
+    #[serde(default)]
+    transmit_rate: Vec<u64>,
 }
 
 impl Switch {
@@ -20,6 +24,8 @@ impl Switch {
             receive: vec![],
             transmit: vec![],
             poe: vec![],
+            receive_rate: vec![],
+            transmit_rate: vec![],
         }
     }
 
@@ -28,14 +34,25 @@ impl Switch {
             receive,
             transmit,
             poe,
+            receive_rate: vec![],
+            transmit_rate: vec![],
         }
     }
 
+    /// Set the receive and transmit rates as collected
+    pub fn with_rates(mut self, receive: Vec<u64>, transmit: Vec<u64>) -> Self {
+        self.receive_rate = receive;
+        self.transmit_rate = transmit;
+        self
+    }
+
     pub fn zero(len: usize) -> Self {
         Self {
             receive: vec![0; len],
             transmit: vec![0; len],
             poe: vec![0; len],
+            receive_rate: vec![0; len],
+            transmit_rate: vec![0; len],
         }
     }
 
@@ -49,7 +66,8 @@ impl Switch {
 
     pub fn paint(
         &self,
-        context: &mut Context,
+        framebuffer: &mut Framebuffer,
+        area: Rect,
         layout: Layout,
         recv_gradient: &Gradient,
         tmit_gradient: &Gradient,
@@ -58,7 +76,7 @@ impl Switch {
         multizip((self.receive.iter(), self.transmit.iter(), self.poe.iter()))
             .enumerate()
             .for_each(|(port, (recv, tmit, poe))| {
-                let coords = &[layout.coordinate(port)];
+                let (x, y) = layout.coordinate(port);
 
                 let mixed = color_art::blend(
                     &recv_gradient.at(*recv),
@@ -67,9 +85,7 @@ impl Switch {
                 );
                 let mixed = color_art::blend(&mixed, &poe_gradient.at(*poe), BlendMode::Screen);
 
-                let color = Color::Rgb(mixed.red(), mixed.green(), mixed.blue());
-
-                context.draw(&Points { coords, color });
+                framebuffer.set_in(area, x, y, [mixed.red(), mixed.green(), mixed.blue()]);
             });
     }
 
@@ -81,10 +97,18 @@ impl Switch {
         &self.receive
     }
 
+    pub fn receive_rate(&self) -> &Vec<u64> {
+        &self.receive_rate
+    }
+
     pub fn transmit(&self) -> &Vec<u64> {
         &self.transmit
     }
 
+    pub fn transmit_rate(&self) -> &Vec<u64> {
+        &self.transmit_rate
+    }
+
     pub fn width(&self) -> u16 {
         let width = match self.receive.len() {
             // 8 ports or less
@@ -370,7 +370,8 @@ fn add_scope<'a>(line: &mut Line<'a>, scope: &'a Scope, format: &FormatState) {
 
 const ERROR_STYLE: Style = Style::new().fg(Color::Red);
 const WARN_STYLE: Style = Style::new().fg(Color::Yellow);
-const INFO_STYLE: Style = Style::new().fg(Color::White);
+// The terminal foreground, so it is readable on dark and light backgrounds
+const INFO_STYLE: Style = Style::new();
 const DEBUG_STYLE: Style = Style::new().fg(Color::Blue);
 const TRACE_STYLE: Style = Style::new().fg(Color::Cyan);
 
@@ -18,6 +18,7 @@ impl Scope {
         self.fields.extend(other.fields);
     }
 
+    #[allow(dead_code)]
     pub fn field_names(&self) -> Keys<'_, &str, String> {
         self.fields.keys()
     }
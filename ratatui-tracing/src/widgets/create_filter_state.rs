@@ -9,6 +9,7 @@ use crate::{
 
 pub struct CreateFilterState {
     pub(crate) event: Arc<Event>,
+    #[allow(dead_code)]
     reloadable: Reloadable,
     selection: Selection,
     pub(crate) level: Level,
@@ -13,7 +13,9 @@ use crate::widgets::{CreateFilter, Detail, EventLogState};
 /// events and selecting an event for a detail view.
 pub struct EventLog<'a> {
     block: Option<Block<'a>>,
+    create_filter: CreateFilter<'a>,
     highlight_style: Style,
+    truncated_style: Style,
 }
 
 impl<'a> EventLog<'a> {
@@ -24,22 +26,40 @@ impl<'a> EventLog<'a> {
         self
     }
 
+    /// Set the widget used to create a filter from the selected event
+    pub fn create_filter(mut self, create_filter: CreateFilter<'a>) -> Self {
+        self.create_filter = create_filter;
+
+        self
+    }
+
     /// Set the highlight style for the selected log event
     pub fn highlight_style(mut self, highlight_style: impl Into<Style>) -> Self {
         self.highlight_style = highlight_style.into();
 
         self
     }
+
+    /// Set the style of the marker on events cut off at the top of the log
+    pub fn truncated_style(mut self, truncated_style: impl Into<Style>) -> Self {
+        self.truncated_style = truncated_style.into();
+
+        self
+    }
 }
 
 impl<'a> Default for EventLog<'a> {
-    /// The default `EventLog` uses a [`Color::DarkGray`] [`Self::highlight_style()`]
+    /// The default `EventLog` uses a [`Color::DarkGray`] [`Self::highlight_style()`] and a dim,
+    /// italic [`Self::truncated_style()`] on [`Color::DarkGray`]
     fn default() -> Self {
         let highlight_style = Style::default().bg(Color::DarkGray);
+        let truncated_style = Style::default().dim().italic().bg(Color::DarkGray);
 
         Self {
             block: None,
+            create_filter: CreateFilter::default(),
             highlight_style,
+            truncated_style,
         }
     }
 }
@@ -62,7 +82,7 @@ impl<'a> StatefulWidget for EventLog<'a> {
         if let Some(detail_state) = state.detail_state() {
             Detail::default().render(area, buf, detail_state);
         } else if let Some(create_filter_state) = state.create_filter_state() {
-            CreateFilter::default().render(area, buf, create_filter_state);
+            self.create_filter.render(area, buf, create_filter_state);
         } else {
             let selected = state.history().selected;
             let epoch = state.epoch();
@@ -115,12 +135,12 @@ impl<'a> StatefulWidget for EventLog<'a> {
                 event.render(event_area, buf);
 
                 if truncate {
-                    add_truncate(event_area, buf);
+                    add_truncate(event_area, buf, self.truncated_style);
 
                     break;
                 }
 
-                if selected.map_or(false, |s| s == i) {
+                if selected == Some(i) {
                     buf.set_style(event_area, self.highlight_style);
                 }
             }
@@ -146,10 +166,8 @@ impl<'a> StatefulWidget for EventLog<'a> {
     }
 }
 
-fn add_truncate(area: Rect, buf: &mut Buffer) {
-    let truncated = Line::from("[truncated]")
-        .right_aligned()
-        .style(Style::default().dim().italic().bg(Color::DarkGray));
+fn add_truncate(area: Rect, buf: &mut Buffer, style: Style) {
+    let truncated = Line::from("[truncated]").right_aligned().style(style);
     let [_, last_line] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
     let [_, truncated_area] = Layout::horizontal([
         Constraint::Fill(1),
@@ -11,6 +11,7 @@ use crate::widgets::{FilterEdit, FilterState};
 /// directives.
 pub struct Filter<'a> {
     block: Option<Block<'a>>,
+    filter_edit: FilterEdit<'a>,
     highlight_style: Style,
     highlight_symbol: String,
 }
@@ -23,6 +24,13 @@ impl<'a> Filter<'a> {
         self
     }
 
+    /// Set the widget used when adding or editing a directive, [`Self::block()`] replaces its block
+    pub fn filter_edit(mut self, filter_edit: FilterEdit<'a>) -> Self {
+        self.filter_edit = filter_edit;
+
+        self
+    }
+
     /// Set the highlight style for the selected filter directive
     pub fn highlight_style(mut self, highlight_style: impl Into<Style>) -> Self {
         self.highlight_style = highlight_style.into();
@@ -62,6 +70,7 @@ impl<'a> Default for Filter<'a> {
 
         Self {
             block: None,
+            filter_edit: FilterEdit::default(),
             highlight_style: highlight_stely,
             highlight_symbol,
         }
@@ -86,10 +95,13 @@ impl<'a> StatefulWidget for Filter<'a> {
         } else {
             let state = &mut state.filter_edit_state;
 
-            self.block
-                .map(|block| FilterEdit::default().block(block))
-                .unwrap_or_default()
-                .render(area, buf, state);
+            let filter_edit = if let Some(block) = self.block {
+                self.filter_edit.block(block)
+            } else {
+                self.filter_edit
+            };
+
+            filter_edit.render(area, buf, state);
         }
     }
 }
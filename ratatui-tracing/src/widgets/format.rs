@@ -58,7 +58,7 @@ impl<'a> Format<'a> {
         self
     }
 
-    fn rows(&self, rows: Vec<(&'static str, &'static str)>) -> Vec<Row> {
+    fn rows(&self, rows: Vec<(&'static str, &'static str)>) -> Vec<Row<'_>> {
         rows.into_iter()
             .map(|(name, value)| {
                 Row::new(vec![